@@ -0,0 +1,75 @@
+use anyhow::Result;
+use tdxhost_core::bios;
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+
+use crate::cli::{BiosArgs, BiosCommand, ChecklistArgs, OutputFormat};
+
+pub fn run(args: &BiosArgs, probe: &Probe) -> Result<()> {
+    match &args.cmd {
+        BiosCommand::Checklist(checklist_args) => checklist(checklist_args, probe),
+    }
+}
+
+fn checklist(args: &ChecklistArgs, probe: &Probe) -> Result<()> {
+    let checklist = bios::checklist(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&checklist)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    println!(
+        "{}",
+        tr_args(
+            "checklist-title",
+            &[
+                (
+                    "model",
+                    &checklist
+                        .hardware
+                        .model()
+                        .unwrap_or_else(|| unknown.clone())
+                ),
+                ("bios", &checklist.hardware.bios().unwrap_or(unknown)),
+            ]
+        )
+    );
+    println!();
+    if checklist.items.is_empty() {
+        println!("{}", tr("wizard-nothing-to-do"));
+        return Ok(());
+    }
+
+    // one heading per menu, in the order the checks first need it
+    let mut paths: Vec<&str> = vec![];
+    for item in &checklist.items {
+        if !paths.contains(&item.path) {
+            paths.push(item.path);
+        }
+    }
+    for path in paths {
+        println!("{}", path);
+        for item in checklist.items.iter().filter(|i| i.path == path) {
+            let key = if item.verify {
+                "checklist-verify"
+            } else {
+                "checklist-set"
+            };
+            println!(
+                "  [ ] {}",
+                tr_args(
+                    key,
+                    &[
+                        ("setting", item.setting),
+                        ("value", &item.value),
+                        ("check", item.check_id),
+                    ]
+                )
+            );
+        }
+        println!();
+    }
+    println!("{}", tr("checklist-reboot"));
+    Ok(())
+}
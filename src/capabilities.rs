@@ -0,0 +1,42 @@
+use anyhow::{anyhow, Result};
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::kvm::{self, TD_ATTRIBUTES, XFAM_FEATURES};
+
+use crate::cli::{CapabilitiesArgs, OutputFormat};
+
+pub fn run(args: &CapabilitiesArgs, probe: &Probe) -> Result<()> {
+    let caps = probe.kvm_tdx_capabilities().map_err(|e| {
+        anyhow!(tr_args(
+            "capabilities-unavailable",
+            &[("error", &e.to_string())]
+        ))
+    })?;
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&caps)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<18} {:#018x}  {}",
+        tr("capabilities-attributes"),
+        caps.supported_attrs,
+        kvm::decode(caps.supported_attrs, &TD_ATTRIBUTES).join(", ")
+    );
+    println!(
+        "{:<18} {:#018x}  {}",
+        tr("capabilities-xfam"),
+        caps.supported_xfam,
+        kvm::decode(caps.supported_xfam, &XFAM_FEATURES).join(", ")
+    );
+
+    println!("{}", tr("capabilities-cpuid"));
+    println!("  leaf       subleaf  eax        ebx        ecx        edx");
+    for entry in &caps.cpuid {
+        println!(
+            "  {:#010x} {:<8} {:08x}   {:08x}   {:08x}   {:08x}",
+            entry.leaf, entry.subleaf, entry.eax, entry.ebx, entry.ecx, entry.edx
+        );
+    }
+    Ok(())
+}
@@ -1,8 +1,45 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
+    /// Message language, e.g. zh-CN (defaults to LC_ALL/LC_MESSAGES/LANG)
+    #[arg(long, global = true, value_name = "LOCALE")]
+    pub lang: Option<String>,
+
+    /// Inspect the host described by a JSON fixture instead of this machine
+    #[arg(long, global = true, value_name = "FIXTURE")]
+    pub simulate: Option<PathBuf>,
+
+    /// Read the MSRs of every CPU instead of CPU 0 only
+    #[arg(long, global = true)]
+    pub all_cpus: bool,
+
+    /// Run inside a virtual machine too, where the host's MSRs, BIOS
+    /// settings and TDX module cannot be seen
+    #[arg(long, global = true)]
+    pub allow_guest: bool,
+
+    /// Read the BIOS settings tdxhost cannot verify on the host from this
+    /// BMC's Redfish service, e.g. https://bmc.example.com/
+    #[arg(long, global = true, value_name = "URL", requires = "redfish_user")]
+    pub redfish: Option<String>,
+
+    /// BMC user for --redfish, whose password is read from
+    /// TDXHOST_REDFISH_PASSWORD
+    #[arg(
+        long = "user",
+        global = true,
+        value_name = "USER",
+        requires = "redfish"
+    )]
+    pub redfish_user: Option<String>,
+
+    /// Accept the BMC's certificate without verifying it
+    #[arg(long, global = true, requires = "redfish")]
+    pub redfish_insecure: bool,
+
     #[command(subcommand)]
     pub cmd: TdxCommand,
 }
@@ -11,5 +48,422 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum TdxCommand {
     /// Probe system for TDX support
-    Ok,
+    Ok(OkArgs),
+    /// Interactive dashboard of the check tree
+    Tui,
+    /// Walk through failed BIOS settings and re-verify them one by one
+    Wizard,
+    /// List the BIOS settings to change
+    Bios(BiosArgs),
+    /// Apply automatable remediations for failing checks
+    Fix(FixArgs),
+    /// Manage the TDX module
+    Module(ModuleArgs),
+    /// Show the TD configurations KVM supports
+    Capabilities(CapabilitiesArgs),
+    /// Create and run a minimal TD to exercise the whole TDX stack
+    SmokeTest,
+    /// Check the kernel, TDX module, QEMU, libvirt and DCAP versions work
+    /// together
+    VerifyStack(VerifyStackArgs),
+    /// Inspect the TDs running on the host
+    Td(TdArgs),
+    /// Report how the memory encryption KeyIDs are split and used
+    Keys(KeysArgs),
+    /// Summarize the platform, e.g. for a support ticket
+    Info(InfoArgs),
+    /// Read the MSRs TDX depends on
+    Msr(MsrArgs),
+    /// Execute the CPUID leaves that enumerate TDX, SGX and TME
+    Cpuid(CpuidArgs),
+    /// Show the sockets, cores and NUMA nodes, and which sockets have TME
+    /// and TDX enabled
+    Topology(TopologyArgs),
+    /// Gather what a TDX support case needs into a tar.gz
+    Collect(CollectArgs),
+    /// Check many hosts over SSH
+    Fleet(FleetArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct OkArgs {
+    /// Where to report check results
+    #[arg(long, value_enum, default_value_t = LogTarget::Auto)]
+    pub log_target: LogTarget,
+
+    /// Report format
+    #[arg(long, value_enum, default_value_t = OkFormat::Text)]
+    pub format: OkFormat,
+
+    /// Write the JSON report or facts to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Sign the JSON report with this PEM private key, writing a detached
+    /// signature to <OUTPUT>.sig (verify with `openssl dgst -sha256 -verify`)
+    #[arg(long, value_name = "PATH", requires = "output")]
+    pub sign_key: Option<PathBuf>,
+
+    /// Write a shell script with the automatable fixes for failing checks
+    #[arg(long, value_name = "PATH")]
+    pub emit_fix_script: Option<PathBuf>,
+
+    /// Print the JSON Schema of the JSON report and exit
+    #[arg(long)]
+    pub json_schema: bool,
+
+    /// Print the hints of the checks, e.g. how to fix a failure, below
+    /// their results; JSON reports always include them
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Print the check dependency graph and exit, e.g. for
+    /// `dot -Tsvg -o checks.svg`
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    pub graph: Option<GraphFormat>,
+
+    /// Check this host over SSH instead, e.g. admin@node17, by running a
+    /// copy of tdxhost there (as root, through `sudo -n` if needed)
+    #[arg(
+        long,
+        value_name = "DESTINATION",
+        conflicts_with_all = ["emit_fix_script", "json_schema", "graph"]
+    )]
+    pub host: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct FixArgs {
+    /// Only fix the check with this id, e.g. kvm.tdx_param
+    #[arg(long, value_name = "ID")]
+    pub check: Option<String>,
+
+    /// Apply every remediation without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BiosArgs {
+    #[command(subcommand)]
+    pub cmd: BiosCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BiosCommand {
+    /// Print the BIOS menu paths and values the failing checks need, for
+    /// the system's vendor
+    Checklist(ChecklistArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ChecklistArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ModuleArgs {
+    #[command(subcommand)]
+    pub cmd: ModuleCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ModuleCommand {
+    /// Show what is known about the loaded TDX module
+    Info(ModuleInfoArgs),
+    /// Replace the running TDX module without stopping TDs
+    Update(ModuleUpdateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ModuleInfoArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct ModuleUpdateArgs {
+    /// TDX module image to install
+    #[arg(value_name = "IMAGE")]
+    pub image: PathBuf,
+
+    /// Update without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CapabilitiesArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct TdArgs {
+    #[command(subcommand)]
+    pub cmd: TdSubcommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TdSubcommand {
+    /// List the running TDs and the KeyIDs they use
+    List(TdListArgs),
+    /// Show the memory, vCPUs and KeyIDs of a running TD
+    Inspect(TdInspectArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TdListArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct TdInspectArgs {
+    /// PID of the TD's VMM process, or the TD's UUID
+    #[arg(value_name = "PID|UUID")]
+    pub target: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct KeysArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct InfoArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct MsrArgs {
+    #[command(subcommand)]
+    pub cmd: MsrCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MsrCommand {
+    /// Read one MSR
+    Read(MsrReadArgs),
+    /// Read every MSR tdxhost knows the fields of
+    Dump(MsrDumpArgs),
+}
+
+/// CPUs to read MSRs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuSelection {
+    All,
+    Cpu(u16),
+}
+
+impl std::str::FromStr for CpuSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(CpuSelection::All);
+        }
+        s.parse()
+            .map(CpuSelection::Cpu)
+            .map_err(|_| format!("{:?} is neither a CPU number nor \"all\"", s))
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct MsrReadArgs {
+    /// Index of the MSR in hex or decimal, or its name, e.g. 0x982 or
+    /// IA32_TME_ACTIVATE
+    #[arg(value_name = "MSR")]
+    pub msr: String,
+
+    /// Logical CPU to read the MSR on, or "all" for every online CPU
+    #[arg(long, value_name = "CPU|all", default_value = "0")]
+    pub cpu: CpuSelection,
+
+    /// Break the value down into the register's named fields
+    #[arg(long)]
+    pub decode: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct MsrDumpArgs {
+    /// Logical CPU to read the MSRs on, or "all" for every online CPU
+    #[arg(long, value_name = "CPU|all", default_value = "0")]
+    pub cpu: CpuSelection,
+
+    /// Break the values down into the registers' named fields
+    #[arg(long)]
+    pub decode: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct CpuidArgs {
+    #[command(subcommand)]
+    pub cmd: CpuidCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CpuidCommand {
+    /// Execute one leaf
+    Read(CpuidReadArgs),
+    /// Execute every leaf tdxhost knows the fields of, as far as the CPU
+    /// implements them
+    Dump(CpuidDumpArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CpuidReadArgs {
+    /// Leaf in hex or decimal, e.g. 0x12
+    #[arg(value_name = "LEAF")]
+    pub leaf: String,
+
+    /// Subleaf in hex or decimal
+    #[arg(long, value_name = "SUBLEAF", default_value = "0")]
+    pub subleaf: String,
+
+    /// Break the registers down into the leaf's named fields
+    #[arg(long)]
+    pub decode: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct CpuidDumpArgs {
+    /// Break the registers down into the leaves' named fields
+    #[arg(long)]
+    pub decode: bool,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct TopologyArgs {
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug)]
+pub struct CollectArgs {
+    /// Where to write the bundle, by default
+    /// tdxhost-support-<HOSTNAME>-<TIME>.tar.gz in the current directory
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct VerifyStackArgs {
+    /// Compatibility matrix to use instead of /etc/tdxhost/compat.toml or
+    /// the bundled one
+    #[arg(long, value_name = "PATH")]
+    pub matrix: Option<PathBuf>,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable report
+    Text,
+    /// Machine readable JSON report
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OkFormat {
+    /// Human readable report
+    Text,
+    /// Machine readable JSON report
+    Json,
+    /// Flat dictionary of tdxhost_* Ansible facts, e.g. for
+    /// /etc/ansible/facts.d; exits successfully whether or not the host is
+    /// ready, which the tdxhost_ready fact tells
+    AnsibleFacts,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FleetFormat {
+    /// Human readable summary, one line per host
+    Text,
+    /// Machine readable JSON report of every host
+    Json,
+    /// HTML page with a host by check matrix and each host's results
+    Html,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogTarget {
+    /// journald when running under systemd, stdout otherwise
+    Auto,
+    /// Human readable results on stdout
+    Stdout,
+    /// Structured journald entries (MESSAGE_ID, CHECK_ID, STATE, and HINTS
+    /// with --verbose)
+    Journald,
+}
+
+#[derive(Args, Debug)]
+pub struct FleetArgs {
+    #[command(subcommand)]
+    pub cmd: FleetCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FleetCommand {
+    /// Run `tdxhost ok` on every host of an inventory and report the
+    /// results side by side
+    Check(FleetCheckArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct FleetCheckArgs {
+    /// YAML file listing the SSH destinations under `hosts`
+    #[arg(long, value_name = "PATH")]
+    pub inventory: PathBuf,
+
+    /// Hosts to check at the same time
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    pub parallel: usize,
+
+    /// Report format
+    #[arg(long, value_enum, default_value_t = FleetFormat::Text)]
+    pub format: FleetFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
 }
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::support;
+
+use crate::cli::CollectArgs;
+
+pub fn run(args: &CollectArgs, probe: &Probe) -> Result<()> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let hostname = probe
+        .read_file("/proc/sys/kernel/hostname")
+        .map(|h| h.trim().to_string())
+        .unwrap_or_else(|_| "host".to_string());
+    // the archive's files are kept in a directory named like it
+    let name = format!("tdxhost-support-{}-{}", hostname, now);
+    let path = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", name)));
+
+    let bundle = support::collect(probe);
+
+    let display = path.display().to_string();
+    let file =
+        File::create(&path).with_context(|| tr_args("error-create", &[("path", &display)]))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for (file_name, contents) in &bundle.files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(now);
+        header.set_cksum();
+        archive
+            .append_data(
+                &mut header,
+                format!("{}/{}", name, file_name),
+                contents.as_slice(),
+            )
+            .with_context(|| tr_args("error-write", &[("path", &display)]))?;
+    }
+    archive
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .with_context(|| tr_args("error-write", &[("path", &display)]))?;
+
+    println!("{}", tr_args("collect-written", &[("path", &display)]));
+    if !bundle.missing.is_empty() {
+        println!(
+            "{}",
+            tr_args(
+                "collect-missing",
+                &[("count", &bundle.missing.len().to_string())]
+            )
+        );
+    }
+    println!("{}", tr("collect-review"));
+    Ok(())
+}
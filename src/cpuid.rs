@@ -0,0 +1,71 @@
+use anyhow::{anyhow, Result};
+use tdxhost_core::check::Probe;
+use tdxhost_core::cpuid::{self, Reading};
+use tdxhost_core::i18n::tr_args;
+
+use crate::cli::{CpuidArgs, CpuidCommand, OutputFormat};
+
+fn number(number: &str) -> Result<u32> {
+    cpuid::parse(number).ok_or_else(|| anyhow!(tr_args("cpuid-invalid", &[("number", number)])))
+}
+
+pub fn run(args: &CpuidArgs, probe: &Probe) -> Result<()> {
+    match &args.cmd {
+        CpuidCommand::Read(read_args) => {
+            let leaf = number(&read_args.leaf)?;
+            let subleaf = number(&read_args.subleaf)?;
+            let reading = cpuid::read(probe, leaf, subleaf, read_args.decode);
+            if read_args.format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&reading)?);
+            } else {
+                print_reading(&reading);
+            }
+        }
+        CpuidCommand::Dump(dump_args) => {
+            let readings = cpuid::dump(probe, dump_args.decode);
+            if dump_args.format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&readings)?);
+            } else {
+                for reading in &readings {
+                    print_reading(reading);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_reading(reading: &Reading) {
+    let mut line = format!(
+        "CPUID {:#x}.{}: eax={:#010x} ebx={:#010x} ecx={:#010x} edx={:#010x}",
+        reading.leaf, reading.subleaf, reading.eax, reading.ebx, reading.ecx, reading.edx
+    );
+    if let Some(name) = reading.name {
+        line += &format!(" ({})", name);
+    }
+    if let Some(signature) = &reading.signature {
+        line += &format!(" {:?}", signature);
+    }
+    println!("{}", line);
+
+    for field in &reading.fields {
+        let (low, high) = field.bits;
+        let bits = if low == high {
+            low.to_string()
+        } else {
+            format!("{}:{}", high, low)
+        };
+        let value = if low == high {
+            field.value.to_string()
+        } else {
+            format!("{} ({:#x})", field.value, field.value)
+        };
+        println!(
+            "    [{} {:>5}]  {:<32} {}",
+            field.register.name(),
+            bits,
+            field.name,
+            value
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use std::io::Write;
+use tdxhost_core::check::{Probe, TestState};
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::registry::Registry;
+use tdxhost_core::remediation::{fixable, Action};
+
+use crate::cli::FixArgs;
+
+/// Ask a yes/no question, defaulting to no.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    print!("  {} ", prompt);
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(matches!(line.trim(), "y" | "Y" | "yes"))
+}
+
+pub fn run(args: &FixArgs, probe: &Probe) -> Result<()> {
+    let registry = Registry::with_plugins(probe);
+    let groups = registry.evaluate(probe);
+    let mut candidates = vec![];
+    fixable(&groups.required, &mut candidates);
+    fixable(&groups.optional, &mut candidates);
+
+    if let Some(id) = &args.check {
+        candidates.retain(|node| node.id == id);
+        if candidates.is_empty() {
+            return Err(anyhow!(tr_args("fix-no-fix-for-check", &[("id", id)])));
+        }
+    }
+
+    if candidates.is_empty() {
+        println!("{}", tr("fix-nothing-to-do"));
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    let mut reboot_required = false;
+    for node in candidates {
+        let remediation = node.remediation.as_ref().unwrap();
+
+        println!();
+        println!("{} ({})", node.result.action.bold(), node.id);
+        println!("  {}", remediation.summary);
+        for action in &remediation.actions {
+            println!("    - {}", action.describe());
+        }
+
+        if !args.yes && !confirm(&tr("fix-prompt"))? {
+            continue;
+        }
+
+        match remediation.actions.iter().try_for_each(Action::apply) {
+            Ok(()) if remediation.reboot_required => {
+                reboot_required = true;
+                println!("  {}", tr("fix-applied-reboot").yellow());
+            }
+            Ok(()) => match registry.rerun(node.id, probe) {
+                Some(n) if n.result.state == TestState::Ok => {
+                    println!("  {}", tr("fix-applied-verified").green())
+                }
+                _ => println!("  {}", tr("fix-applied-unverified").yellow()),
+            },
+            Err(e) => {
+                failed += 1;
+                println!("  {}", format!("{:#}", e).red());
+            }
+        }
+    }
+
+    if reboot_required {
+        println!();
+        println!("{}", tr("fix-reboot-pending").yellow().bold());
+    }
+
+    if failed > 0 {
+        Err(anyhow!(tr_args(
+            "fix-failed",
+            &[("count", &failed.to_string())]
+        )))
+    } else {
+        Ok(())
+    }
+}
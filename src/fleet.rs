@@ -0,0 +1,354 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::Mutex;
+use tdxhost_core::check::TestState;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::report::{Report, ReportEntry};
+
+use crate::cli::{FleetArgs, FleetCheckArgs, FleetCommand, FleetFormat};
+use crate::remote;
+
+/// Hosts to check, e.g.
+///
+/// ```yaml
+/// hosts:
+///   - admin@node17
+///   - node18.example.com
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Inventory {
+    /// SSH destinations
+    hosts: Vec<String>,
+}
+
+/// The outcome of checking one host.
+#[derive(Debug, Serialize)]
+struct HostReport {
+    host: String,
+    /// Why the host could not be checked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<Report>,
+}
+
+impl HostReport {
+    fn ready(&self) -> bool {
+        self.report.as_ref().is_some_and(|r| r.ready)
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &ReportEntry> {
+        self.report
+            .iter()
+            .flat_map(|r| r.required.iter().chain(&r.optional))
+    }
+
+    fn state(&self, id: &str) -> Option<TestState> {
+        self.entries().find(|e| e.id == id).map(|e| e.result.state)
+    }
+}
+
+/// Result of `tdxhost fleet check --format json`
+#[derive(Debug, Serialize)]
+struct FleetReport {
+    /// Every check any host ran, in execution order
+    checks: Vec<String>,
+    hosts: Vec<HostReport>,
+}
+
+impl FleetReport {
+    fn new(hosts: Vec<HostReport>) -> Self {
+        let mut checks: Vec<String> = vec![];
+        for entry in hosts.iter().flat_map(HostReport::entries) {
+            if !checks.contains(&entry.id) {
+                checks.push(entry.id.clone());
+            }
+        }
+        Self { checks, hosts }
+    }
+}
+
+pub fn run(args: &FleetArgs, global_args: &[String]) -> Result<()> {
+    match &args.cmd {
+        FleetCommand::Check(check_args) => check(check_args, global_args),
+    }
+}
+
+/// Check the hosts, `parallel` at a time, in inventory order.
+fn check_hosts(hosts: &[String], parallel: usize, global_args: &[String]) -> Vec<HostReport> {
+    let next = Mutex::new(hosts.iter().enumerate());
+    let results: Mutex<Vec<Option<HostReport>>> = Mutex::new(hosts.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, hosts.len().max(1)) {
+            scope.spawn(|| loop {
+                let Some((i, host)) = next.lock().unwrap().next() else {
+                    break;
+                };
+                let (report, error) = match remote::ok(host, global_args) {
+                    Ok(report) => (Some(report), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                eprintln!(
+                    "{}",
+                    tr_args(
+                        if report.is_some() {
+                            "fleet-checked"
+                        } else {
+                            "fleet-failed"
+                        },
+                        &[("host", host)]
+                    )
+                );
+                results.lock().unwrap()[i] = Some(HostReport {
+                    host: host.clone(),
+                    error,
+                    report,
+                });
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+fn check(args: &FleetCheckArgs, global_args: &[String]) -> Result<()> {
+    let inventory = std::fs::read_to_string(&args.inventory).with_context(|| {
+        tr_args(
+            "error-read",
+            &[("path", &args.inventory.display().to_string())],
+        )
+    })?;
+    let inventory: Inventory = serde_yaml::from_str(&inventory).with_context(|| {
+        tr_args(
+            "fleet-inventory-invalid",
+            &[("path", &args.inventory.display().to_string())],
+        )
+    })?;
+
+    let report = FleetReport::new(check_hosts(&inventory.hosts, args.parallel, global_args));
+
+    let output = match args.format {
+        FleetFormat::Text => text(&report),
+        FleetFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        FleetFormat::Html => html(&report),
+    };
+    match &args.output {
+        Some(path) => std::fs::write(path, output)
+            .with_context(|| tr_args("error-write", &[("path", &path.display().to_string())]))?,
+        None => print!("{}", output),
+    }
+
+    let not_ready = report.hosts.iter().filter(|h| !h.ready()).count();
+    if not_ready > 0 {
+        return Err(anyhow!(tr_args(
+            "fleet-not-ready",
+            &[
+                ("count", &not_ready.to_string()),
+                ("total", &report.hosts.len().to_string())
+            ]
+        )));
+    }
+    Ok(())
+}
+
+/// The checks holding a host back.
+fn failing(host: &HostReport) -> Vec<&str> {
+    host.entries()
+        .filter(|e| e.result.state == TestState::Fail)
+        .map(|e| e.id.as_str())
+        .collect()
+}
+
+fn text(report: &FleetReport) -> String {
+    let width = report.hosts.iter().map(|h| h.host.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for host in &report.hosts {
+        let summary = match &host.error {
+            Some(error) => tr_args("fleet-host-error", &[("error", error)]),
+            None if host.ready() => tr("fleet-host-ready"),
+            None => tr_args(
+                "fleet-host-not-ready",
+                &[("checks", &failing(host).join(", "))],
+            ),
+        };
+        let _ = writeln!(out, "{:<width$}  {}", host.host, summary, width = width);
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Class and text of a matrix cell.
+fn cell(state: Option<TestState>) -> (&'static str, &'static str) {
+    match state {
+        Some(TestState::Ok) => ("ok", "OK"),
+        Some(TestState::Fail) => ("fail", "FAIL"),
+        Some(TestState::Warning) => ("warning", "WARN"),
+        Some(TestState::Tbd) => ("tbd", "TBD"),
+        Some(TestState::Skip) => ("skip", "SKIP"),
+        None => ("none", ""),
+    }
+}
+
+const STYLE: &str = "body { font-family: sans-serif; font-size: 13px; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: left; }
+th.check { writing-mode: vertical-rl; transform: rotate(180deg); }
+.ok { background: #c8e6c9; } .fail { background: #ffcdd2; }
+.warning { background: #ffe0b2; } .tbd, .skip { background: #fff9c4; }";
+
+fn html(report: &FleetReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>",
+        escape(&tr("fleet-title")),
+        STYLE
+    );
+    let _ = writeln!(out, "<h1>{}</h1>", escape(&tr("fleet-title")));
+
+    let _ = writeln!(out, "<table>\n<tr><th></th><th></th>");
+    for id in &report.checks {
+        let _ = writeln!(out, "<th class=\"check\">{}</th>", escape(id));
+    }
+    let _ = writeln!(out, "</tr>");
+    for (i, host) in report.hosts.iter().enumerate() {
+        let (class, ready) = match (&host.error, host.ready()) {
+            (Some(_), _) => ("fail", tr("fleet-unreachable")),
+            (None, true) => ("ok", tr("fleet-ready")),
+            (None, false) => ("fail", tr("fleet-not-ready-cell")),
+        };
+        let _ = write!(
+            out,
+            "<tr><td><a href=\"#host-{}\">{}</a></td><td class=\"{}\">{}</td>",
+            i,
+            escape(&host.host),
+            class,
+            escape(&ready)
+        );
+        for id in &report.checks {
+            let (class, text) = cell(host.state(id));
+            let _ = write!(out, "<td class=\"{}\">{}</td>", class, text);
+        }
+        let _ = writeln!(out, "</tr>");
+    }
+    let _ = writeln!(out, "</table>");
+
+    for (i, host) in report.hosts.iter().enumerate() {
+        let _ = writeln!(out, "<h2 id=\"host-{}\">{}</h2>", i, escape(&host.host));
+        if let Some(hardware) = host.report.as_ref().and_then(|r| r.hardware.model()) {
+            let _ = writeln!(out, "<p>{}</p>", escape(&hardware));
+        }
+        if let Some(error) = &host.error {
+            let _ = writeln!(out, "<p class=\"fail\">{}</p>", escape(error));
+            continue;
+        }
+        let _ = writeln!(out, "<table>");
+        for entry in host.entries() {
+            let (class, text) = cell(Some(entry.result.state));
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&entry.id),
+                class,
+                text,
+                escape(&entry.result.action),
+                escape(&entry.result.reason)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+    }
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tdxhost_core::check::CheckResult;
+
+    fn host(name: &str, states: &[(&str, TestState)]) -> HostReport {
+        let required: Vec<ReportEntry> = states
+            .iter()
+            .map(|&(id, state)| ReportEntry {
+                id: id.to_string(),
+                result: CheckResult {
+                    state,
+                    ..Default::default()
+                },
+            })
+            .collect();
+        HostReport {
+            host: name.to_string(),
+            error: None,
+            report: Some(Report {
+                hardware: Default::default(),
+                ready: required.iter().all(|e| e.result.state != TestState::Fail),
+                degraded: false,
+                required,
+                optional: vec![],
+            }),
+        }
+    }
+
+    #[test]
+    fn aggregated() {
+        let unreachable = HostReport {
+            host: "node3".to_string(),
+            error: Some("ssh: connect to host node3 port 22: Connection refused".to_string()),
+            report: None,
+        };
+        let report = FleetReport::new(vec![
+            host(
+                "node1",
+                &[("cpu.model", TestState::Ok), ("bios.tdx", TestState::Ok)],
+            ),
+            host(
+                "node2",
+                &[
+                    ("cpu.model", TestState::Ok),
+                    ("os.kernel", TestState::Fail),
+                    ("bios.tdx", TestState::Fail),
+                ],
+            ),
+            unreachable,
+        ]);
+
+        // every check any host ran, once, in the order they first ran
+        assert_eq!(report.checks, ["cpu.model", "bios.tdx", "os.kernel"]);
+        assert_eq!(failing(&report.hosts[1]), ["os.kernel", "bios.tdx"]);
+        assert_eq!(report.hosts[0].state("os.kernel"), None);
+        assert_eq!(report.hosts[1].state("bios.tdx"), Some(TestState::Fail));
+        assert!(report.hosts[0].ready());
+        assert!(!report.hosts[1].ready());
+        assert!(!report.hosts[2].ready());
+
+        let text = text(&report);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], format!("node1  {}", tr("fleet-host-ready")));
+        assert_eq!(
+            lines[1],
+            format!(
+                "node2  {}",
+                tr_args("fleet-host-not-ready", &[("checks", "os.kernel, bios.tdx")])
+            )
+        );
+        assert!(lines[2].starts_with("node3  "));
+
+        let html = html(&report);
+        assert_eq!(html.matches("<th class=\"check\">").count(), 3);
+        assert!(html.contains("<td class=\"fail\">FAIL</td>"));
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::info;
+use tdxhost_core::stack::Component;
+
+use crate::cli::{InfoArgs, OutputFormat};
+
+pub fn run(args: &InfoArgs, probe: &Probe) -> Result<()> {
+    let info = info::collect(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    let field = |name: &str, value: Option<String>| {
+        println!("{:<20} {}", name, value.as_deref().unwrap_or(&unknown));
+    };
+    let count = |count: Option<u64>| count.map_or_else(|| unknown.clone(), |c| c.to_string());
+
+    field(&tr("info-platform"), info.hardware.model());
+    field(&tr("info-board"), info.hardware.board.clone());
+    field(&tr("info-bios"), info.hardware.bios());
+    field(&tr("info-cpu"), Some(info.cpu.clone()));
+    field(&tr("info-sockets"), info.sockets.map(|s| s.to_string()));
+    field(&tr("info-microcode"), info.microcode.clone());
+    field(&tr("info-os"), info.os.clone());
+    field(
+        &tr("info-secure-boot"),
+        info.secure_boot.map(|enabled| {
+            tr(if enabled {
+                "info-enabled"
+            } else {
+                "info-disabled"
+            })
+        }),
+    );
+    field(
+        &tr("info-tme"),
+        info.tme_enabled.map(|enabled| {
+            tr(if enabled {
+                "info-enabled"
+            } else {
+                "info-disabled"
+            })
+        }),
+    );
+    field(
+        &tr("info-keyids"),
+        Some(tr_args(
+            "info-keyids-value",
+            &[
+                ("mktme", &count(info.keyids.mktme)),
+                ("tdx", &count(info.keyids.tdx)),
+                ("used", &info.keyids.in_use.to_string()),
+                ("capacity", &count(info.keyids.td_capacity)),
+            ],
+        )),
+    );
+    for installed in &info.components {
+        let version = installed.version.clone();
+        let version = match installed.component {
+            Component::TdxModule => version.map(|version| {
+                let id = if info.tdx_module_initialized {
+                    "info-module-initialized"
+                } else {
+                    "info-module-uninitialized"
+                };
+                tr_args(id, &[("version", &version)])
+            }),
+            _ => version,
+        };
+        field(&format!("{}:", installed.component.name()), version);
+    }
+    for service in &info.attestation_services {
+        field(
+            &format!("{}:", service.unit),
+            Some(
+                service
+                    .state
+                    .clone()
+                    .unwrap_or_else(|| tr("info-service-missing")),
+            ),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use std::os::unix::net::UnixDatagram;
+
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// MESSAGE_ID attached to every check result entry, so that
+/// `journalctl MESSAGE_ID=...` selects tdxhost results only.
+pub const CHECK_RESULT_MESSAGE_ID: &str = "f5eea670791241239dad5057ffd27285";
+
+/// systemd sets JOURNAL_STREAM when stdout/stderr of a unit are connected
+/// to the journal.
+pub fn connected() -> bool {
+    std::env::var_os("JOURNAL_STREAM").is_some() && std::path::Path::new(JOURNAL_SOCKET).exists()
+}
+
+/// Send a single structured entry using the native journal protocol.
+pub fn send(fields: &[(&str, &str)]) -> std::io::Result<()> {
+    let mut buffer: Vec<u8> = Vec::new();
+
+    for (key, value) in fields {
+        buffer.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            // multi-line values need the explicit length encoding
+            buffer.push(b'\n');
+            buffer.extend_from_slice(&(value.len() as u64).to_le_bytes());
+            buffer.extend_from_slice(value.as_bytes());
+        } else {
+            buffer.push(b'=');
+            buffer.extend_from_slice(value.as_bytes());
+        }
+        buffer.push(b'\n');
+    }
+
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to(&buffer, JOURNAL_SOCKET)?;
+    Ok(())
+}
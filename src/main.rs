@@ -1,18 +1,150 @@
+mod bios;
+mod capabilities;
 mod cli;
+mod collect;
+mod cpuid;
+mod fix;
+mod fleet;
+mod info;
+mod journal;
+mod module;
+mod msr;
 mod ok;
+mod remote;
+mod sign;
+mod smoke_test;
+mod td;
+mod topology;
+mod tui;
+mod verify_stack;
+mod wizard;
 
+use anyhow::anyhow;
 use clap::Parser;
+use tdxhost_core::check::Probe;
+use tdxhost_core::config::Config;
+use tdxhost_core::error::TdxHostError;
+use tdxhost_core::i18n::{self, tr, tr_args};
+use tdxhost_core::platform::Fixture;
+use tdxhost_core::redfish::Redfish;
+
+/// Environment variable holding the BMC password for --redfish.
+const REDFISH_PASSWORD_ENV: &str = "TDXHOST_REDFISH_PASSWORD";
 
 fn main() -> anyhow::Result<()> {
     let args = cli::Cli::parse();
+    i18n::init(args.lang.as_deref());
 
-    let res = match args.cmd {
-        cli::TdxCommand::Ok => ok::run_all_checks(),
-    };
+    let res = run(&args);
 
     if let Err(ref e) = res {
         eprintln!("Error: {}", e);
+        // the host could not be fully inspected, which is not a verdict
+        if let Some(TdxHostError::PermissionDenied(_)) = e.downcast_ref() {
+            std::process::exit(2);
+        }
     }
 
     res
 }
+
+/// The global options a copy of tdxhost on another host runs with.
+fn remote_args(args: &cli::Cli) -> Vec<String> {
+    let mut remote = vec![];
+    if let Some(lang) = &args.lang {
+        remote.extend(["--lang".to_string(), lang.clone()]);
+    }
+    if args.all_cpus {
+        remote.push("--all-cpus".to_string());
+    }
+    if args.allow_guest {
+        remote.push("--allow-guest".to_string());
+    }
+    remote
+}
+
+fn run(args: &cli::Cli) -> anyhow::Result<()> {
+    let config = Config::load()?;
+    let mut probe = match &args.simulate {
+        Some(path) => Probe::new(Fixture::load(path)?).with_config(config),
+        // the user to check /dev/kvm access for, on this host only
+        None => Probe::host(config).with_sudo_user(std::env::var("SUDO_USER").ok()),
+    }
+    .every_cpu(args.all_cpus);
+    if let (Some(url), Some(user)) = (&args.redfish, &args.redfish_user) {
+        // kept out of the command line, which other users can see
+        let password = std::env::var(REDFISH_PASSWORD_ENV).map_err(|_| {
+            anyhow!(tr_args(
+                "redfish-no-password",
+                &[("variable", REDFISH_PASSWORD_ENV)]
+            ))
+        })?;
+        probe =
+            probe.with_bios_settings(Redfish::new(url, user, &password, args.redfish_insecure)?);
+    }
+    // the host's checks make no sense from a guest, its CPUID still tells
+    let guest_ok = args.allow_guest
+        || matches!(args.cmd, cli::TdxCommand::Cpuid(_))
+        // checking other hosts, this one need not be one
+        || matches!(args.cmd, cli::TdxCommand::Ok(ref ok_args) if ok_args.host.is_some())
+        || matches!(args.cmd, cli::TdxCommand::Fleet(_));
+    if let Some(hypervisor) = probe.hypervisor().filter(|_| !guest_ok) {
+        let name = hypervisor
+            .name()
+            .map_or_else(|| tr("module-unknown"), str::to_string);
+        let key = if hypervisor.is_td() {
+            "guest-detected-td"
+        } else if hypervisor.nested_vmx {
+            "guest-detected-nested"
+        } else {
+            "guest-detected"
+        };
+        return Err(anyhow!(tr_args(key, &[("hypervisor", &name)])));
+    }
+    if !probe.privileged() {
+        eprintln!("{}", tr("warning-unprivileged"));
+    }
+    if let Some(mode) = probe.lockdown() {
+        eprintln!("{}", tr_args("warning-lockdown", &[("mode", &mode)]));
+    }
+
+    match args.cmd {
+        cli::TdxCommand::Ok(cli::OkArgs {
+            host: Some(ref host),
+            ..
+        }) if args.simulate.is_some() => {
+            Err(anyhow!(tr_args("ok-host-simulated", &[("host", host)])))
+        }
+        cli::TdxCommand::Ok(ref ok_args) => match &ok_args.host {
+            Some(host) => ok::run_remote(ok_args, host, &remote_args(args)),
+            None => ok::run_all_checks(ok_args, &probe),
+        },
+        cli::TdxCommand::Tui => tui::run(probe),
+        cli::TdxCommand::Wizard => wizard::run(&probe),
+        cli::TdxCommand::Bios(ref bios_args) => bios::run(bios_args, &probe),
+        // remediations change the real host, not the fixture
+        cli::TdxCommand::Fix(_) if args.simulate.is_some() => Err(anyhow!(tr("fix-simulated"))),
+        cli::TdxCommand::Fix(ref fix_args) => fix::run(fix_args, &probe),
+        cli::TdxCommand::Module(cli::ModuleArgs {
+            cmd: cli::ModuleCommand::Update(_),
+        }) if args.simulate.is_some() => Err(anyhow!(tr("module-update-simulated"))),
+        cli::TdxCommand::Module(ref module_args) => module::run(module_args, &probe),
+        cli::TdxCommand::Capabilities(ref capabilities_args) => {
+            capabilities::run(capabilities_args, &probe)
+        }
+        cli::TdxCommand::SmokeTest if args.simulate.is_some() => {
+            Err(anyhow!(tr("smoke-test-simulated")))
+        }
+        cli::TdxCommand::SmokeTest => smoke_test::run(),
+        cli::TdxCommand::VerifyStack(ref verify_args) => verify_stack::run(verify_args, &probe),
+        cli::TdxCommand::Td(ref td_args) => td::run(td_args, &probe),
+        cli::TdxCommand::Keys(ref keys_args) => td::keys(keys_args, &probe),
+        cli::TdxCommand::Info(ref info_args) => info::run(info_args, &probe),
+        cli::TdxCommand::Msr(ref msr_args) => msr::run(msr_args, &probe),
+        cli::TdxCommand::Cpuid(ref cpuid_args) => cpuid::run(cpuid_args, &probe),
+        cli::TdxCommand::Topology(ref topology_args) => topology::run(topology_args, &probe),
+        cli::TdxCommand::Collect(ref collect_args) => collect::run(collect_args, &probe),
+        cli::TdxCommand::Fleet(_) if args.simulate.is_some() => Err(anyhow!(tr("fleet-simulated"))),
+        cli::TdxCommand::Fleet(ref fleet_args) => fleet::run(fleet_args, &remote_args(args)),
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use tdxhost_core::check::{Probe, TestState};
+use tdxhost_core::error::TdxHostError;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::module_info::ModuleInfo;
+use tdxhost_core::module_update;
+use tdxhost_core::registry::Registry;
+
+use crate::cli::{ModuleArgs, ModuleCommand, ModuleInfoArgs, ModuleUpdateArgs, OutputFormat};
+use crate::fix::confirm;
+
+/// Checks that must pass before the module can be replaced under running
+/// TDs.
+const PREFLIGHT_CHECKS: [&str; 2] = ["tdx.module", "tdx.runtime_update"];
+
+pub fn run(args: &ModuleArgs, probe: &Probe) -> Result<()> {
+    match &args.cmd {
+        ModuleCommand::Info(info_args) => info(info_args, probe),
+        ModuleCommand::Update(update_args) => update(update_args, probe),
+    }
+}
+
+fn info(args: &ModuleInfoArgs, probe: &Probe) -> Result<()> {
+    let info = ModuleInfo::collect(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    let field = |name: &str, value: Option<&str>| {
+        println!("{:<18} {}", tr(name), value.unwrap_or(&unknown));
+    };
+    field(
+        "module-info-initialized",
+        Some(&tr(if info.initialized {
+            "module-info-yes"
+        } else {
+            "module-info-no"
+        })),
+    );
+    field("module-info-version", info.version.as_deref());
+    field("module-info-build", info.build_num.as_deref());
+    field("module-info-build-date", info.build_date.as_deref());
+    field("module-info-attributes", info.attributes.as_deref());
+    let features = info.features.join(", ");
+    field(
+        "module-info-features",
+        info.features0
+            .as_ref()
+            .map(|f| format!("{} ({})", f, features))
+            .as_deref(),
+    );
+    field("module-info-seamldr", info.seamldr_version.as_deref());
+    let keyids = info.keyids.as_ref().map(|k| {
+        tr_args(
+            "module-info-keyids-split",
+            &[("mktme", &k.mktme.to_string()), ("tdx", &k.tdx.to_string())],
+        )
+    });
+    field("module-info-keyids", keyids.as_deref());
+    let pamt = info.pamt_kib.map(|kib| format!("{} MiB", kib >> 10));
+    field("module-info-pamt", pamt.as_deref());
+
+    println!("{}", tr("module-info-cmrs"));
+    for cmr in &info.cmrs {
+        println!(
+            "  [{:#x}, {:#x})  {} MiB",
+            cmr.start,
+            cmr.end,
+            (cmr.end - cmr.start) >> 20
+        );
+    }
+    if info.cmrs.is_empty() {
+        println!("  {}", unknown);
+    }
+    Ok(())
+}
+
+/// Version of the running module, as reported by the version check.
+fn module_version(registry: &Registry, probe: &Probe) -> Option<String> {
+    registry
+        .rerun("tdx.module_version", probe)?
+        .result
+        .evidence
+        .iter()
+        .find_map(|e| e.strip_prefix("version = ").map(str::to_owned))
+}
+
+fn update(args: &ModuleUpdateArgs, probe: &Probe) -> Result<()> {
+    if !probe.privileged() {
+        return Err(TdxHostError::PermissionDenied(tr("warning-unprivileged")).into());
+    }
+
+    let registry = Registry::builtin();
+    for id in PREFLIGHT_CHECKS {
+        let Some(node) = registry.rerun(id, probe) else {
+            continue;
+        };
+        if node.result.state != TestState::Ok {
+            return Err(anyhow!(tr_args(
+                "module-update-preflight-failed",
+                &[
+                    ("action", &node.result.action),
+                    ("reason", &node.result.reason)
+                ]
+            )));
+        }
+        println!("[ {} ] {}", "OK".green(), node.result.action);
+    }
+
+    let unknown = tr("module-unknown");
+    let before = module_version(&registry, probe);
+    println!(
+        "{}",
+        tr_args(
+            "module-update-current",
+            &[("version", before.as_deref().unwrap_or(&unknown))]
+        )
+    );
+
+    let image = args.image.display().to_string();
+    if !args.yes && !confirm(&tr_args("module-update-prompt", &[("image", &image)]))? {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        tr_args("module-update-installing", &[("image", &image)])
+    );
+    if let Err(e) = module_update::update(&args.image) {
+        eprintln!("{}", tr("module-update-rollback").yellow());
+        return Err(e.into());
+    }
+
+    let after = module_version(&registry, probe);
+    let after = after.as_deref().unwrap_or(&unknown);
+    match before {
+        Some(before) if before != after => println!(
+            "{}",
+            tr_args("module-update-done", &[("from", &before), ("to", after)]).green()
+        ),
+        _ => println!(
+            "{}",
+            tr_args("module-update-unchanged", &[("version", after)]).yellow()
+        ),
+    }
+    Ok(())
+}
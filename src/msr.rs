@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::msr::{self, Reading};
+
+use crate::cli::{CpuSelection, MsrArgs, MsrCommand, OutputFormat};
+
+pub fn run(args: &MsrArgs, probe: &Probe) -> Result<()> {
+    match &args.cmd {
+        MsrCommand::Read(read_args) => {
+            let index = msr::parse(&read_args.msr)
+                .ok_or_else(|| anyhow!(tr_args("msr-invalid", &[("msr", &read_args.msr)])))?;
+            let cpus = cpus(probe, read_args.cpu)?;
+            let reading = msr::read(probe, index, &cpus, read_args.decode)?;
+            if read_args.format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&reading)?);
+            } else {
+                print_reading(&reading, cpus.len());
+            }
+        }
+        MsrCommand::Dump(dump_args) => {
+            let cpus = cpus(probe, dump_args.cpu)?;
+            let readings = msr::dump(probe, &cpus, dump_args.decode)?;
+            if dump_args.format == OutputFormat::Json {
+                println!("{}", serde_json::to_string_pretty(&readings)?);
+            } else {
+                for reading in &readings {
+                    print_reading(reading, cpus.len());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cpus(probe: &Probe, selection: CpuSelection) -> Result<Vec<u16>> {
+    Ok(match selection {
+        CpuSelection::All => probe.online_cpus()?,
+        CpuSelection::Cpu(cpu) => vec![cpu],
+    })
+}
+
+fn print_reading(reading: &Reading, cpus_read: usize) {
+    let register = match reading.name {
+        Some(name) => format!("MSR {:#x} ({})", reading.index, name),
+        None => format!("MSR {:#x}", reading.index),
+    };
+    for value in &reading.values {
+        // the CPUs only matter when they disagree
+        if cpus_read > 1 && reading.values.len() > 1 {
+            let cpus: Vec<String> = value.cpus.iter().map(|c| c.to_string()).collect();
+            println!(
+                "{} = {:#x} {}",
+                register,
+                value.value,
+                tr_args("msr-on-cpus", &[("cpus", &cpus.join(", "))])
+            );
+        } else {
+            println!("{} = {:#x}", register, value.value);
+        }
+        for field in &value.fields {
+            let (low, high) = field.bits;
+            let bits = if low == high {
+                low.to_string()
+            } else {
+                format!("{}:{}", high, low)
+            };
+            let field_value = if low == high {
+                field.value.to_string()
+            } else {
+                format!("{} ({:#x})", field.value, field.value)
+            };
+            println!("    [{:>5}]  {:<28} {}", bits, field.name, field_value);
+        }
+    }
+    if reading.values.is_empty() {
+        println!("{} = {}", register, tr("module-unknown"));
+    }
+}
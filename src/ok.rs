@@ -1,622 +1,323 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use colored::Colorize;
-use msru::{Accessor, Msr};
-use std::process::Command;
-
-#[derive(Debug, Default)]
-enum TestState {
-    Ok,
-    #[default]
-    Fail,
-    #[allow(dead_code)]
-    Warning,
-    Tbd,
-    Skip,
+use serde::Serialize;
+use std::cell::Cell;
+use tdxhost_core::check::{CheckResult, Probe, TestOperationState, TestOptionalState, TestState};
+use tdxhost_core::error::TdxHostError;
+use tdxhost_core::facts;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::info::{self, Hardware};
+use tdxhost_core::registry::{passed, CheckNode, Registry};
+use tdxhost_core::remediation;
+use tdxhost_core::report::Report;
+
+use crate::cli::{GraphFormat, LogTarget, OkArgs, OkFormat};
+use crate::{journal, remote, sign};
+
+/// Routes each check result to the configured sinks.
+struct Reporter {
+    target: LogTarget,
+    format: OkFormat,
+    /// Print the checks' hints below their results
+    hints: bool,
+    /// Checks whose hints were left out
+    hidden_hints: Cell<usize>,
 }
 
-impl From<&TestState> for String {
-    fn from(res: &TestState) -> Self {
-        match res {
-            TestState::Ok => "OK".to_string(),
-            TestState::Fail => "FAIL".to_string(),
-            TestState::Warning => "WARNING".to_string(),
-            TestState::Tbd => "TBD".to_string(),
-            TestState::Skip => "SKIP".to_string(),
-        }
+impl Reporter {
+    fn report(&self, nodes: &[CheckNode]) {
+        self.report_tree(nodes, "", true);
     }
-}
-
-#[derive(Debug, Default)]
-enum TestOptionalState {
-    #[default]
-    Required,
-    Optional,
-}
 
-#[derive(Debug, Default)]
-enum TestOperationState {
-    Manual,
-    #[default]
-    Program,
-}
-
-#[derive(Debug)]
-enum KvmParameter {
-    Tdx,
-    Sgx,
-}
+    /// Report `nodes` below a parent drawn with `prefix`, each check's
+    /// dependents indented under it with tree glyphs.
+    fn report_tree(&self, nodes: &[CheckNode], prefix: &str, top: bool) {
+        for (i, node) in nodes.iter().enumerate() {
+            let (branch, continuation) = match (top, i + 1 == nodes.len()) {
+                (true, _) => ("", ""),
+                (false, true) => ("└─ ", "   "),
+                (false, false) => ("├─ ", "│  "),
+            };
+            let children = format!("{}{}", prefix, continuation);
+            // keep the line down to the dependents through the reason and
+            // hints
+            let details = if node.children.is_empty() {
+                children.clone()
+            } else {
+                format!("{}│  ", children)
+            };
 
-#[derive(Debug, Default)]
-struct TestResult {
-    action: String,
-    reason: String,
-    state: TestState,
-    optional_state: TestOptionalState,
-    operation: TestOperationState,
-}
+            let hints: &[String] = if self.hints { &node.result.hints } else { &[] };
+            // hints go where their result goes
+            let journaled = self.target == LogTarget::Journald
+                && journal_result(node.id, &node.result, hints).is_ok();
+            if self.format == OkFormat::Text {
+                if !self.hints && !node.result.hints.is_empty() {
+                    self.hidden_hints.set(self.hidden_hints.get() + 1);
+                }
+                if !journaled {
+                    print_result(&node.result, &format!("{}{}", prefix, branch), &details);
+                    for hint in hints {
+                        println!("{}{}", details, hint);
+                    }
+                }
+            }
 
-struct Test {
-    name: &'static str,
-    run: Box<dyn Fn() -> TestResult>,
-    sub_tests: Vec<Test>,
-    post_run: Option<Box<dyn Fn()>>,
+            self.report_tree(&node.children, &children, false);
+        }
+    }
 }
 
-const SUPPORTED_OSES: [&str; 3] = [
-    "Ubuntu 22.04.1 LTS",
-    "Red Hat Enterprise Linux 8.7 (Ootpa)",
-    "CentOS Stream 9",
-];
-
-fn get_os_pretty_name() -> String {
-    let os_release =
-        std::fs::read_to_string("/etc/os-release").expect("/etc/os-release does not exist");
-    let pretty_name_line = os_release
-        .lines()
-        .find(|l| l.contains("PRETTY_NAME"))
-        .expect("PRETTY_NAME for os-release does not exist");
-    pretty_name_line[pretty_name_line
-        .find('"')
-        .expect("\" character not found in this line")..]
-        .trim_matches('\"')
-        .to_owned()
+/// Color used to render a result's state, shared by the text report and the
+/// TUI.
+pub fn state_color(result: &CheckResult) -> &'static str {
+    match result.state {
+        TestState::Ok => "green",
+        TestState::Warning => "magenta",
+        TestState::Skip | TestState::Tbd => "yellow",
+        TestState::Fail => match (&result.operation, &result.optional_state) {
+            (TestOperationState::Manual, _) => "red",
+            (_, TestOptionalState::Optional) => "yellow",
+            (_, TestOptionalState::Required) => "red",
+        },
+    }
 }
 
-fn check_os() -> bool {
-    // get os name
-    let pretty_name = get_os_pretty_name();
+/// Print a result's state and action after `prefix`, and its reason on the
+/// next line after `details`.
+fn print_result(result: &CheckResult, prefix: &str, details: &str) {
+    let state = String::from(&result.state);
+    let color = state_color(result);
 
-    // check if the os is supported
-    let mut supported = false;
-    SUPPORTED_OSES
-        .into_iter()
-        .for_each(|o| supported = o == pretty_name);
+    println!("{}[ {} ] {}", prefix, state.color(color), result.action);
+    if result.reason.is_empty() || result.state == TestState::Ok {
+        return;
+    }
 
-    supported
+    let reason_color = match result.state {
+        TestState::Warning => "yellow",
+        _ => color,
+    };
+    let reason_str = format!(
+        "\t{}",
+        tr_args("result-reason", &[("reason", &result.reason)])
+    )
+    .color(reason_color);
+    println!("{}{}", details, reason_str);
+    if let (TestState::Fail, Some(url)) = (result.state, &result.docs_url) {
+        println!("{}\t{}", details, tr_args("result-docs", &[("url", url)]));
+    }
 }
 
-fn check_tdx_module() -> bool {
-    let dmesg_output = Command::new("sudo")
-        .arg("dmesg")
-        .output()
-        .expect("failed to run dmesg");
+/// Send a result to the journal, with `hints` one per line in HINTS.
+fn journal_result(id: &str, result: &CheckResult, hints: &[String]) -> std::io::Result<()> {
+    let state = String::from(&result.state);
 
-    let dmesg_output = String::from_utf8(dmesg_output.stdout)
-        .expect("unable to convert utf8 bytes to owned String");
+    // syslog priorities: 3 = err, 4 = warning, 5 = notice, 6 = info
+    let priority = match (&result.state, &result.optional_state) {
+        (TestState::Ok, _) => "6",
+        (TestState::Fail, TestOptionalState::Required) => "3",
+        (TestState::Fail, TestOptionalState::Optional) | (TestState::Warning, _) => "4",
+        (TestState::Tbd, _) | (TestState::Skip, _) => "5",
+    };
 
-    dmesg_output.contains("virt/tdx: module initialized")
-}
+    let message = if result.reason.is_empty() {
+        format!("[{}] {}", state, result.action)
+    } else {
+        format!("[{}] {}: {}", state, result.action, result.reason)
+    };
 
-fn check_bios_tme_bypass() -> bool {
-    let msr_value = Msr::new(0x982, 0).unwrap().read().unwrap();
-    msr_value & (1 << 31) > 0
+    let hints = hints.join("\n");
+    let mut fields = vec![
+        ("MESSAGE", message.as_str()),
+        ("MESSAGE_ID", journal::CHECK_RESULT_MESSAGE_ID),
+        ("PRIORITY", priority),
+        ("SYSLOG_IDENTIFIER", "tdxhost"),
+        ("CHECK_ID", id),
+        ("STATE", &state),
+        ("REASON", &result.reason),
+    ];
+    if !hints.is_empty() {
+        fields.push(("HINTS", &hints));
+    }
+    journal::send(&fields)
 }
 
-fn check_cpu_manufacturer_id() -> String {
-    let res = unsafe { std::arch::x86_64::__cpuid(0x0000_0000) };
-    let name: [u8; 12] = unsafe { std::mem::transmute([res.ebx, res.edx, res.ecx]) };
-    String::from_utf8(name.to_vec()).unwrap()
+/// The hardware model heading a report, to tell hosts' reports apart.
+fn print_platform(hardware: &Hardware) {
+    if hardware.model().is_none() && hardware.bios().is_none() {
+        return;
+    }
+    let unknown = tr("module-unknown");
+    println!(
+        "{}",
+        tr_args(
+            "report-platform",
+            &[
+                (
+                    "model",
+                    &hardware.model().unwrap_or_else(|| unknown.clone())
+                ),
+                ("bios", &hardware.bios().unwrap_or(unknown)),
+            ]
+        )
+    );
+    println!();
 }
 
-fn check_kvm_supported() -> (TestState, String) {
-    use std::os::fd::AsRawFd;
-
-    match std::fs::File::open("/dev/kvm") {
-        Ok(fd) => {
-            let api_version = unsafe { libc::ioctl(fd.as_raw_fd(), 0xAE00, 0) };
-            if api_version < 0 {
-                (
-                    TestState::Fail,
-                    String::from("KVM device node (/dev/kvm) should be accessible"),
-                )
-            } else {
-                (TestState::Ok, String::new())
+/// Print the JSON report or facts, or write them to `--output` and sign
+/// them.
+fn write_report(args: &OkArgs, report: &impl Serialize) -> Result<()> {
+    let json = serde_json::to_string_pretty(report)?;
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, json + "\n").with_context(|| {
+                tr_args("error-write", &[("path", &path.display().to_string())])
+            })?;
+            if let Some(key) = &args.sign_key {
+                sign::sign_report(path, key)?;
             }
         }
-        Err(_) => (
-            TestState::Fail,
-            String::from("Unable to read KVM device node file (/dev/kvm)"),
-        ),
+        None => println!("{}", json),
     }
+    Ok(())
 }
 
-fn check_kvm_module_supported(param: KvmParameter) -> (TestState, String, String) {
-    let param_loc = match param {
-        KvmParameter::Tdx => "/sys/module/kvm_intel/parameters/tdx",
-        KvmParameter::Sgx => "/sys/module/kvm_intel/parameters/sgx",
-    };
-
-    let path = std::path::Path::new(param_loc);
+/// Check `host` over SSH and report its results like those of this one,
+/// though without the tree of dependencies.
+pub fn run_remote(args: &OkArgs, host: &str, global_args: &[String]) -> Result<()> {
+    if args.format == OkFormat::Text && args.output.is_some() {
+        return Err(anyhow!(tr("ok-output-json")));
+    }
+    // the facts beyond the report are read on the host itself
+    if args.format == OkFormat::AnsibleFacts {
+        return Err(anyhow!(tr("ok-host-ansible-facts")));
+    }
 
-    let (result, reason) = if path.exists() {
-        match std::fs::read_to_string(param_loc) {
-            Ok(result) => {
-                if result.trim() == "1" || result.trim() == "Y" {
-                    (TestState::Ok, String::new())
+    let report = remote::ok(host, global_args)?;
+    if args.format == OkFormat::Json {
+        write_report(args, &report)?;
+    } else {
+        print_platform(&report.hardware);
+        let mut hidden_hints = 0;
+        for (section, entries) in [
+            ("section-required", &report.required),
+            ("section-optional", &report.optional),
+        ] {
+            println!("{}", tr(section));
+            println!("============================");
+            for entry in entries {
+                print_result(&entry.result, "", "");
+                if !args.verbose && !entry.result.hints.is_empty() {
+                    hidden_hints += 1;
                 } else {
-                    (
-                        TestState::Fail,
-                        format!(
-                            "Parameter file ({}) contains invalid value: {}",
-                            param_loc, result
-                        ),
-                    )
+                    for hint in &entry.result.hints {
+                        println!("{}", hint);
+                    }
                 }
             }
-            Err(e) => (
-                TestState::Fail,
-                format!("Unable to read parameter file: {}", e),
-            ),
+            println!();
         }
-    } else {
-        (
-            TestState::Fail,
-            format!("Provided parameter does not exist: {}", param_loc),
-        )
-    };
-
-    let action = format!(
-        "Check /sys/module/kvm_intel/parameters/{} = Y (required)",
-        param_loc[param_loc.rfind('/').unwrap() + 1..].to_owned()
-    );
-
-    (result, action, reason)
-}
-
-fn report_result(result: &mut TestResult) {
-    let state = String::from(&result.state);
-
-    match result.state {
-        TestState::Ok => {
-            println!("[ {} ] {}", state.green(), result.action);
-        }
-        TestState::Warning => {
-            println!("[ {} ] {}", state.magenta(), result.action);
-            if !result.reason.is_empty() {
-                println!("\tReason: {}", result.reason.yellow());
-            }
-        }
-        _ => {
-            let mut color: &str = "red";
-            if let TestOptionalState::Optional = result.optional_state {
-                color = "yellow";
-            }
-
-            if let TestState::Tbd = result.state {
-                color = "yellow";
-            }
-
-            if let TestOperationState::Manual = result.operation {
-                color = "yellow";
-
-                if let TestState::Fail = result.state {
-                    color = "red";
-                }
-
-                result.reason = String::from("Unable to check in program. Please check manually.");
-            }
-            println!("[ {} ] {}", state.color(color), result.action);
-            if !result.reason.is_empty() {
-                let reason_str = format!("\tReason: {}", result.reason).color(color);
-                println!("{}", reason_str);
-            }
+        if hidden_hints > 0 {
+            println!(
+                "{}",
+                tr_args("hints-hidden", &[("count", &hidden_hints.to_string())])
+            );
         }
     }
-}
-
-pub fn run_all_checks() -> Result<()> {
-    println!("Required Features & Settings");
-    println!("============================");
-    let required_tests = get_required_tests();
-    let required_tests_passed = run_test(&required_tests);
 
-    println!();
-    println!("Optional Features & Settings");
-    println!("============================");
-    let optional_tests = get_optional_tests();
-    let _ = run_test(&optional_tests);
-
-    if !required_tests_passed {
-        Err(anyhow!("One or more required tests failed"))
+    let failed = report
+        .required
+        .iter()
+        .any(|e| e.result.state == TestState::Fail);
+    if failed {
+        Err(anyhow!(tr("error-required-failed")))
+    } else if report.degraded {
+        Err(TdxHostError::PermissionDenied(tr("error-needs-root")).into())
     } else {
         Ok(())
     }
 }
 
-fn run_test(tests: &[Test]) -> bool {
-    let mut passed = true;
-
-    for t in tests {
-        let mut res = (t.run)();
-        report_result(&mut res);
-        if let Some(f) = &t.post_run {
-            (f)();
-        }
-        match res.state {
-            TestState::Ok => {
-                if !run_test(&t.sub_tests) {
-                    passed = false;
-                }
-            }
-            TestState::Fail => {
-                passed = false;
-                report_skip_result(&t.sub_tests);
-            }
-            TestState::Tbd => {}
-            TestState::Skip => {}
-            TestState::Warning => {}
-        }
+pub fn run_all_checks(args: &OkArgs, probe: &Probe) -> Result<()> {
+    if args.json_schema {
+        let schema = schemars::schema_for!(Report);
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
     }
-
-    passed
-}
-
-fn report_skip_result(tests: &[Test]) {
-    for t in tests {
-        let res = TestResult {
-            state: TestState::Skip,
-            action: t.name.to_string(),
-            ..Default::default()
-        };
-        let state = String::from(&res.state);
-        println!("[ {} ] {}", state.yellow(), res.action);
-        report_skip_result(&t.sub_tests);
+    if let Some(GraphFormat::Dot) = args.graph {
+        print!("{}", Registry::builtin().dot());
+        return Ok(());
     }
-}
-
-fn get_optional_tests() -> Vec<Test> {
-    let bios_mem_map_test = Test {
-        name: "Volatile Memory should be 1LM",
-        run: Box::new(|| TestResult {
-            action: String::from("Check BIOS: Volatile Memory should be 1LM"),
-            state: TestState::Tbd,
-            optional_state: TestOptionalState::Optional,
-            operation: TestOperationState::Manual,
-            ..Default::default()
-        }),
-        sub_tests: vec![],
-        post_run: Some(Box::new(|| {
-            println!("\tPlease check your BIOS settings:");
-            println!("\t\tSocket Configuration -> Memory Configuration -> Memory Map");
-            println!("\t\t\tVolatile Memory (or Volatile Memory Mode) should be 1LM");
-            println!("\t\tA different BIOS might have a different path for this setting.");
-            println!("\t\tPlease skip this setting if it doesn't exist in your BIOS menu.");
-        })),
-    };
-
-    let bios_tme_bypass_test = Test {
-        name: "TME Bypass is enabled",
-        run: Box::new(|| {
-            let state = if check_bios_tme_bypass() {
-                TestState::Ok
-            } else {
-                TestState::Fail
-            };
-
-            TestResult {
-                action: String::from("Check BIOS: TME Bypass = Enabled"),
-                reason: String::from("The bit 31 of MSR 0x982 should be 1"),
-                state,
-                optional_state: TestOptionalState::Optional,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![],
-        post_run: Some(Box::new(|| {
-            if !check_bios_tme_bypass() {
-                println!("\tThe TME Bypass has not been enabled now.");
-            }
-
-            println!(
-                "\tIt's better to enable TME Bypass for traditional non-confidential workloads."
-            );
-        })),
-    };
-
-    let bios_seam_loader_test = Test {
-        name: "SEAM Loader is enabled",
-        run: Box::new(|| TestResult {
-            action: String::from("Check BIOS: SEAM Loader = Enabled"),
-            state: TestState::Tbd,
-            operation: TestOperationState::Manual,
-            optional_state: TestOptionalState::Optional,
-            ..Default::default()
-        }),
-        sub_tests: vec![],
-        post_run: None,
-    };
-
-    vec![
-        bios_mem_map_test,
-        bios_tme_bypass_test,
-        bios_seam_loader_test,
-    ]
-}
-
-fn get_required_tests() -> Vec<Test> {
-    //                       CPU Manufacturer ID
-    //                                |
-    //                                |
-    //                          OS is supported
-    //                                |
-    //                                |
-    //                          SGX is enabled
-    //                                |
-    //                                |
-    //                          TDX is enabled
-    //                                |
-    //                                |
-    //      +-------------------------+-----------------------+
-    //      |             |           |          |            |
-    //    TDX Mod.       TME       TME-MT     TDX Key      SGX Reg.
-    //  Initialized    Enabled    Enabled    Split != 0    Server
-
-    let tdx_enabled_test = Test {
-        name: "Check TDX enabled",
-        run: Box::new(|| {
-            let msr_value = Msr::new(0x1401, 0).unwrap().read().unwrap();
-            let state = if msr_value & (1 << 11) > 0 {
-                TestState::Ok
-            } else {
-                TestState::Fail
-            };
-            TestResult {
-                action: String::from("Check BIOS: TDX = Enabled"),
-                reason: String::from("The bit 11 of MSR 0x1401 should be 1"),
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![
-            Test {
-                name: "Check TDX module initialized",
-                run: Box::new(|| {
-                    let module_initialized = check_tdx_module();
-                    let state = if module_initialized {
-                        TestState::Ok
-                    } else {
-                        TestState::Fail
-                    };
-                    TestResult {
-                        action: String::from("Check TDX Module: The module is initialized"),
-                        reason: String::from("TDX module is required"),
-                        state,
-                        ..Default::default()
-                    }
-                }),
-                sub_tests: vec![],
-                post_run: None,
-            },
-            Test {
-                name: "Check TME enabled",
-                run: Box::new(|| {
-                    let msr_value = Msr::new(0x982, 0).unwrap().read().unwrap();
-                    let state = if msr_value & (1 << 1) > 0 {
-                        TestState::Ok
-                    } else {
-                        TestState::Fail
-                    };
-                    TestResult {
-                        action: String::from("Check BIOS: TME = Enabled"),
-                        reason: String::from("The bit 1 of MSR 0x982 should be 1."),
-                        state,
-                        ..Default::default()
-                    }
-                }),
-                sub_tests: vec![],
-                post_run: None,
-            },
-            Test {
-                name: "Check TME-MT/TME-MK enabled",
-                run: Box::new(|| {
-                    let msr_value = Msr::new(0x982, 0).unwrap().read().unwrap();
-                    let state = if msr_value & (1 << 1) > 0 {
-                        TestState::Tbd
-                    } else {
-                        TestState::Fail
-                    };
-                    TestResult {
-                        action: String::from("Check BIOS: TME-MT/TME-MK = Enabled"),
-                        reason: String::from("The bit 1 of MSR 0x982 should be 1."),
-                        state,
-                        operation: TestOperationState::Manual,
-                        ..Default::default()
-                    }
-                }),
-                sub_tests: vec![],
-                post_run: Some(Box::new(|| {
-                    println!("\tPlease check your BIOS settings:");
-                    println!(
-                        "\t\tSocket Configuration -> Processor Configuration -> TME, TME-MT, TDX"
-                    );
-                    println!(
-                        "\t\t\tTotal Memory Encryption Multi-Tenant (TME-MT) should be Enable"
-                    );
-                    println!("\t\tA different BIOS might have a different path for this setting.");
-                })),
-            },
-            Test {
-                name: "Check TDX Key Split != 0",
-                run: Box::new(|| {
-                    let msr_value = Msr::new(0x981, 0).unwrap().read().unwrap();
-                    let state = if msr_value & (0x7fff << 36) != 0 {
-                        TestState::Ok
-                    } else {
-                        TestState::Fail
-                    };
-                    TestResult {
-                        action: String::from("Check BIOS: TDX Key Split != 0"),
-                        reason: String::from("TDX Key Split should be non-zero"),
-                        state,
-                        ..Default::default()
-                    }
-                }),
-                sub_tests: vec![],
-                post_run: None,
-            },
-            Test {
-                name: "Check SGX registration server",
-                run: Box::new(|| TestResult {
-                    action: String::from("Check BIOS: SGX registration server"),
-                    reason: String::from(""),
-                    state: TestState::Tbd,
-                    operation: TestOperationState::Manual,
-                    ..Default::default()
-                }),
-                sub_tests: vec![],
-                post_run: Some(Box::new(|| {
-                    let msr_value = Msr::new(0xce, 0).unwrap().read().unwrap();
-                    if msr_value & (1 << 27) > 0 {
-                        println!("\tSGX registration server is SBX");
-                    } else {
-                        println!("\tSGX registration server is LIV");
-                    }
-                })),
-            },
-        ],
-        post_run: None,
-    };
 
-    let sgx_enabled_test = Test {
-        name: "Check SGX enabled",
-        run: Box::new(|| {
-            let msr_value = Msr::new(0x3a, 0).unwrap().read().unwrap();
-            let state = if msr_value & (1 << 18) > 0 {
-                TestState::Ok
-            } else {
-                TestState::Fail
-            };
-            TestResult {
-                action: String::from("Check BIOS: SGX = Enabled"),
-                reason: String::from("The bit 18 of MSR 0x3a should be 1"),
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![tdx_enabled_test],
-        post_run: None,
+    let target = match args.log_target {
+        LogTarget::Auto if journal::connected() => LogTarget::Journald,
+        LogTarget::Auto => LogTarget::Stdout,
+        target => target,
     };
 
-    let os_distro_test = Test {
-        name: "Check OS distro",
-        run: Box::new(|| {
-            let supported = check_os();
-            let state = if supported {
-                TestState::Ok
-            } else {
-                TestState::Fail
-            };
-            TestResult {
-                action: String::from("Check OS: The distro and version are correct"),
-                reason: String::from("Your OS distro is not supported yet."),
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![sgx_enabled_test],
-        post_run: Some(Box::new(|| {
-            let pretty_name = get_os_pretty_name();
-            println!("\tYour current OS is: {}", pretty_name);
-            println!("\tThe following OSs are supported:");
-            for os in SUPPORTED_OSES {
-                println!("\t\t{}", os);
-            }
-            println!("\tThere is no guarantee to other OS distros");
-        })),
-    };
+    if args.format == OkFormat::Text && args.output.is_some() {
+        return Err(anyhow!(tr("ok-output-format")));
+    }
 
-    let cpu_manu_id_test = Test {
-        name: "Check CPU Manufacturer ID",
-        run: Box::new(|| {
-            let manu_name = check_cpu_manufacturer_id();
-            let state = if manu_name == "GenuineIntel" {
-                TestState::Ok
-            } else {
-                TestState::Fail
-            };
-            TestResult {
-                action: String::from("Check CPUID 0x0 Manufacturer ID = GenuineIntel"),
-                reason: String::from("The CPUID Manufacturer ID should be GenuineIntel"),
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![os_distro_test],
-        post_run: None,
+    let reporter = Reporter {
+        target,
+        format: args.format,
+        hints: args.verbose,
+        hidden_hints: Cell::new(0),
     };
+    let print_headers = target == LogTarget::Stdout && args.format == OkFormat::Text;
 
-    //            KVM is enabled
-    //                  |
-    //                  |
-    //      +----------------------+
-    //      |                      |
-    //     SGX                    TDX
-    //  Mod Enabled           Mod Enabled
+    if print_headers {
+        print_platform(&info::hardware(probe));
+        println!("{}", tr("section-required"));
+        println!("============================");
+    }
+    let groups = Registry::with_plugins(probe).evaluate(probe);
+    let required_tests_passed = passed(&groups.required);
+    let degraded = groups.degraded;
+
+    if let Some(path) = &args.emit_fix_script {
+        let mut nodes = vec![];
+        remediation::fixable(&groups.required, &mut nodes);
+        remediation::fixable(&groups.optional, &mut nodes);
+        remediation::write_script(path, &nodes)?;
+    }
 
-    let kvm_sgx_mod_test = Test {
-        name: "Check KVM SGX parameter enabled",
-        run: Box::new(|| {
-            let (state, action, reason) = check_kvm_module_supported(KvmParameter::Sgx);
-            TestResult {
-                action,
-                reason,
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![],
-        post_run: None,
-    };
+    reporter.report(&groups.required);
 
-    let kvm_tdx_mod_test = Test {
-        name: "Check KVM TDX parameter enabled",
-        run: Box::new(|| {
-            let (state, action, reason) = check_kvm_module_supported(KvmParameter::Tdx);
-            TestResult {
-                action,
-                reason,
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![],
-        post_run: None,
-    };
+    if print_headers {
+        println!();
+        println!("{}", tr("section-optional"));
+        println!("============================");
+    }
+    reporter.report(&groups.optional);
+    if reporter.hidden_hints.get() > 0 {
+        println!();
+        println!(
+            "{}",
+            tr_args(
+                "hints-hidden",
+                &[("count", &reporter.hidden_hints.get().to_string())]
+            )
+        );
+    }
 
-    let kvm_supported_test = Test {
-        name: "Check KVM is supported",
-        run: Box::new(|| {
-            let (state, reason) = check_kvm_supported();
-            TestResult {
-                action: String::from("Check KVM is supported"),
-                reason,
-                state,
-                ..Default::default()
-            }
-        }),
-        sub_tests: vec![kvm_sgx_mod_test, kvm_tdx_mod_test],
-        post_run: None,
-    };
+    match args.format {
+        OkFormat::Json => write_report(args, &Report::new(probe, groups))?,
+        // whether the host is ready is a fact, not an error
+        OkFormat::AnsibleFacts => {
+            let report = Report::new(probe, groups);
+            return write_report(args, &facts::collect(probe, &report));
+        }
+        OkFormat::Text => {}
+    }
 
-    vec![cpu_manu_id_test, kvm_supported_test]
+    if !required_tests_passed {
+        Err(anyhow!(tr("error-required-failed")))
+    } else if degraded {
+        Err(TdxHostError::PermissionDenied(tr("error-needs-root")).into())
+    } else {
+        Ok(())
+    }
 }
@@ -0,0 +1,131 @@
+use anyhow::{anyhow, Context, Result};
+use std::process::Command;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::remediation::shell_quote;
+use tdxhost_core::report::Report;
+
+/// Options of SSH and scp: fail rather than prompt, as the output is parsed.
+const SSH_OPTIONS: [&str; 2] = ["-o", "BatchMode=yes"];
+
+/// Run `command` on `destination` and return its output.
+fn ssh(destination: &str, command: &str) -> Result<std::process::Output> {
+    Command::new("ssh")
+        .args(SSH_OPTIONS)
+        .arg(destination)
+        .arg(command)
+        .output()
+        .with_context(|| tr_args("remote-run-failed", &[("program", "ssh")]))
+}
+
+/// Shell commands running `tdxhost ok` with `global_args` from the copy at
+/// `path`, then removing its directory `dir`.
+fn ok_script(path: &str, dir: &str, global_args: &[String]) -> String {
+    let args: Vec<String> = global_args
+        .iter()
+        .map(String::as_str)
+        .chain(["ok", "--format", "json", "--log-target", "stdout"])
+        .map(shell_quote)
+        .collect();
+    // the checks need root; a failing run still prints its report
+    format!(
+        "s=; [ \"$(id -u)\" -eq 0 ] || s=\"sudo -n\"; $s {path} {args}; r=$?; rm -rf {dir}; exit $r",
+        path = shell_quote(path),
+        args = args.join(" "),
+        dir = shell_quote(dir)
+    )
+}
+
+/// Run `tdxhost ok` on `destination` with a copy of this binary, which is
+/// removed afterwards, and return its report.
+pub fn ok(destination: &str, global_args: &[String]) -> Result<Report> {
+    let exe = std::env::current_exe().with_context(|| tr("remote-no-binary"))?;
+
+    // a private directory, so no other user can swap the binary run as root
+    let output = ssh(destination, "mktemp -d /tmp/tdxhost.XXXXXXXX")?;
+    let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || !dir.starts_with("/tmp/tdxhost.") {
+        return Err(anyhow!(tr_args(
+            "remote-mktemp-failed",
+            &[
+                ("host", destination),
+                ("status", &output.status.to_string()),
+                ("error", String::from_utf8_lossy(&output.stderr).trim()),
+            ]
+        )));
+    }
+    let path = format!("{}/tdxhost", dir);
+
+    let status = Command::new("scp")
+        .args(SSH_OPTIONS)
+        .arg("-q")
+        .arg(&exe)
+        .arg(format!("{}:{}", destination, path))
+        .status()
+        .with_context(|| tr_args("remote-run-failed", &[("program", "scp")]))?;
+    if !status.success() {
+        let _ = ssh(destination, &format!("rm -rf {}", shell_quote(&dir)));
+        return Err(anyhow!(tr_args(
+            "remote-scp-failed",
+            &[("host", destination), ("status", &status.to_string())]
+        )));
+    }
+
+    let output = ssh(destination, &ok_script(&path, &dir, global_args))?;
+
+    serde_json::from_slice(&output.stdout).map_err(|_| {
+        anyhow!(tr_args(
+            "remote-tdxhost-failed",
+            &[
+                ("host", destination),
+                ("status", &output.status.to_string()),
+                ("error", String::from_utf8_lossy(&output.stderr).trim()),
+            ]
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn arguments_quoted() {
+        let global_args = ["--lang".to_string(), "zh-CN; rm -rf /".to_string()];
+        assert_eq!(
+            ok_script("/tmp/tdxhost.a b/tdxhost", "/tmp/tdxhost.a b", &global_args),
+            "s=; [ \"$(id -u)\" -eq 0 ] || s=\"sudo -n\"; $s '/tmp/tdxhost.a b/tdxhost' \
+             --lang 'zh-CN; rm -rf /' ok --format json --log-target stdout; r=$?; \
+             rm -rf '/tmp/tdxhost.a b'; exit $r"
+        );
+    }
+
+    #[test]
+    fn script_runs() {
+        // without root the script runs the copy through sudo
+        let root = Command::new("id")
+            .arg("-u")
+            .output()
+            .is_ok_and(|o| o.stdout == b"0\n");
+        if !root {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("tdxhost.it's {}", std::process::id()));
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join("tdxhost");
+        // prints its arguments one per line and exits like a failing run
+        fs::write(&path, "#!/bin/sh\nprintf '%s\\n' \"$@\"\nexit 1\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let global_args = ["--lang".to_string(), "a \"b\" $c".to_string()];
+        let script = ok_script(path.to_str().unwrap(), dir.to_str().unwrap(), &global_args);
+        let output = Command::new("sh").arg("-c").arg(&script).output().unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "--lang\na \"b\" $c\nok\n--format\njson\n--log-target\nstdout\n"
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tdxhost_core::i18n::{tr, tr_args};
+
+/// Produce a detached SHA-256 signature of `report` next to it, using any
+/// private key type openssl understands (RSA, EC, ...).
+pub fn sign_report(report: &Path, key: &Path) -> Result<PathBuf> {
+    let mut signature = report.as_os_str().to_owned();
+    signature.push(".sig");
+    let signature = PathBuf::from(signature);
+
+    let output = Command::new("openssl")
+        .arg("dgst")
+        .arg("-sha256")
+        .arg("-sign")
+        .arg(key)
+        .arg("-out")
+        .arg(&signature)
+        .arg(report)
+        .output()
+        .with_context(|| tr("sign-run-failed"))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(tr_args(
+            "sign-failed",
+            &[
+                ("path", &report.display().to_string()),
+                ("error", String::from_utf8_lossy(&output.stderr).trim()),
+            ]
+        )));
+    }
+
+    Ok(signature)
+}
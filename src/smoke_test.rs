@@ -0,0 +1,37 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use tdxhost_core::error::TdxHostError;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::smoke_test::{self, Stage};
+
+fn stage_name(stage: Stage) -> String {
+    tr(&format!("smoke-test-stage-{}", stage.id()))
+}
+
+pub fn run() -> Result<()> {
+    let res = smoke_test::run(|stage| println!("[ {} ] {}", "OK".green(), stage_name(stage)));
+    match res {
+        Ok(()) => {
+            println!("{}", tr("smoke-test-passed").green());
+            Ok(())
+        }
+        Err(TdxHostError::SmokeTest { stage, reason }) => {
+            println!("[ {} ] {}", "FAIL".red(), stage_name(stage));
+            println!(
+                "{}",
+                format!("\t{}", tr_args("result-reason", &[("reason", &reason)])).red()
+            );
+            if !stage.seamcalls().is_empty() {
+                println!(
+                    "\t{}",
+                    tr_args(
+                        "smoke-test-seamcalls",
+                        &[("seamcalls", &stage.seamcalls().join(", "))]
+                    )
+                );
+            }
+            Err(anyhow!(tr("smoke-test-failed")))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
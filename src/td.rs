@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Result};
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::td;
+
+use crate::cli::{KeysArgs, OutputFormat, TdArgs, TdInspectArgs, TdListArgs, TdSubcommand};
+
+pub fn run(args: &TdArgs, probe: &Probe) -> Result<()> {
+    match &args.cmd {
+        TdSubcommand::List(list_args) => list(list_args, probe),
+        TdSubcommand::Inspect(inspect_args) => inspect(inspect_args, probe),
+    }
+}
+
+fn list(args: &TdListArgs, probe: &Probe) -> Result<()> {
+    let list = td::list(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&list)?);
+        return Ok(());
+    }
+
+    if list.tds.is_empty() {
+        println!("{}", tr("td-list-none"));
+    } else {
+        println!(
+            "{:>8}  {:<24} {:<20} {:>10}  {}",
+            tr("td-list-pid"),
+            tr("td-list-name"),
+            tr("td-list-vmm"),
+            tr("td-list-memory"),
+            tr("td-list-keyids")
+        );
+        for td in &list.tds {
+            let memory = td
+                .memory_mib
+                .map(|mib| format!("{} MiB", mib))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "{:>8}  {:<24} {:<20} {:>10}  {}",
+                td.pid,
+                td.name.as_deref().unwrap_or("-"),
+                td.vmm,
+                memory,
+                td.keyids
+            );
+        }
+    }
+
+    let total = list
+        .keyids_total
+        .map(|t| t.to_string())
+        .unwrap_or_else(|| tr("module-unknown"));
+    println!();
+    println!(
+        "{}",
+        tr_args(
+            "td-list-keyids-used",
+            &[("used", &list.keyids_used.to_string()), ("total", &total)]
+        )
+    );
+    Ok(())
+}
+
+fn inspect(args: &TdInspectArgs, probe: &Probe) -> Result<()> {
+    let details = td::inspect(probe, &args.target)
+        .ok_or_else(|| anyhow!(tr_args("td-inspect-not-found", &[("target", &args.target)])))?;
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&details)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    let field = |name: &str, value: Option<&str>| {
+        println!("{:<20} {}", tr(name), value.unwrap_or(&unknown));
+    };
+    let mib = |kib: Option<u64>| kib.map(|kib| format!("{} MiB", kib >> 10));
+    field("td-inspect-pid", Some(&details.td.pid.to_string()));
+    field("td-inspect-name", details.td.name.as_deref());
+    field("td-inspect-uuid", details.td.uuid.as_deref());
+    field("td-inspect-vmm", Some(&details.td.vmm));
+    field("td-inspect-libvirt", details.libvirt_domain.as_deref());
+    field(
+        "td-inspect-vcpus",
+        details.vcpus.map(|v| v.to_string()).as_deref(),
+    );
+    field(
+        "td-inspect-memory",
+        mib(details.td.memory_mib.map(|m| m << 10)).as_deref(),
+    );
+    field("td-inspect-mapped", mib(details.mapped_kib).as_deref());
+    field("td-inspect-shmem", mib(details.shmem_kib).as_deref());
+    field("td-inspect-anon", mib(details.anon_kib).as_deref());
+    field(
+        "td-inspect-keyids",
+        Some(&tr_args(
+            "td-inspect-keyids-value",
+            &[("count", &details.td.keyids.to_string())],
+        )),
+    );
+    Ok(())
+}
+
+pub fn keys(args: &KeysArgs, probe: &Probe) -> Result<()> {
+    let keyids = td::keyids(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&keyids)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    let field = |name: &str, value: Option<String>| {
+        println!("{:<20} {}", tr(name), value.as_deref().unwrap_or(&unknown));
+    };
+    field("keys-mktme", keyids.mktme.map(|k| k.to_string()));
+    field("keys-tdx", keyids.tdx.map(|k| k.to_string()));
+    field("keys-global", Some(keyids.global.to_string()));
+    field("keys-capacity", keyids.td_capacity.map(|k| k.to_string()));
+    field("keys-in-use", Some(keyids.in_use.to_string()));
+    field(
+        "keys-free",
+        keyids
+            .free
+            .map(|free| tr_args("keys-free-tds", &[("free", &free.to_string())])),
+    );
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::topology::{self, format_cpu_list};
+
+use crate::cli::{OutputFormat, TopologyArgs};
+
+pub fn run(args: &TopologyArgs, probe: &Probe) -> Result<()> {
+    let topology = topology::collect(probe);
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&topology)?);
+        return Ok(());
+    }
+
+    let unknown = tr("module-unknown");
+    let field = |name: &str, value: Option<String>| {
+        println!(
+            "    {:<16} {}",
+            tr(name),
+            value.as_deref().unwrap_or(&unknown)
+        );
+    };
+    let enabled = |enabled: Option<bool>| {
+        enabled.map(|e| {
+            tr(if e {
+                "topology-enabled"
+            } else {
+                "topology-disabled"
+            })
+        })
+    };
+
+    for package in &topology.packages {
+        println!(
+            "{}",
+            tr_args(
+                "topology-package",
+                &[
+                    ("id", &package.id.to_string()),
+                    ("cores", &package.cores.to_string()),
+                    ("threads", &package.cpus.len().to_string()),
+                ]
+            )
+        );
+        field("topology-cpus", Some(format_cpu_list(&package.cpus)));
+        let nodes: Vec<String> = package.nodes.iter().map(|n| n.to_string()).collect();
+        field(
+            "topology-nodes",
+            (!nodes.is_empty()).then(|| nodes.join(", ")),
+        );
+        field("topology-tme", enabled(package.tme_enabled));
+        field("topology-tdx", enabled(package.tdx_enabled));
+    }
+    for node in &topology.nodes {
+        println!(
+            "{}",
+            tr_args("topology-node", &[("id", &node.id.to_string())])
+        );
+        field("topology-cpus", Some(format_cpu_list(&node.cpus)));
+        field(
+            "topology-memory",
+            node.memory_mib
+                .map(|mib| tr_args("topology-memory-value", &[("mib", &mib.to_string())])),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,229 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::registry::{CheckGroups, CheckNode, Registry};
+
+use crate::ok;
+
+enum Row<'a> {
+    Section(String),
+    Check { depth: usize, node: &'a CheckNode },
+}
+
+struct App {
+    registry: Registry,
+    probe: Probe,
+    groups: CheckGroups,
+    collapsed: HashSet<&'static str>,
+    list: ListState,
+}
+
+impl App {
+    fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows = vec![];
+        for (title, nodes) in [
+            (tr("section-required"), &self.groups.required),
+            (tr("section-optional"), &self.groups.optional),
+        ] {
+            rows.push(Row::Section(title));
+            self.push_rows(&mut rows, nodes, 0);
+        }
+        rows
+    }
+
+    fn push_rows<'a>(&'a self, rows: &mut Vec<Row<'a>>, nodes: &'a [CheckNode], depth: usize) {
+        for node in nodes {
+            rows.push(Row::Check { depth, node });
+            if !self.collapsed.contains(node.id) {
+                self.push_rows(rows, &node.children, depth + 1);
+            }
+        }
+    }
+
+    fn selected_id(&self) -> Option<&'static str> {
+        match self.rows().get(self.list.selected()?) {
+            Some(Row::Check { node, .. }) if !node.children.is_empty() => Some(node.id),
+            _ => None,
+        }
+    }
+
+    fn rerun(&mut self) {
+        self.groups = self.registry.evaluate(&self.probe);
+        let rows = self.rows().len();
+        if self.list.selected().is_some_and(|s| s >= rows) {
+            self.list.select(Some(rows.saturating_sub(1)));
+        }
+    }
+}
+
+fn state_style(node: &CheckNode) -> Style {
+    let color = match ok::state_color(&node.result) {
+        "green" => Color::Green,
+        "magenta" => Color::Magenta,
+        "yellow" => Color::Yellow,
+        _ => Color::Red,
+    };
+    Style::default().fg(color)
+}
+
+fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let [tree, details] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(main);
+
+    let rows = app.rows();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| match row {
+            Row::Section(title) => ListItem::new(Line::from(Span::styled(
+                title.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ))),
+            Row::Check { depth, node } => {
+                let glyph = match (node.children.is_empty(), app.collapsed.contains(node.id)) {
+                    (true, _) => "  ",
+                    (false, true) => "▸ ",
+                    (false, false) => "▾ ",
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw("  ".repeat(depth + 1)),
+                    Span::raw(glyph),
+                    Span::styled(
+                        format!("[{}] ", String::from(&node.result.state)),
+                        state_style(node),
+                    ),
+                    Span::raw(node.result.action.clone()),
+                ]))
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Checks "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let selected = app.list.selected().and_then(|s| match rows.get(s) {
+        Some(Row::Check { node, .. }) => Some(*node),
+        _ => None,
+    });
+    let [summary_area, evidence_area, hints_area] = Layout::vertical([
+        Constraint::Percentage(30),
+        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+    ])
+    .areas(details);
+
+    let (summary, evidence, hints) = match selected {
+        Some(node) => {
+            let mut summary = vec![
+                Line::from(Span::styled(
+                    String::from(&node.result.state),
+                    state_style(node),
+                )),
+                Line::from(format!("{} ({})", node.result.action, node.id)),
+            ];
+            if !node.result.reason.is_empty() {
+                summary.push(Line::from(node.result.reason.clone()));
+            }
+            if let Some(url) = &node.result.docs_url {
+                summary.push(Line::from(tr_args("result-docs", &[("url", url)])));
+            }
+            let evidence = node
+                .result
+                .evidence
+                .iter()
+                .map(|e| Line::from(e.clone()))
+                .collect();
+            let hints = node
+                .result
+                .hints
+                .iter()
+                .map(|h| Line::from(h.replace('\t', "  ")))
+                .collect();
+            (summary, evidence, hints)
+        }
+        None => (vec![], vec![], vec![]),
+    };
+
+    frame.render_stateful_widget(list, tree, &mut app.list);
+    for (lines, title, area) in [
+        (summary, " Result ", summary_area),
+        (evidence, " Evidence ", evidence_area),
+        (hints, " Hints ", hints_area),
+    ] {
+        frame.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title(title)),
+            area,
+        );
+    }
+    frame.render_widget(
+        Paragraph::new("↑/↓ move  ←/→ collapse/expand  enter toggle  r re-run  q quit"),
+        footer,
+    );
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, app: &mut App) -> Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, app))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => app.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => app.list.select_previous(),
+            KeyCode::Left | KeyCode::Char('h') => {
+                if let Some(id) = app.selected_id() {
+                    app.collapsed.insert(id);
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(id) = app.selected_id() {
+                    app.collapsed.remove(id);
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(id) = app.selected_id() {
+                    if !app.collapsed.remove(id) {
+                        app.collapsed.insert(id);
+                    }
+                }
+            }
+            KeyCode::Char('r') => app.rerun(),
+            _ => {}
+        }
+    }
+}
+
+pub fn run(probe: Probe) -> Result<()> {
+    let registry = Registry::with_plugins(&probe);
+    let mut app = App {
+        groups: registry.evaluate(&probe),
+        registry,
+        probe,
+        collapsed: HashSet::new(),
+        list: ListState::default().with_selected(Some(1)),
+    };
+
+    let mut terminal = ratatui::init();
+    let res = event_loop(&mut terminal, &mut app);
+    ratatui::restore();
+
+    res
+}
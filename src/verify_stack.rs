@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Result};
+use colored::Colorize;
+use tdxhost_core::check::Probe;
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::stack::{self, Matrix};
+
+use crate::cli::{OutputFormat, VerifyStackArgs};
+
+pub fn run(args: &VerifyStackArgs, probe: &Probe) -> Result<()> {
+    let matrix = match &args.matrix {
+        Some(path) => Matrix::load_from(path)?,
+        None => Matrix::load()?,
+    };
+    let report = stack::verify(probe, &matrix);
+
+    if args.format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "{:<12} {}",
+            tr("verify-stack-component"),
+            tr("verify-stack-version")
+        );
+        let not_installed = tr("verify-stack-not-installed");
+        for installed in &report.components {
+            println!(
+                "{:<12} {}",
+                installed.component.name(),
+                installed.version.as_deref().unwrap_or(&not_installed)
+            );
+        }
+        println!();
+
+        for violation in &report.violations {
+            println!(
+                "[ {} ] {}",
+                "FAIL".red(),
+                tr_args(
+                    "verify-stack-violation",
+                    &[
+                        ("component", violation.component.name()),
+                        ("version", &violation.version),
+                        ("requirement", &violation.requirement.to_string()),
+                    ]
+                )
+            );
+            println!("\t{}", violation.reason);
+        }
+        if report.violations.is_empty() {
+            println!("{}", tr("verify-stack-compatible").green());
+        }
+    }
+
+    if report.violations.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(tr("verify-stack-incompatible")))
+    }
+}
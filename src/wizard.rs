@@ -0,0 +1,146 @@
+use anyhow::Result;
+use colored::Colorize;
+use std::io::Write;
+
+use tdxhost_core::bios;
+use tdxhost_core::check::{Probe, TestState};
+use tdxhost_core::i18n::{tr, tr_args};
+use tdxhost_core::registry::Registry;
+
+use crate::ok;
+
+enum Answer {
+    Verify,
+    Confirm,
+    Skip,
+    Quit,
+}
+
+fn prompt(message: &str) -> Result<Answer> {
+    loop {
+        print!("  {} ", message);
+        std::io::stdout().flush()?;
+
+        let mut line = String::new();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            return Ok(Answer::Quit);
+        }
+        match line.trim() {
+            "r" => return Ok(Answer::Verify),
+            "c" => return Ok(Answer::Confirm),
+            "s" => return Ok(Answer::Skip),
+            "q" => return Ok(Answer::Quit),
+            _ => {}
+        }
+    }
+}
+
+fn print_summary(fixed: usize, total: usize) {
+    println!(
+        "{}",
+        tr_args(
+            "wizard-done",
+            &[
+                ("fixed", &fixed.to_string()),
+                ("remaining", &(total - fixed).to_string()),
+            ]
+        )
+    );
+}
+
+pub fn run(probe: &Probe) -> Result<()> {
+    println!("{}", tr("wizard-title").bold());
+
+    let vendor = bios::system_vendor();
+    match &vendor {
+        Some(v) => println!("{}", tr_args("wizard-vendor", &[("vendor", v)])),
+        None => println!("{}", tr("wizard-vendor-unknown")),
+    }
+
+    let registry = Registry::with_plugins(probe);
+    let groups = registry.evaluate(probe);
+    let mut steps = vec![];
+    bios::pending(&groups.required, &mut steps);
+    bios::pending(&groups.optional, &mut steps);
+
+    if steps.is_empty() {
+        println!("{}", tr("wizard-nothing-to-do"));
+        return Ok(());
+    }
+    println!(
+        "{}",
+        tr_args("wizard-pending", &[("count", &steps.len().to_string())])
+    );
+
+    let mut fixed = 0;
+    let total = steps.len();
+    for (i, (id, manual)) in steps.into_iter().enumerate() {
+        let Some(mut node) = registry.rerun(id, probe) else {
+            continue;
+        };
+        let knob = bios::knob(id).unwrap();
+
+        println!();
+        println!(
+            "[{}/{}] {} [ {} ]",
+            i + 1,
+            total,
+            node.result.action,
+            String::from(&node.result.state).color(ok::state_color(&node.result))
+        );
+        println!(
+            "  {}",
+            tr_args(
+                "wizard-setting",
+                &[("setting", knob.setting), ("value", knob.value)]
+            )
+        );
+        println!(
+            "  {}",
+            tr_args(
+                "wizard-menu",
+                &[("path", knob.menu_path(vendor.as_deref()))]
+            )
+        );
+        for hint in &node.result.hints {
+            println!("  {}", hint.trim_start_matches('\t'));
+        }
+        println!("  {}", tr("wizard-reboot"));
+
+        loop {
+            let question = if manual {
+                tr("wizard-prompt-confirm")
+            } else {
+                tr("wizard-prompt-verify")
+            };
+            match prompt(&question)? {
+                Answer::Confirm if manual => {
+                    fixed += 1;
+                    break;
+                }
+                Answer::Verify if !manual => {
+                    node = registry.rerun(id, probe).unwrap();
+                    if node.result.state == TestState::Ok {
+                        println!("  {}", tr("wizard-verified").green());
+                        fixed += 1;
+                        break;
+                    }
+                    println!(
+                        "  {}",
+                        tr_args("wizard-still-failing", &[("reason", &node.result.reason)]).red()
+                    );
+                }
+                Answer::Skip => break,
+                Answer::Quit => {
+                    print_summary(fixed, total);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    println!();
+    print_summary(fixed, total);
+    Ok(())
+}
@@ -0,0 +1,599 @@
+# English messages for tdxhost. This catalog is the fallback for every
+# other locale, so every message must exist here.
+
+## Report
+
+report-platform = Platform: { $model }, BIOS { $bios }
+section-required = Required Features & Settings
+section-optional = Optional Features & Settings
+result-reason = Reason: { $reason }
+result-docs = See: { $url }
+hints-hidden = { $count } checks have hints, rerun with --verbose to show them
+reason-manual = Unable to check in program. Please check manually.
+reason-needs-root = Needs root privileges, re-run as root.
+reason-lockdown = MSR access is blocked by kernel lockdown ({ $mode })
+hint-lockdown-alternative = Without MSR access, check: { $command }
+probe-failed = Could not be determined: { $error }
+reason-msr-partial = { $reason }, it is not on { $where }
+cpu-location = package { $packages } ({ $count ->
+        [one] CPU
+       *[other] CPUs
+    } { $cpus })
+cpu-location-unknown = { $count ->
+        [one] CPU
+       *[other] CPUs
+    } { $cpus }
+error-required-failed = One or more required tests failed
+error-needs-root = Privileged checks were skipped, the result is incomplete
+warning-unprivileged = Not running as root: MSR and kernel log checks are skipped.
+warning-lockdown = Kernel lockdown is active ({ $mode }), MSR reads may be blocked.
+guest-detected = tdxhost checks host readiness; you appear to be in a guest (hypervisor: { $hypervisor }). Run it on the host, or pass --allow-guest to run anyway.
+guest-detected-nested = tdxhost checks host readiness; this host is itself a virtual machine under { $hypervisor }, with nested VMX. TDX cannot work with nested virtualization: the TDX module only initializes on bare metal. Run tdxhost on the physical host.
+guest-detected-td = tdxhost checks host readiness; you appear to be in a TDX guest. `dmesg | grep -i tdx` shows the TD's memory encryption, and guest-side tooling such as the Intel Trust Authority client (trustauthority-cli) attests it.
+ok-host-simulated = --simulate inspects a fixture, not { $host }
+ok-output-json = --output requires --format json
+ok-output-format = --output requires --format json or ansible-facts
+ok-host-ansible-facts = --format ansible-facts cannot check a host over SSH, run tdxhost on it instead
+sign-run-failed = Unable to run openssl
+sign-failed = Unable to sign { $path }: { $error }
+remote-run-failed = Unable to run { $program }
+remote-no-binary = Unable to find the tdxhost binary
+remote-mktemp-failed = mktemp on { $host } exited with { $status }: { $error }
+remote-scp-failed = scp to { $host } exited with { $status }
+remote-tdxhost-failed = tdxhost on { $host } exited with { $status }: { $error }
+error-write = Unable to write { $path }
+error-read = Unable to read { $path }
+error-create = Unable to create { $path }
+redfish-no-password = --redfish needs the BMC password in { $variable }
+
+## Shared BIOS hints
+
+hint-check-bios-settings = Please check your BIOS settings:
+hint-bios-path-differs = A different BIOS might have a different path for this setting.
+hint-bios-skip-missing = Please skip this setting if it doesn't exist in your BIOS menu.
+hint-bios-attributes-failed = The BIOS settings could not be read: { $error }
+hint-bios-attribute-missing = { $setting } is not among the BIOS settings read
+reason-bios-attribute = { $setting } is { $value } in the BIOS, it should be { $expected }
+
+## CPU and OS
+
+cpu-manufacturer-name = Check CPU Manufacturer ID
+cpu-manufacturer-action = Check CPUID 0x0 Manufacturer ID = GenuineIntel
+cpu-manufacturer-reason = The CPUID Manufacturer ID should be GenuineIntel
+
+cpu-bare-metal-name = Check bare metal
+cpu-bare-metal-action = Check CPU: The host runs on bare metal
+cpu-bare-metal-reason = The host is a virtual machine under { $hypervisor }, TDX needs bare metal
+cpu-bare-metal-reason-nested = The host is a virtual machine under { $hypervisor } with nested VMX, TDX cannot work with nested virtualization
+cpu-bare-metal-reason-td = The host is a TD, TDs cannot run TDs
+cpu-model-name = Check CPU model supports TDX
+cpu-model-action = Check CPU: The model supports TDX
+cpu-model-reason = { $model } does not support TDX, it needs Sapphire Rapids, Emerald Rapids, Granite Rapids or a later Xeon
+
+cpu-tme-name = Check CPU enumerates TME
+cpu-tme-action = Check CPUID: The CPU enumerates TME, which TDX builds on
+cpu-tme-reason = The CPU does not enumerate TME: CPUID.07H:ECX[13] should be 1
+
+cpu-errata-name = Check known CPU errata
+cpu-errata-action = Check CPU: No known TDX errata apply
+cpu-errata-reason = Known errata apply to this CPU: { $errata }
+
+os-distro-name = Check OS distro
+os-distro-action = Check OS: The distro and version are correct
+os-distro-reason-version = { $os } does not support TDX hosts, { $range } does
+os-distro-reason-unknown = { $os } is not known to support TDX hosts
+os-distro-hint-current = Your current OS is: { $os }
+os-distro-hint-supported = The following OSs are supported:
+os-distro-hint-capable = The kernel and QEMU support TDX hosts nonetheless
+os-distro-hint-missing = The kernel and QEMU lack: { $missing }
+os-distro-hint-no-guarantee = There is no guarantee to other OS distros
+
+os-kernel-name = Check kernel version
+os-kernel-action = Check OS: The kernel supports TDX hosts
+os-kernel-reason = Kernel { $release } is older than { $min }, it cannot initialize the TDX module
+os-kernel-hint = Install Linux { $min } or later, or a distro kernel with TDX host support and list it in kernel_backports in { $path }
+
+os-kernel-config-name = Check kernel build configuration
+os-kernel-config-action = Check OS: The kernel is built for TDX hosts
+os-kernel-config-reason = The kernel is built without { $options }
+os-kernel-config-reason-missing = The build configuration of kernel { $release } was not found in /boot or /proc/config.gz
+
+os-cmdline-name = Check kernel command line
+os-cmdline-action = Check OS: The kernel command line is set for TDX hosts
+os-cmdline-reason-required = The kernel command line lacks { $params }
+os-cmdline-reason-recommended = The kernel command line lacks the recommended { $params }
+
+os-hibernate-name = Check hibernation
+os-hibernate-action = Check OS: Hibernation is disabled
+os-hibernate-reason = Hibernation is enabled, the kernel does not initialize the TDX module while it is
+os-hibernate-hint = Add nohibernate to the kernel command line
+
+os-kexec-name = Check kexec and kdump
+os-kexec-action = Check OS: kexec and kdump are not set up
+os-kexec-reason = kexec or kdump is set up, mind how it interacts with TDX
+os-kexec-hint-dump = Crash dumps do not include the private memory of TDs
+os-kexec-hint-module = The TDX module and its state do not carry over into a kexec'd kernel, which has to initialize it anew
+os-kexec-hint-severity = Set kexec_severity to info, warning or fail in { $path } to change how this is reported
+
+os-secure-boot-name = Check Secure Boot
+os-secure-boot-action = Check OS: The TDX stack loads under Secure Boot
+os-secure-boot-reason-unknown = Whether Secure Boot is on could not be read from the EFI variables or mokutil
+os-secure-boot-reason-unsigned = Secure Boot is on and the unsigned modules { $modules } will not load
+os-secure-boot-hint-mok = The out-of-tree modules { $modules } load only when signed with a key enrolled in the MOK list (mokutil --import)
+os-secure-boot-hint-lockdown = Secure Boot put the kernel in lockdown ({ $mode }): MSR writes are refused and checks reading MSRs may be skipped
+
+os-swap-name = Check swap
+os-swap-action = Check OS: Swap does not hold VM memory
+os-swap-reason = { $mib } MiB of swap is active with vm.swappiness = { $swappiness }, the memory of VMs and the shared memory of TDs may be swapped out
+os-swap-hint-private = TD private memory cannot be swapped out, swap does not make room for it
+os-swap-hint = Keep VM memory in RAM with vm.swappiness = { $max } or lower, or turn swap off with swapoff -a
+os-overcommit-name = Check memory overcommit
+os-overcommit-action = Check OS: Memory is not overcommitted unconditionally
+os-overcommit-reason = vm.overcommit_memory = 1 promises VMs memory the host may not have, TD private memory cannot be swapped or reclaimed so the OOM killer ends TDs instead
+os-overcommit-reason-unreadable = Unable to read vm.overcommit_memory
+os-overcommit-hint = Let the kernel refuse allocations it cannot back with vm.overcommit_memory = 0
+os-memlock-name = Check the memory lock limit
+os-memlock-action = Check OS: The VMM may lock the memory of the planned TDs
+os-memlock-reason = The VMM may lock { $limit } by { $source }, less than the { $size } MiB of the planned TDs
+os-memlock-reason-no-size = The size of the planned TDs is unknown, set td_memory_mib in { $path }
+os-memlock-reason-unreadable = Unable to read the memory lock limit from { $source }
+os-memlock-hint = Raise LimitMEMLOCK of the libvirt daemon in a systemd drop-in, or memlock in /etc/security/limits.conf for QEMU run by hand; locking is needed for mem-lock=on and device assignment
+
+os-mac-policy-name = Check SELinux and AppArmor
+os-mac-policy-action = Check OS: SELinux and AppArmor let QEMU open the KVM and SGX devices
+os-mac-policy-reason = The security policy keeps QEMU from opening { $devices }
+os-mac-policy-reason-logged = The audit log shows QEMU was denied { $devices }
+os-mac-policy-hint-restorecon = The device nodes are not labeled for QEMU, relabel them with: restorecon -v { $devices }
+os-mac-policy-hint-ausearch = Inspect QEMU's denials with: ausearch -m AVC -c qemu-kvm
+os-mac-policy-hint-apparmor = Allow libvirt's QEMU the device nodes by adding rules like "{ $rule }" to { $path } and reloading libvirtd
+
+## BIOS
+
+bios-firmware-name = Check BIOS release
+bios-firmware-action = Check BIOS: The firmware release supports TDX
+bios-firmware-reason = { $platform } needs firmware { $required }, but runs { $version }; update the BIOS
+bios-firmware-reason-unknown = No minimum firmware is known for { $platform }, it can be added to { $path }
+bios-firmware-reason-unparsable = BIOS { $version } cannot be compared with { $required }
+bios-firmware-reason-no-dmi = The system vendor is not in DMI
+bios-firmware-required-released = released on or after { $date }
+bios-sgx-name = Check SGX enabled
+bios-sgx-action = Check BIOS: SGX = Enabled
+bios-sgx-reason = The bit 18 of MSR 0x3a should be 1
+
+bios-feature-control-name = Check feature control is locked
+bios-feature-control-action = Check BIOS: IA32_FEATURE_CONTROL is locked with SGX enabled
+bios-feature-control-reason-unlocked = The bit 0 (lock) of MSR 0x3a should be 1
+bios-feature-control-reason-launch-control = The bit 17 (SGX launch control) of MSR 0x3a should be 1
+
+sgx-epc-name = Check SGX EPC size
+sgx-epc-action = Check CPUID 0x12: The EPC is large enough for the quoting enclaves
+sgx-epc-reason-missing = CPUID leaf 0x12 enumerates no EPC section
+sgx-epc-reason-small = The EPC has { $size } MiB, less than the { $min } MiB expected
+
+sgx-driver-name = Check SGX driver
+sgx-driver-action = Check OS: The in-kernel SGX driver is in use
+sgx-driver-reason-legacy = The legacy out-of-tree { $driver } SGX driver is loaded, but the kernel has its own SGX driver
+sgx-driver-reason-missing = No SGX driver is loaded, /dev/sgx_enclave does not exist
+sgx-driver-hint-remove = Unload the { $module } module, uninstall its package (e.g. the DKMS driver) and reboot
+
+sgx-registration-name = Check SGX multi-package registration
+sgx-registration-action = Check SGX: The platform is registered by the MP registration agent
+sgx-registration-reason-missing = The MP registration agent (mpa_registration_tool) is not installed
+sgx-registration-reason-failed = The MP registration agent failed to register the platform
+sgx-registration-reason-pending = The platform registration has not completed
+sgx-registration-hint-install = Install the sgx-ra-service / mpa_registration_tool package from the Intel SGX DCAP repository
+sgx-registration-hint-log = See { $path } for the registration progress
+
+sgx-platform-manifest-name = Check SGX platform manifest
+sgx-platform-manifest-action = Check SGX: A platform manifest exists or the platform is registered
+sgx-platform-manifest-reason = There is no platform manifest ({ $path }) and the platform is not registered, PCK certificates cannot be provisioned
+sgx-platform-manifest-hint-found = Platform manifest: { $path }, last changed { $days } days ago
+
+bios-tdx-name = Check TDX enabled
+bios-tdx-action = Check BIOS: TDX = Enabled
+bios-tdx-reason = The bit 11 of MSR 0x1401 should be 1
+
+bios-tme-name = Check TME enabled
+bios-tme-action = Check BIOS: TME = Enabled
+bios-tme-reason = The bit 1 of MSR 0x982 should be 1.
+
+bios-tme-capability-name = Check TME configuration is supported by the CPU
+bios-tme-capability-action = Check BIOS: TME configuration within IA32_TME_CAPABILITY
+bios-tme-capability-reason-keyids = The BIOS allocated { $requested } KeyID bits, but the CPU supports { $max }
+bios-tme-capability-reason-bypass = TME bypass is enabled, but the CPU does not support it
+
+bios-tme-algorithm-name = Check TME encryption algorithm
+bios-tme-algorithm-action = Check BIOS: TME encryption algorithm
+bios-tme-algorithm-hint = Active TME algorithm: { $algorithm }
+bios-tme-algorithm-reason-tdx = TDX needs AES-XTS-128 enabled for MKTME keys, the BIOS enabled: { $algorithms }
+bios-tme-algorithm-reason-policy = The active TME algorithm is { $active }, but { $path } expects { $expected }
+
+bios-tdx-integrity-name = Check TDX memory integrity mode
+bios-tdx-integrity-action = Check BIOS: TDX memory integrity is as expected
+bios-tdx-integrity-reason-unsupported = Cryptographic integrity is enabled, but IA32_TME_CAPABILITY does not report AES-XTS-128 with integrity
+bios-tdx-integrity-reason-policy = TDX memory integrity is { $active }, { $path } expects { $expected }
+bios-tdx-integrity-hint = TDX memory integrity: { $mode }
+
+bios-tme-mt-name = Check TME-MT/TME-MK enabled
+bios-tme-mt-action = Check BIOS: TME-MT/TME-MK = Enabled
+bios-tme-mt-reason = The bit 1 of MSR 0x982 should be 1.
+bios-tme-mt-hint = Total Memory Encryption Multi-Tenant (TME-MT) should be Enable
+
+bios-tdx-key-split-name = Check TDX Key Split != 0
+bios-tdx-key-split-action = Check BIOS: TDX Key Split != 0
+bios-tdx-key-split-reason = TDX Key Split should be non-zero
+
+bios-tdx-keyids-name = Check TDX private KeyIDs > 0
+bios-tdx-keyids-action = Check BIOS: TDX private KeyIDs > 0
+bios-tdx-keyids-reason = No KeyIDs are reserved for TDX (bits 63:32 of MSR 0x87), no TD can run
+
+bios-msr-consistency-name = Check BIOS settings agree across CPUs
+bios-msr-consistency-action = Check BIOS: MSRs 0x3a, 0x1401, 0x981 and 0x982 are the same on every CPU
+bios-msr-consistency-reason = The MSRs of { $where } differ from the other CPUs, the firmware provisioned them inconsistently
+
+bios-seamrr-name = Check SEAM range register
+bios-seamrr-action = Check BIOS: SEAMRR is configured and enabled
+bios-seamrr-reason-unsupported = The CPU does not support SEAMRR, bit 15 of MSR 0xfe should be 1
+bios-seamrr-reason-unconfigured = SEAMRR is not configured, bit 3 of MSR 0x1400 should be 1
+bios-seamrr-reason-disabled = SEAMRR is not enabled, bit 11 of MSR 0x1401 should be 1
+bios-seamrr-reason-range = The SEAM range in MSRs 0x1400 and 0x1401 is not a contiguous, aligned range
+
+bios-sgx-reg-server-name = Check SGX registration server
+bios-sgx-reg-server-action = Check BIOS: SGX registration server
+bios-sgx-reg-server-reason = The SGX registration server is { $server }, but { $path } expects { $expected }
+bios-sgx-reg-server-hint-sbx = SGX registration server is SBX
+bios-sgx-reg-server-hint-liv = SGX registration server is LIV
+
+bios-volatile-memory-1lm-name = Volatile Memory should be 1LM
+bios-volatile-memory-1lm-action = Check BIOS: Volatile Memory should be 1LM
+bios-volatile-memory-1lm-hint = Volatile Memory (or Volatile Memory Mode) should be 1LM
+bios-volatile-memory-1lm-reason-2lm = Memory is in 2LM mode, DRAM caches persistent memory
+bios-volatile-memory-1lm-reason-pmem = Persistent memory is installed, check it is in App Direct mode rather than 2LM
+
+bios-tme-bypass-name = TME Bypass is enabled
+bios-tme-bypass-action = Check BIOS: TME Bypass = Enabled
+bios-tme-bypass-reason = The bit 31 of MSR 0x982 should be 1
+bios-tme-bypass-hint-disabled = The TME Bypass has not been enabled now.
+bios-tme-bypass-hint = It's better to enable TME Bypass for traditional non-confidential workloads.
+
+bios-seam-loader-name = SEAM Loader is enabled
+bios-seam-loader-action = Check BIOS: SEAM Loader = Enabled
+bios-seam-loader-reason-seamrr = The SEAM range is not configured, so the SEAM loader cannot run
+
+bios-seamldr-version-name = Check NP-SEAMLDR version
+bios-seamldr-version-action = Check BIOS: The NP-SEAMLDR is recent enough for the TDX module
+bios-seamldr-version-reason = NP-SEAMLDR { $version } is older than TDX module { $module } needs, update the BIOS
+bios-seamldr-version-reason-unknown = The kernel does not report the NP-SEAMLDR version
+bios-seamldr-version-hint = NP-SEAMLDR { $version }
+
+## TDX module
+
+tdx-module-name = Check TDX module initialized
+tdx-module-action = Check TDX Module: The module is initialized
+tdx-module-reason = TDX module is required
+tdx-module-reason-failed = The kernel failed to initialize the TDX module: { $causes }
+tdx-module-cause-cmr = memory outside the convertible memory regions (CMRs)
+tdx-module-hint-cmr = All memory the kernel manages must be TDX convertible: remove CXL or persistent memory from the system memory map, or check the BIOS memory configuration
+tdx-module-cause-hotplug = memory was hot-added
+tdx-module-hint-hotplug = Memory hot-added after boot cannot be TDX convertible, do not online it while TDX is in use
+tdx-module-cause-keyids = too few private KeyIDs
+tdx-module-hint-keyids = Raise the TDX key split in the BIOS
+tdx-module-cause-hibernate = hibernation is enabled
+tdx-module-hint-hibernate = Add nohibernate to the kernel command line
+tdx-module-cause-s3 = ACPI S3 is enabled
+tdx-module-hint-s3 = Disable ACPI S3 (suspend to RAM) in the BIOS
+tdx-module-cause-not-loaded = the BIOS did not load the module
+tdx-module-hint-not-loaded = Check the SEAM loader is enabled in the BIOS and a TDX module is installed in the firmware
+tdx-module-cause-seamcall = a SEAMCALL to the module failed
+tdx-module-hint-seamcall = Update the BIOS and the TDX module, the module may be too old for the kernel
+
+tdx-module-version-name = Check TDX module version
+tdx-module-version-action = Check TDX Module: The module version is recent enough
+tdx-module-version-reason = TDX module { $version } is older than { $min }
+tdx-module-version-reason-unknown = The kernel does not report the TDX module version
+tdx-module-version-reason-invalid = Invalid min_tdx_module_version in { $path }: { $min }
+tdx-module-version-hint = TDX module { $version }, build { $build } of { $date }
+
+tdx-cmr-name = Check convertible memory regions
+tdx-cmr-action = Check TDX Module: System RAM is convertible memory
+tdx-cmr-reason = { $uncovered } MiB of { $ram } MiB system RAM is outside the convertible memory regions and unusable for TDs
+tdx-cmr-reason-unknown = The kernel did not log the convertible memory regions
+
+tdx-runtime-update-name = Check TD-preserving TDX module updates
+tdx-runtime-update-action = Check TDX Module: The module can be updated without stopping TDs
+tdx-runtime-update-reason-module = The TDX module does not support TD-preserving updates
+tdx-runtime-update-reason-kernel = The kernel cannot update the TDX module at runtime
+tdx-runtime-update-hint = Module updates can be applied while TDs keep running, see tdxhost module update
+
+tdx-feature-name = Check TDX module feature: { $feature }
+tdx-feature-action = Check TDX Module: The module supports { $feature }
+tdx-feature-reason = The TDX module is built without { $feature }
+tdx-feature-reason-unknown = The kernel does not report the TDX module features
+
+## Attestation
+
+attestation-qcnl-name = Check quote provider (QCNL) configuration
+attestation-qcnl-action = Check attestation: The PCCS in the QCNL configuration is valid and reachable
+attestation-qcnl-reason-unreadable = Unable to read { $path }: { $error }
+attestation-qcnl-reason-invalid = { $path } is not valid: { $error }
+attestation-qcnl-reason-no-url = No PCCS URL (pccs_url) is configured
+attestation-qcnl-reason-bad-url = { $url } is not an http(s) URL
+attestation-qcnl-reason-unreachable = The PCCS at { $url } is not reachable: { $error }
+attestation-qcnl-reason-insecure = TLS certificate verification is disabled (use_secure_cert)
+attestation-qcnl-reason-http = The PCCS is accessed over plain HTTP
+attestation-qcnl-hint = Point pccs_url in { $path } at your PCCS or the Intel PCS
+
+attestation-collateral-name = Check attestation collateral
+attestation-collateral-action = Check attestation: The PCCS has current TCB info and QE identity
+attestation-collateral-reason-no-fmspc = The platform FMSPC is unknown, set fmspc in { $path }
+attestation-collateral-reason-missing = The PCCS has no { $what } ({ $url }): { $error }
+attestation-collateral-reason-expired = The { $what } expired on { $date }
+attestation-collateral-reason-recovery = A TCB recovery was released on { $date }, after the BIOS; update the BIOS to take it
+
+attestation-aesmd-name = Check AESM daemon
+attestation-aesmd-action = Check attestation: aesmd is running with the ECDSA quoting plugins
+attestation-aesmd-reason-unused = aesmd is not installed, quotes are not generated through it
+attestation-aesmd-reason-disabled = aesmd.service is not enabled
+attestation-aesmd-reason-inactive = aesmd.service is not running
+attestation-aesmd-reason-no-socket = The aesmd socket { $path } does not exist
+attestation-aesmd-reason-plugins = The ECDSA quoting plugins are missing: { $plugins }
+
+attestation-dcap-versions-name = Check DCAP component versions
+attestation-dcap-versions-action = Check attestation: The DCAP components support TDX and are from one release
+attestation-dcap-versions-reason-none = No DCAP quoting components are installed
+attestation-dcap-versions-reason-old = { $package } { $version } is older than { $min }, the first release supporting TDX
+attestation-dcap-versions-reason-skew = The DCAP components are from different releases ({ $releases })
+
+## Guest launch
+
+guest-qemu-name = Check QEMU can run TDs
+guest-qemu-action = Check QEMU: The tdx-guest object and TD machine properties are available
+guest-qemu-reason-missing = No QEMU binary was found
+guest-qemu-reason-unsupported = { $version } lacks { $missing }
+guest-qemu-hint = TDs need QEMU { $min } or later, or a distribution build with TDX support
+
+guest-firmware-name = Check TDX guest firmware is installed
+guest-firmware-action = Check guest firmware: A TDVF image is installed
+guest-firmware-reason-missing = No firmware image with TDX support (TDVF metadata) was found
+guest-firmware-hint = Install the OVMF build with TDX support, e.g. edk2-ovmf on Fedora and RHEL, ovmf on Debian and Ubuntu
+
+## KVM
+
+kvm-module-name = Check the kvm_intel module is loaded
+kvm-module-action = Check KVM: The kvm_intel module is loaded
+kvm-module-reason-blacklisted = kvm_intel is blacklisted by { $source }
+kvm-module-reason-hypervisor = The host is a { $hypervisor } guest without nested virtualization, it does not expose VT-x
+kvm-module-reason-no-vmx = The CPU does not report VT-x
+kvm-module-reason-bios = VT-x is disabled in the BIOS
+kvm-module-reason-not-loaded = kvm_intel is not loaded
+
+kvm-supported-name = Check KVM is supported
+kvm-supported-action = Check KVM is supported
+kvm-supported-inaccessible = KVM device node (/dev/kvm) should be accessible
+kvm-supported-unreadable = Unable to read KVM device node file (/dev/kvm)
+kvm-supported-fix = Load the kvm_intel kernel module
+
+kvm-sgx-param-name = Check KVM SGX parameter enabled
+kvm-tdx-param-name = Check KVM TDX parameter enabled
+kvm-param-action = Check /sys/module/kvm_intel/parameters/{ $param } = Y (required)
+kvm-param-invalid = Parameter file ({ $path }) contains invalid value: { $value }
+kvm-param-unreadable = Unable to read parameter file: { $error }
+kvm-param-missing = Provided parameter does not exist: { $path }
+
+kvm-tdx-vm-name = Check KVM creates TD VMs
+kvm-tdx-vm-action = Check KVM: The TDX VM type is offered
+kvm-tdx-vm-reason-missing = KVM does not offer the TDX VM type, only: { $types }
+kvm-tdx-vm-reason-unreadable = Unable to query the VM types of KVM: { $error }
+kvm-tdx-vm-hint = kvm_intel only offers TDs once the TDX module initialized, see the TDX module checks and dmesg | grep -i tdx
+
+kvm-private-memory-name = Check KVM private memory support
+kvm-private-memory-action = Check KVM: guest_memfd and private memory attributes are offered
+kvm-private-memory-reason = KVM lacks { $missing }, TDs cannot be given private memory
+kvm-private-memory-reason-unreadable = Unable to query the private memory capabilities of KVM: { $error }
+kvm-private-memory-hint = Kernel { $release } is too old or built without CONFIG_KVM_PRIVATE_MEM: guest_memfd came with Linux { $guest_memfd }, TDX host support with Linux { $tdx }
+
+kvm-devices-name = Check the KVM and SGX device nodes
+kvm-devices-action = Check KVM: The VMM users can open the KVM and SGX device nodes
+kvm-devices-reason-kvm = /dev/kvm belongs to group { $group } with mode { $mode }, expected group kvm with mode 660
+kvm-devices-reason-rules = /dev/{ $device } has no udev rule setting its group and mode
+kvm-devices-reason-user = { $user } is not in group { $group }
+kvm-devices-hint-user = Add { $user } to the group with: usermod -aG { $group } { $user }
+kvm-devices-fix = Install the standard KVM and SGX udev rules to { $path }
+
+## Wizard
+
+wizard-title = TDX BIOS remediation wizard
+wizard-vendor = Detected system vendor: { $vendor }
+wizard-vendor-unknown = System vendor could not be detected, showing Intel reference BIOS paths.
+wizard-nothing-to-do = All BIOS related checks passed, nothing to do.
+wizard-pending = { $count } BIOS setting(s) need attention.
+wizard-setting = Set: { $setting } = { $value }
+wizard-menu = Menu: { $path }
+wizard-reboot = Change the setting, save and reboot. After the reboot run `tdxhost wizard` again to continue.
+wizard-prompt-verify = [r]e-verify now, [s]kip, [q]uit:
+wizard-prompt-confirm = [c]onfirm it is set, [s]kip, [q]uit:
+wizard-verified = Verified.
+wizard-still-failing = Still not set: { $reason }
+wizard-done = Wizard finished: { $fixed } fixed, { $remaining } remaining.
+
+## Fleet
+
+fleet-simulated = --simulate inspects a fixture, not the hosts of an inventory
+fleet-inventory-invalid = Invalid inventory { $path }
+fleet-checked = Checked { $host }
+fleet-failed = Could not check { $host }
+fleet-not-ready = { $count } of { $total } hosts are not ready
+fleet-host-ready = ready
+fleet-host-not-ready = not ready: { $checks }
+fleet-host-error = not checked: { $error }
+fleet-title = TDX host readiness
+fleet-ready = ready
+fleet-not-ready-cell = not ready
+fleet-unreachable = not checked
+
+## BIOS checklist
+
+checklist-title = BIOS checklist for { $model }, BIOS { $bios }
+checklist-set = Set { $setting } to { $value } ({ $check })
+checklist-verify = Verify { $setting } is { $value } ({ $check })
+checklist-reboot = Save the settings and reboot, then run `tdxhost ok` to confirm.
+
+## Fix
+
+fix-prompt = Apply this fix? [y/N]
+fix-nothing-to-do = No failing check has an automatable fix.
+fix-no-fix-for-check = Check { $id } is not failing or has no automatable fix
+fix-applied-verified = Applied, the check passes now.
+fix-applied-unverified = Applied, but the check still does not pass.
+fix-applied-reboot = Applied, takes effect after a reboot.
+fix-reboot-pending = A reboot is pending for the applied fixes to take effect.
+fix-failed = { $count } fix(es) could not be applied
+fix-simulated = Fixes cannot be applied to a simulated host
+modprobe-fix = Set { $module } option { $option } in { $path }
+grub-fix = Add { $params } to the kernel command line in the GRUB configuration
+
+## Module info
+
+module-unknown = unknown
+module-info-initialized = Initialized:
+module-info-yes = yes
+module-info-no = no
+module-info-version = Version:
+module-info-build = Build:
+module-info-build-date = Build date:
+module-info-attributes = Attributes:
+module-info-features = Features:
+module-info-seamldr = NP-SEAMLDR:
+module-info-keyids = KeyIDs:
+module-info-keyids-split = { $mktme } MKTME, { $tdx } TDX private
+module-info-pamt = PAMT:
+module-info-cmrs = Convertible memory regions:
+
+## Module update
+
+module-update-current = Running TDX module: { $version }
+module-update-prompt = Install { $image } now? [y/N]
+module-update-installing = Installing { $image }, TDs keep running...
+module-update-done = TDX module updated: { $from } -> { $to }
+module-update-unchanged = The kernel reported success, but the TDX module is still { $version }
+module-update-preflight-failed = Cannot update the TDX module: { $action }: { $reason }
+module-update-rollback = The previous TDX module is still running, TDs are unaffected. To return to it after an update, install its image with tdxhost module update.
+module-update-simulated = The TDX module of a simulated host cannot be updated
+
+## Capabilities
+
+capabilities-unavailable = Cannot query the TDX capabilities of KVM: { $error }
+capabilities-attributes = TD attributes:
+capabilities-xfam = XFAM:
+capabilities-cpuid = Configurable CPUID bits:
+
+## Smoke test
+
+smoke-test-stage-create-vm = Create a VM of the TDX type
+smoke-test-stage-init-vm = Initialize the TD
+smoke-test-stage-create-vcpu = Create a vCPU
+smoke-test-stage-init-vcpu = Initialize the vCPU
+smoke-test-stage-add-memory = Add a measured page of guest code
+smoke-test-stage-finalize = Finalize the TD measurement
+smoke-test-stage-run = Run the vCPU until the guest code exits
+smoke-test-seamcalls = SEAMCALLs of this stage: { $seamcalls }
+smoke-test-passed = A TD was created and ran on this host
+smoke-test-failed = The TD smoke test failed
+smoke-test-simulated = A simulated host cannot run TDs
+
+## Verify stack
+
+verify-stack-component = Component
+verify-stack-version = Version
+verify-stack-not-installed = not installed
+verify-stack-violation = { $component } { $version } does not satisfy { $requirement }
+verify-stack-compatible = The installed versions are compatible
+verify-stack-incompatible = The installed versions are not known to work together
+
+## TD list
+
+td-list-pid = PID
+td-list-name = NAME
+td-list-vmm = VMM
+td-list-memory = MEMORY
+td-list-keyids = KEYIDS
+td-list-none = No TDs are running
+td-list-keyids-used = TDX KeyIDs in use: { $used } of { $total }
+
+td-inspect-not-found = No running TD has the PID or UUID { $target }
+td-inspect-pid = PID:
+td-inspect-name = Name:
+td-inspect-uuid = UUID:
+td-inspect-vmm = VMM:
+td-inspect-libvirt = libvirt domain:
+td-inspect-vcpus = vCPUs:
+td-inspect-memory = Configured memory:
+td-inspect-mapped = Mapped memory:
+td-inspect-shmem = Shared memory:
+td-inspect-anon = Anonymous memory:
+td-inspect-keyids = Private KeyIDs:
+td-inspect-keyids-value = { $count } (KVM does not expose the KeyID value)
+
+## Keys
+
+keys-mktme = MKTME KeyIDs:
+keys-tdx = TDX KeyIDs:
+keys-global = Kept by the kernel:
+keys-capacity = Available to TDs:
+keys-in-use = In use by TDs:
+keys-free = Free:
+keys-free-tds = { $free } (room for { $free } more TDs)
+
+## Info
+
+info-platform = Platform:
+info-board = Board:
+info-bios = BIOS:
+info-cpu = CPU:
+info-sockets = Sockets:
+info-microcode = Microcode:
+info-os = OS:
+info-secure-boot = Secure Boot:
+info-tme = TME:
+info-enabled = enabled
+info-disabled = disabled
+info-keyids = KeyIDs:
+info-keyids-value = { $mktme } MKTME, { $tdx } TDX, { $used } of { $capacity } in use by TDs
+info-module-initialized = { $version } (initialized)
+info-module-uninitialized = { $version } (not initialized)
+info-service-missing = not installed
+
+## MSR
+
+msr-invalid = { $msr } is neither an MSR index nor the name of a known MSR
+msr-on-cpus = on CPUs { $cpus }
+
+## CPUID
+
+cpuid-invalid = { $number } is not a CPUID leaf or subleaf
+
+## Topology
+
+topology-package = Socket { $id }: { $cores } cores, { $threads } threads
+topology-cpus = CPUs:
+topology-nodes = NUMA nodes:
+topology-tme = TME:
+topology-tdx = TDX:
+topology-enabled = enabled
+topology-disabled = disabled
+topology-node = NUMA node { $id }:
+topology-memory = Memory:
+topology-memory-value = { $mib } MiB
+
+## Collect
+
+collect-written = Support bundle written to { $path }
+collect-missing = { $count } items could not be collected, see missing.txt in the bundle
+collect-review = Review the bundle before sharing it: it holds the host's configuration files and kernel log
+
+## Plugins
+
+plugin-failed = Plugin failed: { $error }
+plugin-ignored = Warning: ignoring plugin: { $error }
+plugin-duplicate-id = Warning: ignoring plugin { $path }: check id { $id } is already in use
+plugin-untrusted = Warning: ignoring plugin { $path }: it must be owned by root and writable by root only
@@ -0,0 +1,593 @@
+# Simplified Chinese messages for tdxhost. Every message of the en-US
+# catalog must be translated here too, `cargo test` lists the missing ones.
+
+## Report
+
+report-platform = 平台：{ $model }，BIOS { $bios }
+section-required = 必需的功能与设置
+section-optional = 可选的功能与设置
+result-reason = 原因：{ $reason }
+result-docs = 参见：{ $url }
+hints-hidden = { $count } 项检查有提示，使用 --verbose 重新运行以查看
+reason-manual = 程序无法自动检查，请手动检查。
+reason-needs-root = 需要 root 权限，请以 root 身份重新运行。
+reason-lockdown = 内核锁定（{ $mode }）阻止了 MSR 访问
+hint-lockdown-alternative = 无法访问 MSR 时，请检查：{ $command }
+probe-failed = 无法确定：{ $error }
+reason-msr-partial = { $reason }，{ $where } 上不满足
+cpu-location = 处理器插槽 { $packages }（CPU { $cpus }）
+cpu-location-unknown = CPU { $cpus }
+error-required-failed = 一项或多项必需检查未通过
+error-needs-root = 已跳过需要特权的检查，结果不完整
+warning-unprivileged = 未以 root 身份运行：已跳过 MSR 和内核日志检查。
+warning-lockdown = 内核锁定已启用（{ $mode }），MSR 读取可能被阻止。
+guest-detected = tdxhost 检查主机是否就绪；您似乎位于虚拟机中（虚拟机监控器：{ $hypervisor }）。请在主机上运行，或使用 --allow-guest 强制运行。
+guest-detected-nested = tdxhost 检查主机是否就绪；此主机本身是 { $hypervisor } 下启用了嵌套 VMX 的虚拟机。TDX 无法在嵌套虚拟化下工作：TDX 模块只能在裸机上初始化。请在物理主机上运行 tdxhost。
+guest-detected-td = tdxhost 检查主机是否就绪；您似乎位于 TDX 虚拟机中。`dmesg | grep -i tdx` 可显示 TD 的内存加密状态，客户机侧工具（如 Intel Trust Authority 客户端 trustauthority-cli）可对其进行证明。
+ok-host-simulated = --simulate 检查的是夹具数据，而不是 { $host }
+ok-output-json = --output 需要 --format json
+ok-output-format = --output 需要 --format json 或 ansible-facts
+ok-host-ansible-facts = --format ansible-facts 无法通过 SSH 检查主机，请直接在该主机上运行 tdxhost
+sign-run-failed = 无法运行 openssl
+sign-failed = 无法签名 { $path }：{ $error }
+remote-run-failed = 无法运行 { $program }
+remote-no-binary = 找不到 tdxhost 可执行文件
+remote-mktemp-failed = { $host } 上的 mktemp 退出，{ $status }：{ $error }
+remote-scp-failed = scp 到 { $host } 退出，{ $status }
+remote-tdxhost-failed = { $host } 上的 tdxhost 退出，{ $status }：{ $error }
+error-write = 无法写入 { $path }
+error-read = 无法读取 { $path }
+error-create = 无法创建 { $path }
+redfish-no-password = --redfish 需要在 { $variable } 中提供 BMC 密码
+
+## Shared BIOS hints
+
+hint-check-bios-settings = 请检查您的 BIOS 设置：
+hint-bios-path-differs = 不同的 BIOS 中此设置的路径可能不同。
+hint-bios-skip-missing = 如果您的 BIOS 菜单中没有此设置，请跳过。
+hint-bios-attributes-failed = 无法读取 BIOS 设置：{ $error }
+hint-bios-attribute-missing = 读取到的 BIOS 设置中没有 { $setting }
+reason-bios-attribute = BIOS 中 { $setting } 为 { $value }，应为 { $expected }
+
+## CPU and OS
+
+cpu-manufacturer-name = 检查 CPU 制造商 ID
+cpu-manufacturer-action = 检查 CPUID 0x0 制造商 ID = GenuineIntel
+cpu-manufacturer-reason = CPUID 制造商 ID 应为 GenuineIntel
+
+cpu-bare-metal-name = 检查裸机
+cpu-bare-metal-action = 检查 CPU：主机运行在裸机上
+cpu-bare-metal-reason = 主机是 { $hypervisor } 下的虚拟机，TDX 需要裸机
+cpu-bare-metal-reason-nested = 主机是 { $hypervisor } 下启用了嵌套 VMX 的虚拟机，TDX 无法在嵌套虚拟化下工作
+cpu-bare-metal-reason-td = 主机是一个 TD，TD 中无法运行 TD
+cpu-model-name = 检查 CPU 型号支持 TDX
+cpu-model-action = 检查 CPU：该型号支持 TDX
+cpu-model-reason = { $model } 不支持 TDX，需要 Sapphire Rapids、Emerald Rapids、Granite Rapids 或更新的至强处理器
+
+cpu-tme-name = 检查 CPU 枚举 TME
+cpu-tme-action = 检查 CPUID：CPU 枚举了 TDX 所依赖的 TME
+cpu-tme-reason = CPU 未枚举 TME：CPUID.07H:ECX[13] 应为 1
+
+cpu-errata-name = 检查已知的 CPU 勘误
+cpu-errata-action = 检查 CPU：没有适用的已知 TDX 勘误
+cpu-errata-reason = 此 CPU 适用以下已知勘误：{ $errata }
+
+os-distro-name = 检查操作系统发行版
+os-distro-action = 检查操作系统：发行版及版本正确
+os-distro-reason-version = { $os } 不支持 TDX 主机，支持的版本为 { $range }
+os-distro-reason-unknown = 未知 { $os } 是否支持 TDX 主机
+os-distro-hint-current = 您当前的操作系统为：{ $os }
+os-distro-hint-supported = 支持以下操作系统：
+os-distro-hint-capable = 内核和 QEMU 仍然支持 TDX 主机
+os-distro-hint-missing = 内核和 QEMU 缺少：{ $missing }
+os-distro-hint-no-guarantee = 不保证其他操作系统发行版可用
+
+os-kernel-name = 检查内核版本
+os-kernel-action = 检查操作系统：内核支持 TDX 主机
+os-kernel-reason = 内核 { $release } 早于 { $min }，无法初始化 TDX 模块
+os-kernel-hint = 请安装 Linux { $min } 或更新版本，或安装支持 TDX 主机的发行版内核并将其列入 { $path } 中的 kernel_backports
+
+os-kernel-config-name = 检查内核编译配置
+os-kernel-config-action = 检查操作系统：内核为 TDX 主机编译
+os-kernel-config-reason = 内核编译时未启用 { $options }
+os-kernel-config-reason-missing = 在 /boot 或 /proc/config.gz 中找不到内核 { $release } 的编译配置
+
+os-cmdline-name = 检查内核命令行
+os-cmdline-action = 检查操作系统：内核命令行已为 TDX 主机设置
+os-cmdline-reason-required = 内核命令行缺少 { $params }
+os-cmdline-reason-recommended = 内核命令行缺少推荐的 { $params }
+
+os-hibernate-name = 检查休眠
+os-hibernate-action = 检查操作系统：休眠已禁用
+os-hibernate-reason = 休眠已启用，启用休眠时内核不会初始化 TDX 模块
+os-hibernate-hint = 请在内核命令行中添加 nohibernate
+
+os-kexec-name = 检查 kexec 和 kdump
+os-kexec-action = 检查操作系统：未设置 kexec 和 kdump
+os-kexec-reason = 已设置 kexec 或 kdump，请注意它与 TDX 的相互影响
+os-kexec-hint-dump = 崩溃转储不包含 TD 的私有内存
+os-kexec-hint-module = TDX 模块及其状态不会保留到通过 kexec 启动的内核中，该内核需要重新初始化模块
+os-kexec-hint-severity = 在 { $path } 中将 kexec_severity 设置为 info、warning 或 fail 以更改报告方式
+
+os-secure-boot-name = 检查安全启动
+os-secure-boot-action = 检查操作系统：TDX 软件栈可在安全启动下加载
+os-secure-boot-reason-unknown = 无法从 EFI 变量或 mokutil 读取安全启动是否开启
+os-secure-boot-reason-unsigned = 安全启动已开启，未签名的模块 { $modules } 将无法加载
+os-secure-boot-hint-mok = 树外模块 { $modules } 只有使用已登记到 MOK 列表的密钥签名后才能加载（mokutil --import）
+os-secure-boot-hint-lockdown = 安全启动使内核进入锁定状态（{ $mode }）：MSR 写入被拒绝，读取 MSR 的检查可能被跳过
+
+os-swap-name = 检查交换空间
+os-swap-action = 检查操作系统：交换空间不保存虚拟机内存
+os-swap-reason = 已启用 { $mib } MiB 交换空间且 vm.swappiness = { $swappiness }，虚拟机内存和 TD 的共享内存可能被换出
+os-swap-hint-private = TD 私有内存无法换出，交换空间无法为其腾出空间
+os-swap-hint = 将 vm.swappiness 设置为 { $max } 或更低以使虚拟机内存保留在内存中，或使用 swapoff -a 关闭交换空间
+os-overcommit-name = 检查内存超额分配
+os-overcommit-action = 检查操作系统：内存未被无条件超额分配
+os-overcommit-reason = vm.overcommit_memory = 1 会向虚拟机承诺主机可能没有的内存，TD 私有内存无法换出或回收，因此 OOM killer 会终止 TD
+os-overcommit-reason-unreadable = 无法读取 vm.overcommit_memory
+os-overcommit-hint = 设置 vm.overcommit_memory = 0，使内核拒绝无法满足的内存分配
+os-memlock-name = 检查内存锁定限制
+os-memlock-action = 检查操作系统：VMM 可以锁定计划中 TD 的内存
+os-memlock-reason = { $source } 允许 VMM 锁定 { $limit }，少于计划中 TD 的 { $size } MiB
+os-memlock-reason-no-size = 计划中 TD 的大小未知，请在 { $path } 中设置 td_memory_mib
+os-memlock-reason-unreadable = 无法从 { $source } 读取内存锁定限制
+os-memlock-hint = 在 systemd drop-in 中提高 libvirt 守护进程的 LimitMEMLOCK，手动运行 QEMU 时则提高 /etc/security/limits.conf 中的 memlock；mem-lock=on 和设备直通需要锁定内存
+
+os-mac-policy-name = 检查 SELinux 和 AppArmor
+os-mac-policy-action = 检查操作系统：SELinux 和 AppArmor 允许 QEMU 打开 KVM 和 SGX 设备
+os-mac-policy-reason = 安全策略阻止 QEMU 打开 { $devices }
+os-mac-policy-reason-logged = 审计日志显示 QEMU 被拒绝访问 { $devices }
+os-mac-policy-hint-restorecon = 设备节点没有适用于 QEMU 的标签，请使用以下命令重新标记：restorecon -v { $devices }
+os-mac-policy-hint-ausearch = 使用以下命令查看 QEMU 被拒绝的记录：ausearch -m AVC -c qemu-kvm
+os-mac-policy-hint-apparmor = 在 { $path } 中添加类似 "{ $rule }" 的规则并重新加载 libvirtd，以允许 libvirt 的 QEMU 访问设备节点
+
+## BIOS
+
+bios-firmware-name = 检查 BIOS 版本
+bios-firmware-action = 检查 BIOS：固件版本支持 TDX
+bios-firmware-reason = { $platform } 需要固件 { $required }，但当前运行 { $version }；请更新 BIOS
+bios-firmware-reason-unknown = 未知 { $platform } 的最低固件版本，可将其添加到 { $path }
+bios-firmware-reason-unparsable = 无法将 BIOS { $version } 与 { $required } 进行比较
+bios-firmware-reason-no-dmi = DMI 中没有系统厂商信息
+bios-firmware-required-released = 发布于 { $date } 或之后
+bios-sgx-name = 检查 SGX 已启用
+bios-sgx-action = 检查 BIOS：SGX = Enabled
+bios-sgx-reason = MSR 0x3a 的第 18 位应为 1
+
+bios-feature-control-name = 检查 Feature Control 已锁定
+bios-feature-control-action = 检查 BIOS：IA32_FEATURE_CONTROL 已锁定且 SGX 已启用
+bios-feature-control-reason-unlocked = MSR 0x3a 的第 0 位（锁定）应为 1
+bios-feature-control-reason-launch-control = MSR 0x3a 的第 17 位（SGX 启动控制）应为 1
+
+sgx-epc-name = 检查 SGX EPC 大小
+sgx-epc-action = 检查 CPUID 0x12：EPC 足够容纳引用飞地
+sgx-epc-reason-missing = CPUID 叶 0x12 未枚举任何 EPC 区段
+sgx-epc-reason-small = EPC 为 { $size } MiB，少于预期的 { $min } MiB
+
+sgx-driver-name = 检查 SGX 驱动
+sgx-driver-action = 检查操作系统：正在使用内核自带的 SGX 驱动
+sgx-driver-reason-legacy = 已加载旧的树外 { $driver } SGX 驱动，但内核自带 SGX 驱动
+sgx-driver-reason-missing = 未加载 SGX 驱动，/dev/sgx_enclave 不存在
+sgx-driver-hint-remove = 卸载 { $module } 模块，卸载其软件包（如 DKMS 驱动）并重启
+
+sgx-registration-name = 检查 SGX 多插槽注册
+sgx-registration-action = 检查 SGX：平台已由 MP 注册代理注册
+sgx-registration-reason-missing = 未安装 MP 注册代理（mpa_registration_tool）
+sgx-registration-reason-failed = MP 注册代理注册平台失败
+sgx-registration-reason-pending = 平台注册尚未完成
+sgx-registration-hint-install = 从 Intel SGX DCAP 软件仓库安装 sgx-ra-service / mpa_registration_tool 软件包
+sgx-registration-hint-log = 注册进度参见 { $path }
+
+sgx-platform-manifest-name = 检查 SGX 平台清单
+sgx-platform-manifest-action = 检查 SGX：平台清单存在或平台已注册
+sgx-platform-manifest-reason = 没有平台清单（{ $path }）且平台未注册，无法获取 PCK 证书
+sgx-platform-manifest-hint-found = 平台清单：{ $path }，最后修改于 { $days } 天前
+
+bios-tdx-name = 检查 TDX 已启用
+bios-tdx-action = 检查 BIOS：TDX = Enabled
+bios-tdx-reason = MSR 0x1401 的第 11 位应为 1
+
+bios-tme-name = 检查 TME 已启用
+bios-tme-action = 检查 BIOS：TME = Enabled
+bios-tme-reason = MSR 0x982 的第 1 位应为 1。
+
+bios-tme-capability-name = 检查 CPU 支持 TME 配置
+bios-tme-capability-action = 检查 BIOS：TME 配置在 IA32_TME_CAPABILITY 范围内
+bios-tme-capability-reason-keyids = BIOS 分配了 { $requested } 个 KeyID 位，但 CPU 只支持 { $max } 个
+bios-tme-capability-reason-bypass = 已启用 TME Bypass，但 CPU 不支持
+
+bios-tme-algorithm-name = 检查 TME 加密算法
+bios-tme-algorithm-action = 检查 BIOS：TME 加密算法
+bios-tme-algorithm-hint = 当前 TME 算法：{ $algorithm }
+bios-tme-algorithm-reason-tdx = TDX 需要为 MKTME 密钥启用 AES-XTS-128，BIOS 启用的是：{ $algorithms }
+bios-tme-algorithm-reason-policy = 当前 TME 算法为 { $active }，但 { $path } 要求 { $expected }
+
+bios-tdx-integrity-name = 检查 TDX 内存完整性模式
+bios-tdx-integrity-action = 检查 BIOS：TDX 内存完整性符合预期
+bios-tdx-integrity-reason-unsupported = 已启用加密完整性，但 IA32_TME_CAPABILITY 未报告带完整性的 AES-XTS-128
+bios-tdx-integrity-reason-policy = TDX 内存完整性为 { $active }，{ $path } 要求 { $expected }
+bios-tdx-integrity-hint = TDX 内存完整性：{ $mode }
+
+bios-tme-mt-name = 检查 TME-MT/TME-MK 已启用
+bios-tme-mt-action = 检查 BIOS：TME-MT/TME-MK = Enabled
+bios-tme-mt-reason = MSR 0x982 的第 1 位应为 1。
+bios-tme-mt-hint = Total Memory Encryption Multi-Tenant (TME-MT) 应设置为 Enable
+
+bios-tdx-key-split-name = 检查 TDX Key Split != 0
+bios-tdx-key-split-action = 检查 BIOS：TDX Key Split != 0
+bios-tdx-key-split-reason = TDX Key Split 应为非零值
+
+bios-tdx-keyids-name = 检查 TDX 私有 KeyID > 0
+bios-tdx-keyids-action = 检查 BIOS：TDX 私有 KeyID > 0
+bios-tdx-keyids-reason = 没有为 TDX 保留 KeyID（MSR 0x87 的第 63:32 位），无法运行任何 TD
+
+bios-msr-consistency-name = 检查各 CPU 的 BIOS 设置一致
+bios-msr-consistency-action = 检查 BIOS：每个 CPU 上的 MSR 0x3a、0x1401、0x981 和 0x982 相同
+bios-msr-consistency-reason = { $where } 的 MSR 与其他 CPU 不同，固件的配置不一致
+
+bios-seamrr-name = 检查 SEAM 范围寄存器
+bios-seamrr-action = 检查 BIOS：SEAMRR 已配置并启用
+bios-seamrr-reason-unsupported = CPU 不支持 SEAMRR，MSR 0xfe 的第 15 位应为 1
+bios-seamrr-reason-unconfigured = SEAMRR 未配置，MSR 0x1400 的第 3 位应为 1
+bios-seamrr-reason-disabled = SEAMRR 未启用，MSR 0x1401 的第 11 位应为 1
+bios-seamrr-reason-range = MSR 0x1400 和 0x1401 中的 SEAM 范围不是连续且对齐的范围
+
+bios-sgx-reg-server-name = 检查 SGX 注册服务器
+bios-sgx-reg-server-action = 检查 BIOS：SGX 注册服务器
+bios-sgx-reg-server-reason = SGX 注册服务器为 { $server }，但 { $path } 要求 { $expected }
+bios-sgx-reg-server-hint-sbx = SGX 注册服务器为 SBX
+bios-sgx-reg-server-hint-liv = SGX 注册服务器为 LIV
+
+bios-volatile-memory-1lm-name = Volatile Memory 应为 1LM
+bios-volatile-memory-1lm-action = 检查 BIOS：Volatile Memory 应为 1LM
+bios-volatile-memory-1lm-hint = Volatile Memory（或 Volatile Memory Mode）应为 1LM
+bios-volatile-memory-1lm-reason-2lm = 内存处于 2LM 模式，DRAM 用作持久内存的缓存
+bios-volatile-memory-1lm-reason-pmem = 已安装持久内存，请确认其处于 App Direct 模式而非 2LM
+
+bios-tme-bypass-name = TME Bypass 已启用
+bios-tme-bypass-action = 检查 BIOS：TME Bypass = Enabled
+bios-tme-bypass-reason = MSR 0x982 的第 31 位应为 1
+bios-tme-bypass-hint-disabled = 当前未启用 TME Bypass。
+bios-tme-bypass-hint = 对于传统的非机密工作负载，建议启用 TME Bypass。
+
+bios-seam-loader-name = SEAM Loader 已启用
+bios-seam-loader-action = 检查 BIOS：SEAM Loader = Enabled
+bios-seam-loader-reason-seamrr = SEAM 范围未配置，SEAM 加载器无法运行
+
+bios-seamldr-version-name = 检查 NP-SEAMLDR 版本
+bios-seamldr-version-action = 检查 BIOS：NP-SEAMLDR 版本足以加载 TDX 模块
+bios-seamldr-version-reason = NP-SEAMLDR { $version } 早于 TDX 模块 { $module } 所需的版本，请更新 BIOS
+bios-seamldr-version-reason-unknown = 内核未报告 NP-SEAMLDR 版本
+bios-seamldr-version-hint = NP-SEAMLDR { $version }
+
+## TDX module
+
+tdx-module-name = 检查 TDX 模块已初始化
+tdx-module-action = 检查 TDX 模块：模块已初始化
+tdx-module-reason = 需要 TDX 模块
+tdx-module-reason-failed = 内核初始化 TDX 模块失败：{ $causes }
+tdx-module-cause-cmr = 内存位于可转换内存区域（CMR）之外
+tdx-module-hint-cmr = 内核管理的所有内存都必须可供 TDX 转换：请从系统内存映射中移除 CXL 或持久内存，或检查 BIOS 内存配置
+tdx-module-cause-hotplug = 有内存被热插入
+tdx-module-hint-hotplug = 启动后热插入的内存无法供 TDX 转换，使用 TDX 时请勿将其上线
+tdx-module-cause-keyids = 私有 KeyID 太少
+tdx-module-hint-keyids = 在 BIOS 中提高 TDX Key Split
+tdx-module-cause-hibernate = 休眠已启用
+tdx-module-hint-hibernate = 请在内核命令行中添加 nohibernate
+tdx-module-cause-s3 = ACPI S3 已启用
+tdx-module-hint-s3 = 在 BIOS 中禁用 ACPI S3（挂起到内存）
+tdx-module-cause-not-loaded = BIOS 未加载模块
+tdx-module-hint-not-loaded = 检查 BIOS 中已启用 SEAM 加载器且固件中已安装 TDX 模块
+tdx-module-cause-seamcall = 对模块的 SEAMCALL 调用失败
+tdx-module-hint-seamcall = 请更新 BIOS 和 TDX 模块，模块可能过旧，与内核不匹配
+
+tdx-module-version-name = 检查 TDX 模块版本
+tdx-module-version-action = 检查 TDX 模块：模块版本足够新
+tdx-module-version-reason = TDX 模块 { $version } 早于 { $min }
+tdx-module-version-reason-unknown = 内核未报告 TDX 模块版本
+tdx-module-version-reason-invalid = { $path } 中的 min_tdx_module_version 无效：{ $min }
+tdx-module-version-hint = TDX 模块 { $version }，构建 { $build }，日期 { $date }
+
+tdx-cmr-name = 检查可转换内存区域
+tdx-cmr-action = 检查 TDX 模块：系统内存均为可转换内存
+tdx-cmr-reason = { $ram } MiB 系统内存中有 { $uncovered } MiB 位于可转换内存区域之外，TD 无法使用
+tdx-cmr-reason-unknown = 内核未记录可转换内存区域
+
+tdx-runtime-update-name = 检查保留 TD 的 TDX 模块更新
+tdx-runtime-update-action = 检查 TDX 模块：无需停止 TD 即可更新模块
+tdx-runtime-update-reason-module = TDX 模块不支持保留 TD 的更新
+tdx-runtime-update-reason-kernel = 内核无法在运行时更新 TDX 模块
+tdx-runtime-update-hint = 可以在 TD 继续运行的同时应用模块更新，参见 tdxhost module update
+
+tdx-feature-name = 检查 TDX 模块功能：{ $feature }
+tdx-feature-action = 检查 TDX 模块：模块支持 { $feature }
+tdx-feature-reason = TDX 模块构建时未包含 { $feature }
+tdx-feature-reason-unknown = 内核未报告 TDX 模块功能
+
+## Attestation
+
+attestation-qcnl-name = 检查引用提供程序（QCNL）配置
+attestation-qcnl-action = 检查证明：QCNL 配置中的 PCCS 有效且可访问
+attestation-qcnl-reason-unreadable = 无法读取 { $path }：{ $error }
+attestation-qcnl-reason-invalid = { $path } 无效：{ $error }
+attestation-qcnl-reason-no-url = 未配置 PCCS URL（pccs_url）
+attestation-qcnl-reason-bad-url = { $url } 不是 http(s) URL
+attestation-qcnl-reason-unreachable = 无法访问 { $url } 上的 PCCS：{ $error }
+attestation-qcnl-reason-insecure = TLS 证书验证已禁用（use_secure_cert）
+attestation-qcnl-reason-http = 通过明文 HTTP 访问 PCCS
+attestation-qcnl-hint = 将 { $path } 中的 pccs_url 指向您的 PCCS 或 Intel PCS
+
+attestation-collateral-name = 检查证明附属数据
+attestation-collateral-action = 检查证明：PCCS 具有当前的 TCB 信息和 QE 身份
+attestation-collateral-reason-no-fmspc = 平台 FMSPC 未知，请在 { $path } 中设置 fmspc
+attestation-collateral-reason-missing = PCCS 没有 { $what }（{ $url }）：{ $error }
+attestation-collateral-reason-expired = { $what } 已于 { $date } 过期
+attestation-collateral-reason-recovery = { $date } 发布了晚于 BIOS 的 TCB 恢复；请更新 BIOS 以应用
+
+attestation-aesmd-name = 检查 AESM 守护进程
+attestation-aesmd-action = 检查证明：aesmd 正在运行且带有 ECDSA 引用插件
+attestation-aesmd-reason-unused = 未安装 aesmd，引用不通过它生成
+attestation-aesmd-reason-disabled = aesmd.service 未启用
+attestation-aesmd-reason-inactive = aesmd.service 未运行
+attestation-aesmd-reason-no-socket = aesmd 套接字 { $path } 不存在
+attestation-aesmd-reason-plugins = 缺少 ECDSA 引用插件：{ $plugins }
+
+attestation-dcap-versions-name = 检查 DCAP 组件版本
+attestation-dcap-versions-action = 检查证明：DCAP 组件支持 TDX 且来自同一版本
+attestation-dcap-versions-reason-none = 未安装 DCAP 引用组件
+attestation-dcap-versions-reason-old = { $package } { $version } 早于首个支持 TDX 的版本 { $min }
+attestation-dcap-versions-reason-skew = DCAP 组件来自不同的版本（{ $releases }）
+
+## Guest launch
+
+guest-qemu-name = 检查 QEMU 可以运行 TD
+guest-qemu-action = 检查 QEMU：tdx-guest 对象和 TD 机器属性可用
+guest-qemu-reason-missing = 未找到 QEMU 可执行文件
+guest-qemu-reason-unsupported = { $version } 缺少 { $missing }
+guest-qemu-hint = TD 需要 QEMU { $min } 或更新版本，或支持 TDX 的发行版构建
+
+guest-firmware-name = 检查已安装 TDX 客户机固件
+guest-firmware-action = 检查客户机固件：已安装 TDVF 镜像
+guest-firmware-reason-missing = 未找到支持 TDX 的固件镜像（TDVF 元数据）
+guest-firmware-hint = 安装支持 TDX 的 OVMF 构建，例如 Fedora 和 RHEL 上的 edk2-ovmf，Debian 和 Ubuntu 上的 ovmf
+
+## KVM
+
+kvm-module-name = 检查已加载 kvm_intel 模块
+kvm-module-action = 检查 KVM：已加载 kvm_intel 模块
+kvm-module-reason-blacklisted = kvm_intel 被 { $source } 列入黑名单
+kvm-module-reason-hypervisor = 主机是未启用嵌套虚拟化的 { $hypervisor } 虚拟机，未提供 VT-x
+kvm-module-reason-no-vmx = CPU 未报告 VT-x
+kvm-module-reason-bios = BIOS 中已禁用 VT-x
+kvm-module-reason-not-loaded = 未加载 kvm_intel
+
+kvm-supported-name = 检查 KVM 受支持
+kvm-supported-action = 检查 KVM 受支持
+kvm-supported-inaccessible = KVM 设备节点（/dev/kvm）应可访问
+kvm-supported-unreadable = 无法读取 KVM 设备节点文件（/dev/kvm）
+kvm-supported-fix = 加载 kvm_intel 内核模块
+
+kvm-sgx-param-name = 检查 KVM SGX 参数已启用
+kvm-tdx-param-name = 检查 KVM TDX 参数已启用
+kvm-param-action = 检查 /sys/module/kvm_intel/parameters/{ $param } = Y（必需）
+kvm-param-invalid = 参数文件（{ $path }）包含无效值：{ $value }
+kvm-param-unreadable = 无法读取参数文件：{ $error }
+kvm-param-missing = 指定的参数不存在：{ $path }
+
+kvm-tdx-vm-name = 检查 KVM 可创建 TD 虚拟机
+kvm-tdx-vm-action = 检查 KVM：提供 TDX 虚拟机类型
+kvm-tdx-vm-reason-missing = KVM 未提供 TDX 虚拟机类型，只提供：{ $types }
+kvm-tdx-vm-reason-unreadable = 无法查询 KVM 的虚拟机类型：{ $error }
+kvm-tdx-vm-hint = kvm_intel 只有在 TDX 模块初始化后才提供 TD，参见 TDX 模块检查和 dmesg | grep -i tdx
+
+kvm-private-memory-name = 检查 KVM 私有内存支持
+kvm-private-memory-action = 检查 KVM：提供 guest_memfd 和私有内存属性
+kvm-private-memory-reason = KVM 缺少 { $missing }，无法为 TD 提供私有内存
+kvm-private-memory-reason-unreadable = 无法查询 KVM 的私有内存能力：{ $error }
+kvm-private-memory-hint = 内核 { $release } 过旧或编译时未启用 CONFIG_KVM_PRIVATE_MEM：guest_memfd 始于 Linux { $guest_memfd }，TDX 主机支持始于 Linux { $tdx }
+
+kvm-devices-name = 检查 KVM 和 SGX 设备节点
+kvm-devices-action = 检查 KVM：VMM 用户可以打开 KVM 和 SGX 设备节点
+kvm-devices-reason-kvm = /dev/kvm 属于组 { $group }，权限为 { $mode }，应属于组 kvm 且权限为 660
+kvm-devices-reason-rules = 没有设置 /dev/{ $device } 组和权限的 udev 规则
+kvm-devices-reason-user = { $user } 不在组 { $group } 中
+kvm-devices-hint-user = 使用以下命令将 { $user } 加入该组：usermod -aG { $group } { $user }
+kvm-devices-fix = 将标准 KVM 和 SGX udev 规则安装到 { $path }
+
+## Wizard
+
+wizard-title = TDX BIOS 修复向导
+wizard-vendor = 检测到的系统厂商：{ $vendor }
+wizard-vendor-unknown = 无法检测系统厂商，显示 Intel 参考 BIOS 路径。
+wizard-nothing-to-do = 所有 BIOS 相关检查均已通过，无需操作。
+wizard-pending = 有 { $count } 项 BIOS 设置需要处理。
+wizard-setting = 设置：{ $setting } = { $value }
+wizard-menu = 菜单：{ $path }
+wizard-reboot = 修改该设置，保存并重启。重启后再次运行 `tdxhost wizard` 继续。
+wizard-prompt-verify = [r] 立即重新验证，[s] 跳过，[q] 退出：
+wizard-prompt-confirm = [c] 确认已设置，[s] 跳过，[q] 退出：
+wizard-verified = 已验证。
+wizard-still-failing = 仍未设置：{ $reason }
+wizard-done = 向导结束：已修复 { $fixed } 项，剩余 { $remaining } 项。
+
+## Fleet
+
+fleet-simulated = --simulate 检查的是夹具数据，而不是清单中的主机
+fleet-inventory-invalid = 无效的主机清单 { $path }
+fleet-checked = 已检查 { $host }
+fleet-failed = 无法检查 { $host }
+fleet-not-ready = { $total } 台主机中有 { $count } 台未就绪
+fleet-host-ready = 就绪
+fleet-host-not-ready = 未就绪：{ $checks }
+fleet-host-error = 未检查：{ $error }
+fleet-title = TDX 主机就绪状态
+fleet-ready = 就绪
+fleet-not-ready-cell = 未就绪
+fleet-unreachable = 未检查
+
+## BIOS checklist
+
+checklist-title = { $model } 的 BIOS 检查清单，BIOS { $bios }
+checklist-set = 将 { $setting } 设置为 { $value }（{ $check }）
+checklist-verify = 确认 { $setting } 为 { $value }（{ $check }）
+checklist-reboot = 保存设置并重启，然后运行 `tdxhost ok` 进行确认。
+
+## Fix
+
+fix-prompt = 应用此修复？[y/N]
+fix-nothing-to-do = 没有可自动修复的失败检查。
+fix-no-fix-for-check = 检查 { $id } 未失败或没有可自动应用的修复
+fix-applied-verified = 已应用，检查现在通过。
+fix-applied-unverified = 已应用，但检查仍未通过。
+fix-applied-reboot = 已应用，重启后生效。
+fix-reboot-pending = 需要重启才能使已应用的修复生效。
+fix-failed = 有 { $count } 项修复无法应用
+fix-simulated = 无法对模拟主机应用修复
+modprobe-fix = 在 { $path } 中设置 { $module } 的选项 { $option }
+grub-fix = 在 GRUB 配置的内核命令行中添加 { $params }
+
+## Module info
+
+module-unknown = 未知
+module-info-initialized = 已初始化：
+module-info-yes = 是
+module-info-no = 否
+module-info-version = 版本：
+module-info-build = 构建：
+module-info-build-date = 构建日期：
+module-info-attributes = 属性：
+module-info-features = 功能：
+module-info-seamldr = NP-SEAMLDR：
+module-info-keyids = KeyID：
+module-info-keyids-split = { $mktme } 个 MKTME，{ $tdx } 个 TDX 私有
+module-info-pamt = PAMT：
+module-info-cmrs = 可转换内存区域：
+
+## Module update
+
+module-update-current = 正在运行的 TDX 模块：{ $version }
+module-update-prompt = 立即安装 { $image }？[y/N]
+module-update-installing = 正在安装 { $image }，TD 保持运行……
+module-update-done = TDX 模块已更新：{ $from } -> { $to }
+module-update-unchanged = 内核报告成功，但 TDX 模块仍为 { $version }
+module-update-preflight-failed = 无法更新 TDX 模块：{ $action }：{ $reason }
+module-update-rollback = 之前的 TDX 模块仍在运行，TD 不受影响。如需在更新后恢复，请使用 tdxhost module update 安装其镜像。
+module-update-simulated = 无法更新模拟主机的 TDX 模块
+
+## Capabilities
+
+capabilities-unavailable = 无法查询 KVM 的 TDX 能力：{ $error }
+capabilities-attributes = TD 属性：
+capabilities-xfam = XFAM：
+capabilities-cpuid = 可配置的 CPUID 位：
+
+## Smoke test
+
+smoke-test-stage-create-vm = 创建 TDX 类型的虚拟机
+smoke-test-stage-init-vm = 初始化 TD
+smoke-test-stage-create-vcpu = 创建 vCPU
+smoke-test-stage-init-vcpu = 初始化 vCPU
+smoke-test-stage-add-memory = 添加一个经过度量的客户机代码页
+smoke-test-stage-finalize = 完成 TD 度量
+smoke-test-stage-run = 运行 vCPU 直到客户机代码退出
+smoke-test-seamcalls = 此阶段的 SEAMCALL：{ $seamcalls }
+smoke-test-passed = 已在此主机上创建并运行 TD
+smoke-test-failed = TD 冒烟测试失败
+smoke-test-simulated = 模拟主机无法运行 TD
+
+## Verify stack
+
+verify-stack-component = 组件
+verify-stack-version = 版本
+verify-stack-not-installed = 未安装
+verify-stack-violation = { $component } { $version } 不满足 { $requirement }
+verify-stack-compatible = 已安装的版本相互兼容
+verify-stack-incompatible = 已安装的版本未知能否协同工作
+
+## TD list
+
+td-list-pid = PID
+td-list-name = 名称
+td-list-vmm = VMM
+td-list-memory = 内存
+td-list-keyids = KEYID
+td-list-none = 没有正在运行的 TD
+td-list-keyids-used = 使用中的 TDX KeyID：{ $used } / { $total }
+
+td-inspect-not-found = 没有 PID 或 UUID 为 { $target } 的运行中 TD
+td-inspect-pid = PID：
+td-inspect-name = 名称：
+td-inspect-uuid = UUID：
+td-inspect-vmm = VMM：
+td-inspect-libvirt = libvirt 域：
+td-inspect-vcpus = vCPU：
+td-inspect-memory = 配置的内存：
+td-inspect-mapped = 已映射内存：
+td-inspect-shmem = 共享内存：
+td-inspect-anon = 匿名内存：
+td-inspect-keyids = 私有 KeyID：
+td-inspect-keyids-value = { $count }（KVM 不公开 KeyID 的值）
+
+## Keys
+
+keys-mktme = MKTME KeyID：
+keys-tdx = TDX KeyID：
+keys-global = 内核保留：
+keys-capacity = 可供 TD 使用：
+keys-in-use = TD 使用中：
+keys-free = 空闲：
+keys-free-tds = { $free }（还可运行 { $free } 个 TD）
+
+## Info
+
+info-platform = 平台：
+info-board = 主板：
+info-bios = BIOS：
+info-cpu = CPU：
+info-sockets = 插槽数：
+info-microcode = 微码：
+info-os = 操作系统：
+info-secure-boot = 安全启动：
+info-tme = TME：
+info-enabled = 已启用
+info-disabled = 已禁用
+info-keyids = KeyID：
+info-keyids-value = { $mktme } 个 MKTME，{ $tdx } 个 TDX，TD 使用 { $used } / { $capacity }
+info-module-initialized = { $version }（已初始化）
+info-module-uninitialized = { $version }（未初始化）
+info-service-missing = 未安装
+
+## MSR
+
+msr-invalid = { $msr } 既不是 MSR 索引，也不是已知 MSR 的名称
+msr-on-cpus = 位于 CPU { $cpus }
+
+## CPUID
+
+cpuid-invalid = { $number } 不是 CPUID 叶或子叶
+
+## Topology
+
+topology-package = 插槽 { $id }：{ $cores } 个核心，{ $threads } 个线程
+topology-cpus = CPU：
+topology-nodes = NUMA 节点：
+topology-tme = TME：
+topology-tdx = TDX：
+topology-enabled = 已启用
+topology-disabled = 已禁用
+topology-node = NUMA 节点 { $id }：
+topology-memory = 内存：
+topology-memory-value = { $mib } MiB
+
+## Collect
+
+collect-written = 支持包已写入 { $path }
+collect-missing = 有 { $count } 项无法收集，参见支持包中的 missing.txt
+collect-review = 分享前请检查支持包：其中包含主机的配置文件和内核日志
+
+## Plugins
+
+plugin-failed = 插件失败：{ $error }
+plugin-ignored = 警告：忽略插件：{ $error }
+plugin-duplicate-id = 警告：忽略插件 { $path }：检查 ID { $id } 已被使用
+plugin-untrusted = 警告：忽略插件 { $path }：它必须属于 root 且只有 root 可写
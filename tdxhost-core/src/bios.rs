@@ -0,0 +1,240 @@
+use serde::Serialize;
+
+use crate::check::{Probe, TestOperationState, TestState};
+use crate::error::Result;
+use crate::info::{self, Hardware};
+use crate::registry::{CheckNode, Registry};
+
+/// A BIOS setting that one of the `bios.*` checks depends on.
+pub struct BiosKnob {
+    pub check_id: &'static str,
+    /// Setting name as it appears in the BIOS menu
+    pub setting: &'static str,
+    pub value: &'static str,
+    /// Intel reference BIOS path, used by most AMI based boards
+    pub path: &'static str,
+    /// Vendor specific paths, keyed by a prefix of DMI sys_vendor
+    pub vendor_paths: &'static [(&'static str, &'static str)],
+    /// Names BIOS attribute registries give the setting besides `setting`
+    pub attribute_names: &'static [&'static str],
+}
+
+/// A BIOS setting as read from outside the BIOS menu, e.g. from the BMC.
+#[derive(Debug, Clone)]
+pub struct BiosAttribute {
+    /// Attribute name, e.g. "TmeMtEnable"
+    pub name: String,
+    /// Name in the BIOS menu, if the attribute registry gives it
+    pub display_name: Option<String>,
+    pub value: String,
+}
+
+/// A source of the BIOS settings besides the BIOS menu, which automates the
+/// checks of the settings the host cannot see.
+pub trait BiosSettingsProvider: Send + Sync {
+    /// The current BIOS settings.
+    fn bios_attributes(&self, probe: &Probe) -> Result<Vec<BiosAttribute>>;
+}
+
+const PROCESSOR_CONFIGURATION: &str =
+    "Socket Configuration -> Processor Configuration -> TME, TME-MT, TDX";
+const DELL_SYSTEM_SECURITY: &str = "System BIOS -> System Security";
+
+pub const KNOBS: [BiosKnob; 9] = [
+    BiosKnob {
+        check_id: "bios.sgx",
+        setting: "SW Guard Extensions (SGX)",
+        value: "Enable",
+        path: "Socket Configuration -> Processor Configuration -> SW Guard Extensions (SGX)",
+        vendor_paths: &[("Dell", "System BIOS -> System Security -> Intel SGX")],
+        attribute_names: &[],
+    },
+    BiosKnob {
+        check_id: "bios.tdx",
+        setting: "Trust Domain Extension (TDX)",
+        value: "Enable",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[("Dell", "System BIOS -> System Security -> Intel TDX")],
+        attribute_names: &[],
+    },
+    BiosKnob {
+        check_id: "bios.tme",
+        setting: "Total Memory Encryption (TME)",
+        value: "Enable",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[("Dell", DELL_SYSTEM_SECURITY)],
+        attribute_names: &[],
+    },
+    BiosKnob {
+        check_id: "bios.tme_mt",
+        setting: "Total Memory Encryption Multi-Tenant (TME-MT)",
+        value: "Enable",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[("Dell", DELL_SYSTEM_SECURITY)],
+        attribute_names: &[
+            "TME-MT",
+            "Total Memory Encryption Multi-Tenant",
+            "TmeMtEnable",
+        ],
+    },
+    BiosKnob {
+        check_id: "bios.tdx_key_split",
+        setting: "TME-MT/TDX key split",
+        value: "non-zero",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[("Dell", DELL_SYSTEM_SECURITY)],
+        attribute_names: &[],
+    },
+    BiosKnob {
+        check_id: "bios.sgx_reg_server",
+        setting: "SGX Registration Server",
+        value: "LIV (production) or SBX (pre-production)",
+        path: "Socket Configuration -> Processor Configuration -> SW Guard Extensions (SGX)",
+        vendor_paths: &[],
+        attribute_names: &["SgxRegistrationServer"],
+    },
+    BiosKnob {
+        check_id: "bios.volatile_memory_1lm",
+        setting: "Volatile Memory",
+        value: "1LM",
+        path: "Socket Configuration -> Memory Configuration -> Memory Map",
+        vendor_paths: &[],
+        attribute_names: &["Volatile Memory Mode", "VolMemMode"],
+    },
+    BiosKnob {
+        check_id: "bios.tme_bypass",
+        setting: "Total Memory Encryption (TME) Bypass",
+        value: "Enable",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[],
+        attribute_names: &[],
+    },
+    BiosKnob {
+        check_id: "bios.seam_loader",
+        setting: "SEAM Loader",
+        value: "Enable",
+        path: PROCESSOR_CONFIGURATION,
+        vendor_paths: &[(
+            "Dell",
+            "System BIOS -> System Security -> TDX Secure Arbitration Mode Loader",
+        )],
+        attribute_names: &[
+            "SEAM Loader Manager",
+            "TDX Secure Arbitration Mode Loader",
+            "TdxSeamLoader",
+        ],
+    },
+];
+
+/// Letters and digits of a name, lowercase, e.g. "tmemt" for "TME-MT".
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+pub fn knob(check_id: &str) -> Option<&'static BiosKnob> {
+    KNOBS.iter().find(|k| k.check_id == check_id)
+}
+
+impl BiosKnob {
+    /// Menu path for the given system vendor, falling back to the Intel
+    /// reference layout.
+    pub fn menu_path(&self, vendor: Option<&str>) -> &'static str {
+        vendor
+            .and_then(|v| {
+                self.vendor_paths
+                    .iter()
+                    .find(|(prefix, _)| v.starts_with(prefix))
+            })
+            .map(|(_, path)| *path)
+            .unwrap_or(self.path)
+    }
+
+    /// The attribute holding the setting, by its name or display name.
+    pub fn attribute<'a>(&self, attributes: &'a [BiosAttribute]) -> Option<&'a BiosAttribute> {
+        let names: Vec<String> = std::iter::once(self.setting)
+            .chain(self.attribute_names.iter().copied())
+            .map(normalize)
+            .collect();
+        attributes.iter().find(|a| {
+            std::iter::once(&a.name)
+                .chain(&a.display_name)
+                .any(|name| names.contains(&normalize(name)))
+        })
+    }
+}
+
+pub fn system_vendor() -> Option<String> {
+    std::fs::read_to_string("/sys/class/dmi/id/sys_vendor")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// BIOS checks the operator has to act on, in check tree order, and whether
+/// each needs the setting verified by hand rather than changed.
+pub fn pending(nodes: &[CheckNode], out: &mut Vec<(&'static str, bool)>) {
+    for node in nodes {
+        let manual = matches!(node.result.operation, TestOperationState::Manual);
+        let actionable = match node.result.state {
+            TestState::Fail => true,
+            TestState::Tbd => manual,
+            _ => false,
+        };
+        if actionable && knob(node.id).is_some() {
+            out.push((node.id, manual));
+        }
+        pending(&node.children, out);
+    }
+}
+
+/// A BIOS setting to change.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecklistItem {
+    pub check_id: &'static str,
+    pub setting: &'static str,
+    pub value: String,
+    /// Menu path on the system's vendor's BIOS
+    pub path: &'static str,
+    /// The host cannot tell the setting, so it may already be right
+    pub verify: bool,
+}
+
+/// The BIOS settings to change for the failing checks.
+#[derive(Debug, Clone, Serialize)]
+pub struct Checklist {
+    pub hardware: Hardware,
+    pub items: Vec<ChecklistItem>,
+}
+
+/// Run the checks and list the BIOS settings the failing ones need, with
+/// the menu paths of the system's vendor.
+pub fn checklist(probe: &Probe) -> Checklist {
+    let hardware = info::hardware(probe);
+    let groups = Registry::builtin().evaluate(probe);
+    let mut pending_ids = vec![];
+    pending(&groups.required, &mut pending_ids);
+    pending(&groups.optional, &mut pending_ids);
+
+    let items = pending_ids
+        .into_iter()
+        .filter_map(|(id, verify)| {
+            let knob = knob(id)?;
+            // the site knows which registration server it needs
+            let value = match id {
+                "bios.sgx_reg_server" => probe.config().sgx_registration_server.name(),
+                _ => knob.value,
+            };
+            Some(ChecklistItem {
+                check_id: knob.check_id,
+                setting: knob.setting,
+                value: value.to_string(),
+                path: knob.menu_path(hardware.vendor.as_deref()),
+                verify,
+            })
+        })
+        .collect();
+    Checklist { hardware, items }
+}
@@ -0,0 +1,203 @@
+//! BIOS settings read with the server vendors' configuration utilities.
+
+use serde::Deserialize;
+use std::io::ErrorKind;
+
+use crate::bios::{BiosAttribute, BiosSettingsProvider};
+use crate::check::Probe;
+use crate::error::{Result, TdxHostError};
+
+/// Groups of the Dell BIOS holding the TDX settings.
+const RACADM_GROUPS: [&str; 3] = ["BIOS.ProcSettings", "BIOS.SysSecurity", "BIOS.MemSettings"];
+/// Where SCELNX and syscfg save the BIOS settings to.
+const SCRIPT_PATH: &str = "/run/tdxhost-bios.txt";
+
+/// A vendor utility reading the BIOS settings from the running host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BiosTool {
+    /// Dell iDRAC's racadm
+    Racadm,
+    /// Lenovo XClarity Essentials OneCLI
+    Onecli,
+    /// AMI's SCELNX_64
+    Scelnx,
+    /// Intel server boards' syscfg
+    Syscfg,
+}
+
+/// Settings of "Name=Value" lines, e.g. "ProcTme=Enabled" from racadm or
+/// "Processors.TotalMemoryEncryption=Enable" from OneCLI, without the group
+/// of the name and a pending value.
+fn name_values(output: &str) -> Vec<BiosAttribute> {
+    output
+        .lines()
+        .filter(|l| !l.starts_with(['[', ';', '#']))
+        .filter_map(|l| {
+            let (name, value) = l.split_once('=')?;
+            let name = name.trim();
+            let name = name.rsplit_once('.').map_or(name, |(_, name)| name);
+            // e.g. "Enabled (Pending Value=Disabled)"
+            let value = value.split(" (").next().unwrap_or_default().trim();
+            (!name.is_empty()).then(|| BiosAttribute {
+                name: name.to_string(),
+                display_name: None,
+                value: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Settings of an AMI setup control script, whose questions list their
+/// options with the current one starred:
+///
+/// ```text
+/// Setup Question  = Total Memory Encryption (TME)
+/// Token   =5C     // Do NOT change this line
+/// Options =*[01]Enable    // Move "*" to the desired Option
+///          [00]Disable
+/// ```
+fn setup_questions(script: &str) -> Vec<BiosAttribute> {
+    let mut attributes = vec![];
+    let mut question: Option<String> = None;
+    for line in script.lines() {
+        let (key, value) = line.split_once('=').unwrap_or(("", line));
+        let value = value.split("//").next().unwrap_or_default().trim();
+        match key.trim() {
+            "Setup Question" => question = Some(value.to_string()),
+            // numeric questions, e.g. "Value =<2>"
+            "Value" => {
+                if let Some(name) = question.take() {
+                    attributes.push(BiosAttribute {
+                        name,
+                        display_name: None,
+                        value: value.trim_matches(['<', '>']).to_string(),
+                    });
+                }
+            }
+            _ => {
+                let Some(option) = value.strip_prefix('*') else {
+                    continue;
+                };
+                if let Some(name) = question.take() {
+                    let option = option.split_once(']').map_or(option, |(_, o)| o);
+                    attributes.push(BiosAttribute {
+                        name,
+                        display_name: None,
+                        value: option.trim().to_string(),
+                    });
+                }
+            }
+        }
+    }
+    attributes
+}
+
+impl BiosTool {
+    pub fn program(&self) -> &'static str {
+        match self {
+            BiosTool::Racadm => "racadm",
+            BiosTool::Onecli => "onecli",
+            BiosTool::Scelnx => "SCELNX_64",
+            BiosTool::Syscfg => "syscfg",
+        }
+    }
+
+    fn run(&self, probe: &Probe, args: &[&str]) -> Result<String> {
+        let output = probe
+            .output(self.program(), args)
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", self.program(), e)))?;
+        Ok(String::from_utf8_lossy(&output).into_owned())
+    }
+
+    /// Have the tool save the settings to `SCRIPT_PATH` and read them back.
+    fn saved_script(&self, probe: &Probe, args: &[&str]) -> Result<String> {
+        self.run(probe, args)?;
+        let script = probe
+            .read_file(SCRIPT_PATH)
+            .map_err(|e| TdxHostError::io(std::path::Path::new(SCRIPT_PATH), e));
+        // the settings are left to no one
+        let _ = probe.remove_file(SCRIPT_PATH);
+        script
+    }
+}
+
+impl BiosSettingsProvider for BiosTool {
+    fn bios_attributes(&self, probe: &Probe) -> Result<Vec<BiosAttribute>> {
+        let attributes = match self {
+            BiosTool::Racadm => {
+                let mut attributes = vec![];
+                for group in RACADM_GROUPS {
+                    attributes.extend(name_values(&self.run(probe, &["get", group])?));
+                }
+                attributes
+            }
+            BiosTool::Onecli => name_values(&self.run(probe, &["config", "show", "BIOS"])?),
+            BiosTool::Scelnx => {
+                setup_questions(&self.saved_script(probe, &["/o", "/s", SCRIPT_PATH])?)
+            }
+            BiosTool::Syscfg => name_values(&self.saved_script(probe, &["/s", SCRIPT_PATH, "/b"])?),
+        };
+        if attributes.is_empty() {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{} listed no BIOS settings", self.program()),
+            )
+            .into());
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(attributes: &[BiosAttribute]) -> Vec<(&str, &str)> {
+        attributes
+            .iter()
+            .map(|a| (a.name.as_str(), a.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn racadm_and_onecli_settings() {
+        let racadm = "[Key=BIOS.Setup.1-1#ProcSettings]\nProcTme=Enabled\nProcTdx=Disabled (Pending Value=Enabled)\n#ProcSgx=Off\n";
+        assert_eq!(
+            pairs(&name_values(racadm)),
+            [("ProcTme", "Enabled"), ("ProcTdx", "Disabled")]
+        );
+        let onecli = "Processors.TotalMemoryEncryption=Enable\nProcessors.SGX=Disable\n;comment\n";
+        assert_eq!(
+            pairs(&name_values(onecli)),
+            [("TotalMemoryEncryption", "Enable"), ("SGX", "Disable")]
+        );
+    }
+
+    #[test]
+    fn ami_setup_questions() {
+        let script = "\
+Setup Question\t= Total Memory Encryption (TME)
+Token\t=5C\t// Do NOT change this line
+Options\t=[00]Disable\t// Move \"*\" to the desired Option
+         *[01]Enable
+
+Setup Question\t= TME-MT memory integrity
+Token\t=5D\t// Do NOT change this line
+Options\t=*[00]Disable\t// Move \"*\" to the desired Option
+         [01]Enable
+
+Setup Question\t= TDX Key Split
+Token\t=60\t// Do NOT change this line
+Value\t=<2>\t// Use '<' and '>' to enclose the value
+";
+        assert_eq!(
+            pairs(&setup_questions(script)),
+            [
+                ("Total Memory Encryption (TME)", "Enable"),
+                ("TME-MT memory integrity", "Disable"),
+                ("TDX Key Split", "2"),
+            ]
+        );
+    }
+}
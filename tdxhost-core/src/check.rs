@@ -0,0 +1,551 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::arch::x86_64::CpuidResult;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::bios::{BiosAttribute, BiosSettingsProvider};
+use crate::config::Config;
+use crate::error::{Result, TdxHostError};
+use crate::kvm::{PrivateMemory, TdxCapabilities};
+use crate::platform::{Host, Platform};
+use crate::remediation::Remediation;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TestState {
+    Ok,
+    #[default]
+    Fail,
+    Warning,
+    Tbd,
+    Skip,
+}
+
+impl From<&TestState> for String {
+    fn from(res: &TestState) -> Self {
+        match res {
+            TestState::Ok => "OK".to_string(),
+            TestState::Fail => "FAIL".to_string(),
+            TestState::Warning => "WARNING".to_string(),
+            TestState::Tbd => "TBD".to_string(),
+            TestState::Skip => "SKIP".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOptionalState {
+    #[default]
+    Required,
+    Optional,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOperationState {
+    Manual,
+    #[default]
+    Program,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CheckResult {
+    /// What was checked
+    pub action: String,
+    /// Why the check did not pass, if known
+    pub reason: String,
+    pub state: TestState,
+    pub optional_state: TestOptionalState,
+    /// Whether the check could be verified by the program or needs a human
+    pub operation: TestOperationState,
+    /// Raw values the verdict was based on (register contents, log lines)
+    pub evidence: Vec<String>,
+    /// Free-form guidance for the operator, nested lines indented with tabs
+    #[serde(default)]
+    pub hints: Vec<String>,
+    /// Where the check and how to fix a failure are documented
+    #[serde(default)]
+    pub docs_url: Option<String>,
+}
+
+/// Inputs several checks need, read at most once per run.
+#[derive(Default)]
+struct Cache {
+    kernel_log: Option<Arc<str>>,
+    kernel_journal: Option<Arc<str>>,
+    os_release: Option<Arc<str>>,
+    modules: Option<Arc<str>>,
+    cmdline: Option<Arc<str>>,
+    /// By CPU and index
+    msrs: HashMap<(u16, u32), u64>,
+    bios_attributes: Option<Arc<Result<Vec<BiosAttribute>>>>,
+}
+
+/// Access to the host state the checks inspect.
+///
+/// MSRs, the kernel log, os-release, the loaded modules and the kernel
+/// command line are cached until the next run, see `refresh`.
+pub struct Probe {
+    platform: Box<dyn Platform>,
+    cache: Mutex<Cache>,
+    every_cpu: bool,
+    config: Config,
+    bios_settings: Option<Box<dyn BiosSettingsProvider>>,
+    sudo_user: Option<String>,
+}
+
+impl Probe {
+    /// Inspect `platform` instead of the running host, e.g. a `Fixture`.
+    pub fn new(platform: impl Platform + 'static) -> Self {
+        Self {
+            platform: Box::new(platform),
+            cache: Mutex::default(),
+            every_cpu: false,
+            config: Config::default(),
+            bios_settings: None,
+            sudo_user: None,
+        }
+    }
+
+    /// Inspect the running host with the site's `config`, e.g.
+    /// `Config::load()`.
+    pub fn host(config: Config) -> Self {
+        Self::new(Host::new(&config)).with_config(config)
+    }
+
+    /// Site settings the checks compare the host against. The BIOS settings
+    /// are read through its `bios_tool`, if it names one.
+    pub fn with_config(mut self, config: Config) -> Self {
+        if let Some(tool) = config.bios_tool {
+            self.bios_settings = Some(Box::new(tool));
+        }
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Read the BIOS settings the host cannot see from `provider`, e.g. the
+    /// BMC's Redfish service.
+    pub fn with_bios_settings(mut self, provider: impl BiosSettingsProvider + 'static) -> Self {
+        self.bios_settings = Some(Box::new(provider));
+        self
+    }
+
+    /// The user who ran tdxhost through sudo, whose access to /dev/kvm is
+    /// checked besides libvirt's unless the config names the VMM user.
+    pub fn with_sudo_user(mut self, user: Option<String>) -> Self {
+        self.sudo_user = user;
+        self
+    }
+
+    pub fn sudo_user(&self) -> Option<&str> {
+        self.sudo_user.as_deref()
+    }
+
+    /// Have the MSR checks read every online CPU instead of one per package.
+    pub fn every_cpu(mut self, every_cpu: bool) -> Self {
+        self.every_cpu = every_cpu;
+        self
+    }
+
+    /// Forget the cached inputs, so the next run sees the current host state.
+    pub fn refresh(&self) {
+        *self.cache.lock().unwrap() = Cache::default();
+    }
+
+    fn cached(
+        &self,
+        slot: fn(&mut Cache) -> &mut Option<Arc<str>>,
+        read: impl FnOnce() -> Result<String>,
+    ) -> Result<Arc<str>> {
+        if let Some(value) = slot(&mut self.cache.lock().unwrap()) {
+            return Ok(value.clone());
+        }
+
+        let value: Arc<str> = read()?.into();
+        *slot(&mut self.cache.lock().unwrap()) = Some(value.clone());
+        Ok(value)
+    }
+
+    fn read_cached_file(
+        &self,
+        path: &str,
+        slot: fn(&mut Cache) -> &mut Option<Arc<str>>,
+    ) -> Result<Arc<str>> {
+        self.cached(slot, || {
+            self.read_file(path)
+                .map_err(|e| TdxHostError::io(Path::new(path), e))
+        })
+    }
+
+    /// Read an MSR on CPU 0.
+    pub fn read_msr(&self, index: u32) -> Result<u64> {
+        self.read_msr_on(0, index)
+    }
+
+    /// Read an MSR on a logical CPU.
+    pub fn read_msr_on(&self, cpu: u16, index: u32) -> Result<u64> {
+        if let Some(value) = self.cache.lock().unwrap().msrs.get(&(cpu, index)) {
+            return Ok(*value);
+        }
+
+        let value = self.platform.read_msr(cpu, index)?;
+        self.cache.lock().unwrap().msrs.insert((cpu, index), value);
+        Ok(value)
+    }
+
+    pub fn read_file(&self, path: &str) -> std::io::Result<String> {
+        self.platform.read_file(path)
+    }
+
+    pub fn read_bytes(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        self.platform.read_bytes(path)
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.platform.exists(path)
+    }
+
+    /// Names of the entries of a directory, sorted.
+    pub fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        self.platform.read_dir(path)
+    }
+
+    pub fn modified(&self, path: &str) -> std::io::Result<SystemTime> {
+        self.platform.modified(path)
+    }
+
+    pub fn cpuid(&self, leaf: u32) -> CpuidResult {
+        self.platform.cpuid(leaf)
+    }
+
+    pub fn cpuid_count(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        self.platform.cpuid_count(leaf, subleaf)
+    }
+
+    /// The kernel ring buffer.
+    pub fn kernel_log(&self) -> Result<Arc<str>> {
+        self.cached(|c| &mut c.kernel_log, || self.platform.kernel_log())
+    }
+
+    /// Kernel messages of the current boot from the systemd journal, which
+    /// outlive the ring buffer.
+    pub fn kernel_journal(&self) -> Result<Arc<str>> {
+        self.cached(
+            |c| &mut c.kernel_journal,
+            || {
+                let output = self
+                    .platform
+                    .output("journalctl", &["-k", "-b", "-o", "cat", "--no-pager"])?;
+                Ok(String::from_utf8_lossy(&output).into_owned())
+            },
+        )
+    }
+
+    /// Contents of /etc/os-release.
+    pub fn os_release(&self) -> Result<Arc<str>> {
+        self.read_cached_file("/etc/os-release", |c| &mut c.os_release)
+    }
+
+    /// The loaded kernel modules as listed by lsmod, from /proc/modules.
+    pub fn modules(&self) -> Result<Arc<str>> {
+        self.read_cached_file("/proc/modules", |c| &mut c.modules)
+    }
+
+    /// The kernel command line.
+    pub fn cmdline(&self) -> Result<Arc<str>> {
+        self.read_cached_file("/proc/cmdline", |c| &mut c.cmdline)
+    }
+
+    /// Standard output of a command, failing when it exits non-zero.
+    pub fn output(&self, program: &str, args: &[&str]) -> std::io::Result<Vec<u8>> {
+        self.platform.output(program, args)
+    }
+
+    /// Standard output of a command given `input` on its standard input.
+    pub fn output_with_input(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> std::io::Result<Vec<u8>> {
+        self.platform.output_with_input(program, args, input)
+    }
+
+    /// Whether site plugins run against this probe's host.
+    pub fn runs_plugins(&self) -> bool {
+        self.platform.runs_plugins()
+    }
+
+    /// Remove a file a command left behind.
+    pub fn remove_file(&self, path: &str) -> std::io::Result<()> {
+        self.platform.remove_file(path)
+    }
+
+    /// The BIOS settings, if they can be read from outside the BIOS menu.
+    pub fn bios_attributes(&self) -> Option<Arc<Result<Vec<BiosAttribute>>>> {
+        let provider = self.bios_settings.as_ref()?;
+        if let Some(attributes) = &self.cache.lock().unwrap().bios_attributes {
+            return Some(attributes.clone());
+        }
+
+        // a BMC or tool that failed is not asked again until `refresh`
+        let attributes = Arc::new(provider.bios_attributes(self));
+        self.cache.lock().unwrap().bios_attributes = Some(attributes.clone());
+        Some(attributes)
+    }
+
+    /// Result of the KVM_GET_API_VERSION ioctl on /dev/kvm, negative when
+    /// the ioctl failed.
+    pub fn kvm_api_version(&self) -> std::io::Result<i32> {
+        self.platform.kvm_api_version()
+    }
+
+    /// Bitmap of the VM types KVM creates, bit 5 being TDs.
+    pub fn kvm_vm_types(&self) -> std::io::Result<u64> {
+        self.platform.kvm_vm_types()
+    }
+
+    /// The TD configurations KVM accepts.
+    pub fn kvm_tdx_capabilities(&self) -> std::io::Result<TdxCapabilities> {
+        self.platform.kvm_tdx_capabilities()
+    }
+
+    /// What KVM offers for the private memory of TDs.
+    pub fn kvm_private_memory(&self) -> std::io::Result<PrivateMemory> {
+        self.platform.kvm_private_memory()
+    }
+
+    /// Online logical CPUs.
+    pub fn online_cpus(&self) -> Result<Vec<u16>> {
+        let path = "/sys/devices/system/cpu/online";
+        let online = self
+            .read_file(path)
+            .map_err(|e| TdxHostError::io(Path::new(path), e))?;
+
+        parse_cpu_list(&online).ok_or_else(|| {
+            TdxHostError::UnsupportedPlatform(format!(
+                "invalid CPU list in {}: {}",
+                path,
+                online.trim()
+            ))
+        })
+    }
+
+    /// CPUs the MSR checks read: the first CPU of every package, or every
+    /// online CPU.
+    pub fn msr_cpus(&self) -> Result<Vec<u16>> {
+        if self.every_cpu {
+            return self.online_cpus();
+        }
+
+        // without topology information, assume a single package
+        let Ok(cpus) = self.online_cpus() else {
+            return Ok(vec![0]);
+        };
+        let mut packages = HashSet::new();
+        Ok(cpus
+            .into_iter()
+            .filter(|&cpu| packages.insert(self.package(cpu)))
+            .collect())
+    }
+
+    /// Physical packages (sockets) of the online CPUs.
+    pub fn packages(&self) -> Result<Vec<u16>> {
+        let mut packages: Vec<u16> = self
+            .online_cpus()?
+            .into_iter()
+            .filter_map(|cpu| self.package(cpu))
+            .collect();
+        packages.sort_unstable();
+        packages.dedup();
+        Ok(packages)
+    }
+
+    /// Physical package (socket) of a logical CPU.
+    pub fn package(&self, cpu: u16) -> Option<u16> {
+        self.read_file(&format!(
+            "/sys/devices/system/cpu/cpu{}/topology/physical_package_id",
+            cpu
+        ))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+    }
+
+    /// The active kernel lockdown mode, e.g. "integrity", if any.
+    pub fn lockdown(&self) -> Option<String> {
+        let modes = self.read_file("/sys/kernel/security/lockdown").ok()?;
+        let active = modes
+            .split_whitespace()
+            .find_map(|m| m.strip_prefix('[')?.strip_suffix(']'))?;
+        (active != "none").then(|| active.to_string())
+    }
+
+    /// The hypervisor tdxhost runs under, None on bare metal.
+    pub fn hypervisor(&self) -> Option<Hypervisor> {
+        // CPUID.1:ECX bit 31 is reserved for hypervisors to set
+        let features = self.cpuid(0x1).ecx;
+        if features & (1 << 31) == 0 {
+            return None;
+        }
+        let text = |registers: [u32; 3]| -> String {
+            let text: String = registers
+                .iter()
+                .flat_map(|r| r.to_le_bytes())
+                .map(char::from)
+                .collect();
+            text.trim_matches(['\0', ' ']).to_string()
+        };
+        // the TDX module's leaf, enumerated to TDs only
+        let tdx = self.cpuid(0x21);
+        let signature =
+            if self.cpuid(0x0).eax >= 0x21 && text([tdx.ebx, tdx.edx, tdx.ecx]) == "IntelTDX" {
+                "IntelTDX".to_string()
+            } else {
+                let vendor = self.cpuid(0x4000_0000);
+                text([vendor.ebx, vendor.ecx, vendor.edx])
+            };
+        Some(Hypervisor {
+            signature,
+            // CPUID.1:ECX bit 5: VMX
+            nested_vmx: features & (1 << 5) != 0,
+        })
+    }
+
+    /// Whether privileged checks can run, i.e. tdxhost runs as root.
+    pub fn privileged(&self) -> bool {
+        self.platform.privileged()
+    }
+}
+
+/// Hypervisors by their CPUID vendor signature.
+const HYPERVISORS: [(&str, &str); 9] = [
+    ("KVMKVMKVM", "KVM"),
+    ("Microsoft Hv", "Microsoft Hyper-V"),
+    ("VMwareVMware", "VMware"),
+    ("XenVMMXenVMM", "Xen"),
+    ("VBoxVBoxVBox", "VirtualBox"),
+    ("TCGTCGTCGTCG", "QEMU (TCG)"),
+    ("ACRNACRNACRN", "ACRN"),
+    ("bhyve bhyve", "bhyve"),
+    ("lrpepyh  vr", "Parallels"),
+];
+
+/// The hypervisor of a guest.
+#[derive(Debug, Clone)]
+pub struct Hypervisor {
+    /// CPUID vendor signature, e.g. "KVMKVMKVM", or "IntelTDX" inside a TD,
+    /// empty if the hypervisor does not tell
+    pub signature: String,
+    /// Whether the guest is given VMX, to run guests of its own
+    pub nested_vmx: bool,
+}
+
+impl Hypervisor {
+    /// Whether the guest is a TD.
+    pub fn is_td(&self) -> bool {
+        self.signature == "IntelTDX"
+    }
+
+    /// The hypervisor's name, e.g. "Microsoft Hyper-V", else its signature.
+    pub fn name(&self) -> Option<&str> {
+        HYPERVISORS
+            .iter()
+            .find(|(signature, _)| *signature == self.signature)
+            .map(|(_, name)| *name)
+            .or((!self.signature.is_empty()).then_some(self.signature.as_str()))
+    }
+}
+
+/// CPUs of a list in the kernel's format, e.g. "0-3,8".
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<u16>> {
+    let mut cpus = vec![];
+    for range in list.trim().split(',').filter(|r| !r.is_empty()) {
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        cpus.extend(first.parse::<u16>().ok()?..=last.parse().ok()?);
+    }
+    Some(cpus)
+}
+
+/// Area of the host a check covers, for selecting checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Tag {
+    Cpu,
+    Os,
+    Bios,
+    Sgx,
+    Tdx,
+    Kvm,
+    /// Quote generation and verification prerequisites
+    Attestation,
+    /// What launching a TD needs besides the host: the VMM and guest
+    /// firmware
+    Guest,
+    /// Checks provided by plugins
+    Plugin,
+}
+
+/// A single host readiness check.
+///
+/// Checks are arranged into a tree by their dependencies: a check only runs
+/// once every check it depends on passed, and is listed under the first one.
+pub trait Check: Send + Sync {
+    /// Stable identifier, e.g. "bios.tdx"
+    fn id(&self) -> &'static str;
+
+    fn description(&self) -> String;
+
+    fn dependencies(&self) -> &[&'static str] {
+        &[]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[]
+    }
+
+    /// Where the check and how to fix a failure are documented.
+    fn docs_url(&self) -> Option<String> {
+        crate::checks::docs_url(self.id())
+    }
+
+    /// Optional checks do not affect whether the host is ready.
+    fn optional(&self) -> bool {
+        false
+    }
+
+    /// Privileged checks read MSRs or the kernel log and are skipped when
+    /// not running as root.
+    fn privileged(&self) -> bool {
+        false
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult;
+
+    /// Automatable fix, asked for when the check failed.
+    fn remediation(&self, _probe: &Probe) -> Option<Remediation> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_lists() {
+        assert_eq!(parse_cpu_list("0-3,8\n"), Some(vec![0, 1, 2, 3, 8]));
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list(""), Some(vec![]));
+        assert_eq!(parse_cpu_list("0-x"), None);
+    }
+}
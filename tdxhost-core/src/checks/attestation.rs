@@ -0,0 +1,645 @@
+//! Checks of the DCAP stack that generates and verifies TD quotes.
+
+use std::time::SystemTime;
+
+use super::{dmi, package_version, probe_failed, release, unit_properties};
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::config;
+use crate::i18n::{tr, tr_args};
+
+pub(crate) const QCNL_CONF: &str = "/etc/sgx_default_qcnl.conf";
+
+/// Quote provider library settings from `QCNL_CONF`.
+struct Qcnl {
+    pccs_url: Option<String>,
+    collateral_service: Option<String>,
+    use_secure_cert: bool,
+}
+
+/// Parse `QCNL_CONF`, JSON with // comments, or the KEY=value format of
+/// older DCAP releases.
+fn parse_qcnl(contents: &str) -> std::result::Result<Qcnl, String> {
+    let json: String = contents
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if json.trim_start().starts_with('{') {
+        let conf: serde_json::Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        let string = |key: &str| conf.get(key).and_then(|v| v.as_str()).map(str::to_owned);
+        return Ok(Qcnl {
+            pccs_url: string("pccs_url"),
+            collateral_service: string("collateral_service"),
+            use_secure_cert: conf
+                .get("use_secure_cert")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+        });
+    }
+
+    let value = |key: &str| {
+        contents
+            .lines()
+            .filter(|l| !l.trim_start().starts_with('#'))
+            .filter_map(|l| l.split_once('='))
+            .find(|(k, _)| k.trim() == key)
+            .map(|(_, v)| v.trim().to_owned())
+    };
+    Ok(Qcnl {
+        pccs_url: value("PCCS_URL"),
+        collateral_service: None,
+        use_secure_cert: !value("USE_SECURE_CERT").is_some_and(|v| v.eq_ignore_ascii_case("false")),
+    })
+}
+
+/// The settings in `QCNL_CONF`, or why they are unusable.
+fn read_qcnl(probe: &Probe) -> std::result::Result<Qcnl, String> {
+    let contents = probe.read_file(QCNL_CONF).map_err(|e| {
+        tr_args(
+            "attestation-qcnl-reason-unreadable",
+            &[("path", QCNL_CONF), ("error", &e.to_string())],
+        )
+    })?;
+    parse_qcnl(&contents).map_err(|e| {
+        tr_args(
+            "attestation-qcnl-reason-invalid",
+            &[("path", QCNL_CONF), ("error", &e)],
+        )
+    })
+}
+
+/// GET `url` with the TLS settings of `qcnl`, failing on HTTP errors when
+/// the body is wanted.
+fn pccs_get(probe: &Probe, qcnl: &Qcnl, url: &str, body: bool) -> std::io::Result<Vec<u8>> {
+    let mut curl = vec!["-sS", "--max-time", "10"];
+    if body {
+        curl.push("-f");
+    } else {
+        curl.extend(["-o", "/dev/null"]);
+    }
+    if !qcnl.use_secure_cert {
+        curl.push("-k");
+    }
+    curl.push(url);
+    probe.output("curl", &curl)
+}
+
+/// Days since the epoch of a date, after Howard Hinnant's days_from_civil.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Days since the epoch of a UTC time as used in PCS collateral, e.g.
+/// "2024-05-10T12:00:00Z".
+pub(super) fn collateral_day(time: &str) -> Option<i64> {
+    let mut date = time.get(..10)?.split('-');
+    let year = date.next()?.parse().ok()?;
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+    Some(days_from_civil(year, month, day))
+}
+
+/// Days since the epoch of the BIOS release date in DMI, e.g. "03/14/2024".
+pub(super) fn bios_day(probe: &Probe) -> Option<i64> {
+    let date = dmi(probe, "bios_date")?;
+    let mut date = date.split('/');
+    let month = date.next()?.parse().ok()?;
+    let day = date.next()?.parse().ok()?;
+    let year = date.next()?.parse().ok()?;
+    Some(days_from_civil(year, month, day))
+}
+
+const AESM_SOCKET: &str = "/var/run/aesmd/aesm.socket";
+
+const AESM_DIR: &str = "/opt/intel/sgx-aesm-service/aesm";
+
+/// Plugins aesmd needs to generate ECDSA quotes
+const AESM_ECDSA_PLUGINS: [&str; 3] = [
+    "libsgx_aesm_ecdsa_plugin",
+    "libsgx_aesm_pce_plugin",
+    "libsgx_aesm_quote_ex_plugin",
+];
+
+/// DCAP packages TDX quoting uses, with the first release supporting TDX.
+pub(crate) const DCAP_PACKAGES: [(&str, (u32, u32)); 4] = [
+    ("libsgx-dcap-ql", (1, 15)),
+    ("libsgx-dcap-default-qpl", (1, 15)),
+    ("tdx-qgs", (1, 15)),
+    ("libsgx-pce-logic", (1, 15)),
+];
+
+/// Whether `url` is an http(s) URL with a host.
+fn valid_url(url: &str) -> bool {
+    url.strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .is_some_and(|host| !host.is_empty())
+}
+
+pub(super) struct AttestationQcnl;
+
+impl Check for AttestationQcnl {
+    fn id(&self) -> &'static str {
+        "attestation.qcnl"
+    }
+
+    fn description(&self) -> String {
+        tr("attestation-qcnl-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Attestation]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("attestation-qcnl-action");
+        let fail = |reason: String, evidence: Vec<String>| CheckResult {
+            action: action.clone(),
+            reason,
+            evidence,
+            state: TestState::Fail,
+            hints: vec![format!(
+                "\t{}",
+                tr_args("attestation-qcnl-hint", &[("path", QCNL_CONF)])
+            )],
+            ..Default::default()
+        };
+
+        let qcnl = match read_qcnl(probe) {
+            Ok(qcnl) => qcnl,
+            Err(reason) => return fail(reason, vec![]),
+        };
+
+        let mut evidence = vec![format!("use_secure_cert = {}", qcnl.use_secure_cert)];
+        if let Some(service) = &qcnl.collateral_service {
+            evidence.push(format!("collateral_service = {}", service));
+        }
+        let Some(url) = &qcnl.pccs_url else {
+            return fail(tr("attestation-qcnl-reason-no-url"), evidence);
+        };
+        evidence.insert(0, format!("pccs_url = {}", url));
+        for url in std::iter::once(url).chain(&qcnl.collateral_service) {
+            if !valid_url(url) {
+                return fail(
+                    tr_args("attestation-qcnl-reason-bad-url", &[("url", url)]),
+                    evidence,
+                );
+            }
+        }
+
+        if let Err(e) = pccs_get(probe, &qcnl, url, false) {
+            return fail(
+                tr_args(
+                    "attestation-qcnl-reason-unreachable",
+                    &[("url", url), ("error", &e.to_string())],
+                ),
+                evidence,
+            );
+        }
+
+        let mut warnings = vec![];
+        if !qcnl.use_secure_cert {
+            warnings.push(tr("attestation-qcnl-reason-insecure"));
+        }
+        if url.starts_with("http://") {
+            warnings.push(tr("attestation-qcnl-reason-http"));
+        }
+
+        CheckResult {
+            action,
+            state: if warnings.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            reason: warnings.join("; "),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct AttestationCollateral;
+
+impl Check for AttestationCollateral {
+    fn id(&self) -> &'static str {
+        "attestation.collateral"
+    }
+
+    fn description(&self) -> String {
+        tr("attestation-collateral-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["attestation.qcnl"]
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Attestation, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("attestation-collateral-action");
+        let Some(fmspc) = &probe.config().fmspc else {
+            return CheckResult {
+                action,
+                reason: tr_args(
+                    "attestation-collateral-reason-no-fmspc",
+                    &[("path", config::CONFIG_PATH)],
+                ),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+        let qcnl = match read_qcnl(probe) {
+            Ok(qcnl) => qcnl,
+            Err(reason) => {
+                return CheckResult {
+                    action,
+                    reason,
+                    ..Default::default()
+                }
+            }
+        };
+        let Some(url) = qcnl.pccs_url.clone() else {
+            return CheckResult {
+                action,
+                reason: tr("attestation-qcnl-reason-no-url"),
+                ..Default::default()
+            };
+        };
+        // TDX collateral is served next to the SGX collateral
+        let base = format!(
+            "{}/",
+            url.replace("/sgx/certification/", "/tdx/certification/")
+                .trim_end_matches('/')
+        );
+        let today = (SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            / 86400) as i64;
+
+        let mut evidence = vec![format!("fmspc = {}", fmspc)];
+        let mut warnings = vec![];
+        let mut fetch = |what: &str, url: String, key: &str| {
+            let collateral = pccs_get(probe, &qcnl, &url, true)
+                .map_err(|e| e.to_string())
+                .and_then(|body| {
+                    serde_json::from_slice::<serde_json::Value>(&body).map_err(|e| e.to_string())
+                });
+            let collateral = match collateral {
+                Ok(c) => c[key].clone(),
+                Err(e) => {
+                    warnings.push(tr_args(
+                        "attestation-collateral-reason-missing",
+                        &[("what", what), ("url", &url), ("error", &e)],
+                    ));
+                    return None;
+                }
+            };
+            if let Some(next_update) = collateral["nextUpdate"].as_str() {
+                evidence.push(format!("{} nextUpdate = {}", what, next_update));
+                if collateral_day(next_update).is_some_and(|day| day < today) {
+                    warnings.push(tr_args(
+                        "attestation-collateral-reason-expired",
+                        &[("what", what), ("date", next_update)],
+                    ));
+                }
+            }
+            Some(collateral)
+        };
+
+        let tcb_info = fetch(
+            "TCB info",
+            format!("{}tcb?fmspc={}", base, fmspc),
+            "tcbInfo",
+        );
+        fetch(
+            "QE identity",
+            format!("{}qe/identity", base),
+            "enclaveIdentity",
+        );
+
+        // levels are ordered newest first; a BIOS older than the newest
+        // level predates its microcode
+        if let Some(tcb_info) = tcb_info {
+            if let Some(number) = tcb_info["tcbEvaluationDataNumber"].as_u64() {
+                evidence.push(format!("tcbEvaluationDataNumber = {}", number));
+            }
+            if let Some(latest) = tcb_info["tcbLevels"][0]["tcbDate"].as_str() {
+                evidence.push(format!("latest tcbDate = {}", latest));
+                let bios = bios_day(probe);
+                if let (Some(latest_day), Some(bios)) = (collateral_day(latest), bios) {
+                    if bios < latest_day {
+                        warnings.push(tr_args(
+                            "attestation-collateral-reason-recovery",
+                            &[("date", &latest[..10])],
+                        ));
+                    }
+                }
+            }
+        }
+
+        CheckResult {
+            action,
+            state: if warnings.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            reason: warnings.join("; "),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct AttestationAesmd;
+
+impl Check for AttestationAesmd {
+    fn id(&self) -> &'static str {
+        "attestation.aesmd"
+    }
+
+    fn description(&self) -> String {
+        tr("attestation-aesmd-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Attestation, Tag::Sgx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("attestation-aesmd-action");
+        let unit = match unit_properties(
+            probe,
+            "aesmd.service",
+            "LoadState,UnitFileState,ActiveState,MainPID",
+        ) {
+            Ok(unit) => unit,
+            Err(e) => return probe_failed(action, e),
+        };
+        let property = |name: &str| {
+            unit.iter()
+                .find(|(k, _)| k == name)
+                .map_or("", |(_, v)| v.as_str())
+        };
+        let mut evidence: Vec<String> = unit
+            .iter()
+            .map(|(k, v)| format!("aesmd.service {}={}", k, v))
+            .collect();
+
+        // quotes are generated without aesmd, e.g. by the TDX quote
+        // generation service
+        if property("LoadState") == "not-found" {
+            return CheckResult {
+                action,
+                reason: tr("attestation-aesmd-reason-unused"),
+                evidence,
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        }
+
+        let mut reasons = vec![];
+        if property("UnitFileState") != "enabled" {
+            reasons.push(tr("attestation-aesmd-reason-disabled"));
+        }
+        if property("ActiveState") != "active" {
+            reasons.push(tr("attestation-aesmd-reason-inactive"));
+        }
+        if probe.exists(AESM_SOCKET) {
+            evidence.push(format!("socket = {}", AESM_SOCKET));
+        } else {
+            reasons.push(tr_args(
+                "attestation-aesmd-reason-no-socket",
+                &[("path", AESM_SOCKET)],
+            ));
+        }
+
+        // plugins mapped into the daemon, or installed when its memory map
+        // cannot be read
+        let maps = probe
+            .read_file(&format!("/proc/{}/maps", property("MainPID")))
+            .ok();
+        let (found, missing): (Vec<&str>, Vec<&str>) =
+            AESM_ECDSA_PLUGINS.iter().partition(|plugin| match &maps {
+                Some(maps) => maps.contains(*plugin),
+                None => ["so", "so.1"]
+                    .iter()
+                    .any(|ext| probe.exists(&format!("{}/{}.{}", AESM_DIR, plugin, ext))),
+            });
+        evidence.push(format!(
+            "{} plugins = {}",
+            if maps.is_some() {
+                "loaded"
+            } else {
+                "installed"
+            },
+            found.join(", ")
+        ));
+        if !missing.is_empty() {
+            reasons.push(tr_args(
+                "attestation-aesmd-reason-plugins",
+                &[("plugins", &missing.join(", "))],
+            ));
+        }
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            reason: reasons.join("; "),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct AttestationDcapVersions;
+
+impl Check for AttestationDcapVersions {
+    fn id(&self) -> &'static str {
+        "attestation.dcap_versions"
+    }
+
+    fn description(&self) -> String {
+        tr("attestation-dcap-versions-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Attestation, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("attestation-dcap-versions-action");
+        let installed: Vec<(&str, (u32, u32), String)> = DCAP_PACKAGES
+            .iter()
+            .filter_map(|&(package, min)| Some((package, min, package_version(probe, package)?)))
+            .collect();
+        if installed.is_empty() {
+            return CheckResult {
+                action,
+                reason: tr("attestation-dcap-versions-reason-none"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        }
+
+        let mut evidence = vec![];
+        let mut reasons = vec![];
+        for (package, (major, minor), version) in &installed {
+            evidence.push(format!("{} = {}", package, version));
+            if release(version).is_some_and(|r| r < (*major, *minor)) {
+                reasons.push(tr_args(
+                    "attestation-dcap-versions-reason-old",
+                    &[
+                        ("package", package),
+                        ("version", version),
+                        ("min", &format!("{}.{}", major, minor)),
+                    ],
+                ));
+            }
+        }
+        // the components of one DCAP release are only tested together
+        let mut releases: Vec<(u32, u32)> = installed
+            .iter()
+            .filter_map(|(_, _, v)| release(v))
+            .collect();
+        releases.sort_unstable();
+        releases.dedup();
+        if releases.len() > 1 {
+            let releases: Vec<String> = releases
+                .iter()
+                .map(|(major, minor)| format!("{}.{}", major, minor))
+                .collect();
+            reasons.push(tr_args(
+                "attestation-dcap-versions-reason-skew",
+                &[("releases", &releases.join(", "))],
+            ));
+        }
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            reason: reasons.join("; "),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    #[test]
+    fn qcnl_json() {
+        let qcnl = parse_qcnl(
+            r#"{
+  // PCCS server address
+  "pccs_url": "https://localhost:8081/sgx/certification/v4/",
+  "use_secure_cert": false,
+  "collateral_service": "https://api.trustedservices.intel.com/sgx/certification/v4/"
+}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            qcnl.pccs_url.as_deref(),
+            Some("https://localhost:8081/sgx/certification/v4/")
+        );
+        assert!(qcnl.collateral_service.is_some());
+        assert!(!qcnl.use_secure_cert);
+
+        let qcnl = parse_qcnl("{}").unwrap();
+        assert_eq!(qcnl.pccs_url, None);
+        assert!(qcnl.use_secure_cert);
+        assert!(parse_qcnl("{ \"pccs_url\": ").is_err());
+    }
+
+    #[test]
+    fn qcnl_key_value() {
+        let qcnl = parse_qcnl(
+            "# PCCS server address\nPCCS_URL=https://pccs.example:8081/sgx/certification/v3/\nUSE_SECURE_CERT=FALSE\n",
+        )
+        .unwrap();
+        assert_eq!(
+            qcnl.pccs_url.as_deref(),
+            Some("https://pccs.example:8081/sgx/certification/v3/")
+        );
+        assert!(!qcnl.use_secure_cert);
+        assert!(parse_qcnl("PCCS_URL=x\n").unwrap().use_secure_cert);
+    }
+
+    #[test]
+    fn days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(
+            days_from_civil(2024, 2, 29) + 1,
+            days_from_civil(2024, 3, 1)
+        );
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+
+        assert_eq!(
+            collateral_day("2024-05-10T12:00:00Z"),
+            Some(days_from_civil(2024, 5, 10))
+        );
+        assert_eq!(collateral_day("2024-05"), None);
+        assert_eq!(collateral_day("not a date"), None);
+    }
+
+    #[test]
+    fn bios_release_day() {
+        let probe = |date: &str| {
+            Probe::new(Fixture {
+                files: [("/sys/class/dmi/id/bios_date".to_string(), date.to_string())].into(),
+                ..Default::default()
+            })
+        };
+        assert_eq!(
+            bios_day(&probe("03/14/2024\n")),
+            Some(days_from_civil(2024, 3, 14))
+        );
+        assert_eq!(bios_day(&probe("2024-03-14\n")), None);
+        assert_eq!(bios_day(&Probe::new(Fixture::default())), None);
+    }
+
+    #[test]
+    fn urls() {
+        assert!(valid_url("https://pccs.example:8081/sgx/"));
+        assert!(valid_url("http://localhost"));
+        assert!(!valid_url("https://"));
+        assert!(!valid_url("ftp://pccs.example/"));
+    }
+}
@@ -0,0 +1,1292 @@
+//! Checks of the BIOS settings TDX needs, read from the MSRs they land in.
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestOperationState, TestState};
+use crate::config;
+use crate::config::{IntegrityMode, RegistrationServer};
+use crate::firmware::{FirmwareTable, FIRMWARE_PATH};
+use crate::i18n::{tr, tr_args};
+
+use super::attestation::{bios_day, collateral_day};
+use super::tdx::{check_tdx_module, seamldr_version, tdx_log_lines, TdxModuleRelease};
+use super::{
+    bios_attribute_result, bios_enabled, cpu_location, dmi, msr_bit_result, msr_bit_unset,
+    msr_evidence, msr_failed, probe_failed, read_msr_on,
+};
+
+/// SEAMRR base and mask hold a physical address in bits 51:25.
+const SEAMRR_GRANULARITY: u64 = 1 << 25;
+
+/// Why the SEAM range in IA32_SEAMRR_PHYS_BASE/MASK is unusable, if it is.
+fn check_seamrr(base: u64, mask: u64, address_bits: u32) -> Option<String> {
+    if base & (1 << 3) == 0 {
+        return Some(tr("bios-seamrr-reason-unconfigured"));
+    }
+    if mask & (1 << 11) == 0 {
+        return Some(tr("bios-seamrr-reason-disabled"));
+    }
+
+    let address_mask = ((1u64 << address_bits) - 1) & !(SEAMRR_GRANULARITY - 1);
+    let range_mask = mask & address_mask;
+    let size = (!range_mask & address_mask) + SEAMRR_GRANULARITY;
+    // the mask must cover contiguous high bits and the base be aligned to it
+    if range_mask == 0
+        || !size.is_power_of_two()
+        || address_mask & !(size - 1) != range_mask
+        || base & address_mask & (size - 1) != 0
+    {
+        return Some(tr("bios-seamrr-reason-range"));
+    }
+    None
+}
+
+/// TME encryption algorithms, by their bit in IA32_TME_CAPABILITY[2:0] and
+/// IA32_TME_ACTIVATE[50:48].
+const TME_ALGORITHMS: [&str; 3] = ["AES-XTS-128", "AES-XTS-128 with integrity", "AES-XTS-256"];
+
+/// Names of the algorithms set in `bits`, e.g. "AES-XTS-128, AES-XTS-256".
+fn tme_algorithms(bits: u64) -> String {
+    let names: Vec<&str> = TME_ALGORITHMS
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| bits & (1 << i) != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+pub(super) struct BiosFirmware;
+
+impl Check for BiosFirmware {
+    fn id(&self) -> &'static str {
+        "bios.firmware"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-firmware-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.distro"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-firmware-action");
+        let Some(vendor) = dmi(probe, "sys_vendor") else {
+            return CheckResult {
+                action,
+                reason: tr("bios-firmware-reason-no-dmi"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+        let product = dmi(probe, "product_name").unwrap_or_default();
+        let board = dmi(probe, "board_name").unwrap_or_default();
+        let version = dmi(probe, "bios_version").unwrap_or_default();
+        let platform = format!("{} {}", vendor, product).trim().to_string();
+        let mut evidence = vec![
+            format!("sys_vendor = {}", vendor),
+            format!("product_name = {}", product),
+            format!("board_name = {}", board),
+            format!("bios_version = {}", version),
+        ];
+        if let Some(date) = dmi(probe, "bios_date") {
+            evidence.push(format!("bios_date = {}", date));
+        }
+
+        let table = match FirmwareTable::load() {
+            Ok(table) => table,
+            Err(e) => return probe_failed(action, e),
+        };
+        let Some(firmware) = table.find(&vendor, &[&product, &board]) else {
+            return CheckResult {
+                action,
+                reason: tr_args(
+                    "bios-firmware-reason-unknown",
+                    &[("platform", &platform), ("path", FIRMWARE_PATH)],
+                ),
+                state: TestState::Skip,
+                evidence,
+                ..Default::default()
+            };
+        };
+
+        let mut required = vec![];
+        let mut older = false;
+        let mut unknown = false;
+        if let Some(requirement) = &firmware.version {
+            required.push(requirement.to_string());
+            match requirement.matches(&version) {
+                Some(matches) => older |= !matches,
+                None => unknown = true,
+            }
+        }
+        if let Some(released) = &firmware.released {
+            required.push(tr_args(
+                "bios-firmware-required-released",
+                &[("date", released)],
+            ));
+            match (bios_day(probe), collateral_day(released)) {
+                (Some(bios), Some(day)) => older |= bios < day,
+                _ => unknown = true,
+            }
+        }
+        let required = required.join(", ");
+
+        let (state, reason) = if older {
+            (
+                TestState::Fail,
+                tr_args(
+                    "bios-firmware-reason",
+                    &[
+                        ("platform", &platform),
+                        ("required", &required),
+                        ("version", &version),
+                    ],
+                ),
+            )
+        } else if unknown {
+            (
+                TestState::Warning,
+                tr_args(
+                    "bios-firmware-reason-unparsable",
+                    &[("version", &version), ("required", &required)],
+                ),
+            )
+        } else {
+            (TestState::Ok, String::new())
+        };
+        CheckResult {
+            action,
+            state,
+            reason,
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosSgx;
+
+impl Check for BiosSgx {
+    fn id(&self) -> &'static str {
+        "bios.sgx"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-sgx-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.distro"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Sgx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        msr_bit_result(probe, 0x3a, 18, "bios-sgx-action", "bios-sgx-reason")
+    }
+}
+
+pub(super) struct BiosFeatureControl;
+
+impl Check for BiosFeatureControl {
+    fn id(&self) -> &'static str {
+        "bios.feature_control"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-feature-control-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.sgx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Sgx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-feature-control-action");
+        // IA32_FEATURE_CONTROL
+        let values = match probe
+            .msr_cpus()
+            .and_then(|cpus| read_msr_on(probe, &cpus, 0x3a))
+        {
+            Ok(values) => values,
+            Err(e) => return msr_failed(probe, 0x3a, action, e),
+        };
+
+        let mut reasons: Vec<String> =
+            msr_bit_unset(probe, &values, 0, "bios-feature-control-reason-unlocked")
+                .into_iter()
+                .collect();
+        // CPUID.07H:ECX[30] enumerates SGX launch control
+        let launch_control = probe.cpuid(0x0000_0007).ecx & (1 << 30) != 0;
+        if launch_control {
+            reasons.extend(msr_bit_unset(
+                probe,
+                &values,
+                17,
+                "bios-feature-control-reason-launch-control",
+            ));
+        }
+
+        let mut evidence = msr_evidence(0x3a, &values);
+        evidence.push(format!("SGX launch control supported = {}", launch_control));
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            reason: reasons.join("; "),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosTdx;
+
+impl Check for BiosTdx {
+    fn id(&self) -> &'static str {
+        "bios.tdx"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tdx-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.feature_control"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        msr_bit_result(probe, 0x1401, 11, "bios-tdx-action", "bios-tdx-reason")
+    }
+}
+
+pub(super) struct BiosTme;
+
+impl Check for BiosTme {
+    fn id(&self) -> &'static str {
+        "bios.tme"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tme-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        msr_bit_result(probe, 0x982, 1, "bios-tme-action", "bios-tme-reason")
+    }
+}
+
+pub(super) struct BiosTmeCapability;
+
+impl Check for BiosTmeCapability {
+    fn id(&self) -> &'static str {
+        "bios.tme_capability"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tme-capability-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tme"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-tme-capability-action");
+        // IA32_TME_CAPABILITY and IA32_TME_ACTIVATE
+        let capability = match probe.read_msr(0x981) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x981, action, e),
+        };
+        let activate = match probe.read_msr(0x982) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x982, action, e),
+        };
+
+        let bypass_supported = capability & (1 << 31) != 0;
+        let max_keyid_bits = (capability >> 32) & 0xf;
+        let max_keys = (capability >> 36) & 0x7fff;
+        let mktme_keyid_bits = (activate >> 32) & 0xf;
+        let tdx_keyid_bits = (activate >> 36) & 0xf;
+
+        let mut reasons = vec![];
+        if mktme_keyid_bits + tdx_keyid_bits > max_keyid_bits {
+            reasons.push(tr_args(
+                "bios-tme-capability-reason-keyids",
+                &[
+                    (
+                        "requested",
+                        &(mktme_keyid_bits + tdx_keyid_bits).to_string(),
+                    ),
+                    ("max", &max_keyid_bits.to_string()),
+                ],
+            ));
+        }
+        if activate & (1 << 31) != 0 && !bypass_supported {
+            reasons.push(tr("bios-tme-capability-reason-bypass"));
+        }
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            reason: reasons.join("; "),
+            evidence: vec![
+                format!("MSR 0x981 = {:#x}", capability),
+                format!(
+                    "supported algorithms = {}",
+                    tme_algorithms(capability & 0x7)
+                ),
+                format!("bypass supported = {}", bypass_supported),
+                format!(
+                    "max KeyID bits = {}, max keys = {}",
+                    max_keyid_bits, max_keys
+                ),
+                format!("MSR 0x982 = {:#x}", activate),
+                format!(
+                    "KeyID bits: MKTME = {}, TDX = {}",
+                    mktme_keyid_bits, tdx_keyid_bits
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosTmeAlgorithm;
+
+impl Check for BiosTmeAlgorithm {
+    fn id(&self) -> &'static str {
+        "bios.tme_algorithm"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tme-algorithm-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tme"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-tme-algorithm-action");
+        // IA32_TME_ACTIVATE
+        let activate = match probe.read_msr(0x982) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x982, action, e),
+        };
+
+        // TME policy in bits 7:4 numbers the algorithm
+        let policy = (activate >> 4) & 0xf;
+        let active = TME_ALGORITHMS
+            .get(policy as usize)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("unknown ({:#x})", policy));
+        let mktme_algorithms = (activate >> 48) & 0xffff;
+
+        let mut reasons = vec![];
+        // TDX private keys use AES-XTS-128, with or without integrity
+        if mktme_algorithms & 0b11 == 0 {
+            reasons.push(tr_args(
+                "bios-tme-algorithm-reason-tdx",
+                &[("algorithms", &tme_algorithms(mktme_algorithms))],
+            ));
+        }
+        if let Some(expected) = &probe.config().tme_algorithm {
+            if !expected.eq_ignore_ascii_case(&active) {
+                reasons.push(tr_args(
+                    "bios-tme-algorithm-reason-policy",
+                    &[
+                        ("active", &active),
+                        ("expected", expected),
+                        ("path", config::CONFIG_PATH),
+                    ],
+                ));
+            }
+        }
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            reason: reasons.join("; "),
+            evidence: vec![
+                format!("MSR 0x982 = {:#x}", activate),
+                format!("TME algorithm = {}", active),
+                format!("MKTME algorithms = {}", tme_algorithms(mktme_algorithms)),
+            ],
+            hints: vec![format!(
+                "\t{}",
+                tr_args("bios-tme-algorithm-hint", &[("algorithm", &active)])
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosTdxIntegrity;
+
+impl Check for BiosTdxIntegrity {
+    fn id(&self) -> &'static str {
+        "bios.tdx_integrity"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tdx-integrity-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tme_algorithm"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-tdx-integrity-action");
+        // IA32_TME_ACTIVATE and IA32_TME_CAPABILITY
+        let activate = match probe.read_msr(0x982) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x982, action, e),
+        };
+        let capability = match probe.read_msr(0x981) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x981, action, e),
+        };
+
+        // TDX keys use AES-XTS-128 with integrity when the BIOS enables it
+        // for MKTME, and a TD-owner bit otherwise
+        let mode = if activate & (1 << 49) != 0 {
+            IntegrityMode::Cryptographic
+        } else {
+            IntegrityMode::Logical
+        };
+
+        let mut reasons = vec![];
+        if mode == IntegrityMode::Cryptographic && capability & (1 << 1) == 0 {
+            reasons.push(tr("bios-tdx-integrity-reason-unsupported"));
+        }
+        if let Some(expected) = probe.config().integrity_mode {
+            if expected != mode {
+                reasons.push(tr_args(
+                    "bios-tdx-integrity-reason-policy",
+                    &[
+                        ("active", mode.name()),
+                        ("expected", expected.name()),
+                        ("path", config::CONFIG_PATH),
+                    ],
+                ));
+            }
+        }
+
+        CheckResult {
+            action,
+            state: if reasons.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            reason: reasons.join("; "),
+            evidence: vec![
+                format!("MSR 0x981 = {:#x}", capability),
+                format!("MSR 0x982 = {:#x}", activate),
+                format!("integrity = {}", mode.name()),
+            ],
+            hints: vec![format!(
+                "\t{}",
+                tr_args("bios-tdx-integrity-hint", &[("mode", mode.name())])
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosTmeMt;
+
+impl Check for BiosTmeMt {
+    fn id(&self) -> &'static str {
+        "bios.tme_mt"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tme-mt-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let msr_value = match probe.read_msr(0x982) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x982, tr("bios-tme-mt-action"), e),
+        };
+        let state = if msr_value & (1 << 1) > 0 {
+            TestState::Tbd
+        } else {
+            TestState::Fail
+        };
+        let result = CheckResult {
+            action: tr("bios-tme-mt-action"),
+            reason: tr("bios-tme-mt-reason"),
+            evidence: vec![format!("MSR 0x982 = {:#x}", msr_value)],
+            state,
+            operation: TestOperationState::Manual,
+            hints: vec![
+                format!("\t{}", tr("hint-check-bios-settings")),
+                String::from(
+                    "\t\tSocket Configuration -> Processor Configuration -> TME, TME-MT, TDX",
+                ),
+                format!("\t\t\t{}", tr("bios-tme-mt-hint")),
+                format!("\t\t{}", tr("hint-bios-path-differs")),
+            ],
+            ..Default::default()
+        };
+        if state == TestState::Fail {
+            return result;
+        }
+        bios_attribute_result(probe, self.id(), "Enable", bios_enabled, result)
+    }
+}
+
+pub(super) struct BiosTdxKeySplit;
+
+impl Check for BiosTdxKeySplit {
+    fn id(&self) -> &'static str {
+        "bios.tdx_key_split"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tdx-key-split-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let msr_value = match probe.read_msr(0x981) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x981, tr("bios-tdx-key-split-action"), e),
+        };
+        let state = if msr_value & (0x7fff << 36) != 0 {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        };
+        CheckResult {
+            action: tr("bios-tdx-key-split-action"),
+            reason: tr("bios-tdx-key-split-reason"),
+            evidence: vec![format!("MSR 0x981 = {:#x}", msr_value)],
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosTdxKeyIds;
+
+impl Check for BiosTdxKeyIds {
+    fn id(&self) -> &'static str {
+        "bios.tdx_keyids"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tdx-keyids-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx_key_split"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        // IA32_MKTME_KEYID_PARTITIONING
+        let msr_value = match probe.read_msr(0x87) {
+            Ok(v) => v,
+            Err(e) => return msr_failed(probe, 0x87, tr("bios-tdx-keyids-action"), e),
+        };
+        let mktme_keyids = msr_value & 0xffff_ffff;
+        let tdx_keyids = msr_value >> 32;
+        let state = if tdx_keyids != 0 {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        };
+        CheckResult {
+            action: tr("bios-tdx-keyids-action"),
+            reason: tr("bios-tdx-keyids-reason"),
+            evidence: vec![
+                format!("MSR 0x87 = {:#x}", msr_value),
+                format!(
+                    "MKTME KeyIDs = {}, TDX private KeyIDs = {}",
+                    mktme_keyids, tdx_keyids
+                ),
+            ],
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosSeamrr;
+
+impl Check for BiosSeamrr {
+    fn id(&self) -> &'static str {
+        "bios.seamrr"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-seamrr-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-seamrr-action");
+        // IA32_MTRRCAP, IA32_SEAMRR_PHYS_BASE and IA32_SEAMRR_PHYS_MASK
+        let mut values = [0; 3];
+        for (value, msr) in values.iter_mut().zip([0xfe, 0x1400, 0x1401]) {
+            *value = match probe.read_msr(msr) {
+                Ok(v) => v,
+                Err(e) => return msr_failed(probe, msr, action, e),
+            };
+        }
+        let [mtrrcap, base, mask] = values;
+
+        // CPUID 0x80000008 EAX[7:0] is the physical address width
+        let address_bits = match probe.cpuid(0x8000_0008).eax & 0xff {
+            0 => 52,
+            bits => bits.min(52),
+        };
+        let address_mask = ((1u64 << address_bits) - 1) & !(SEAMRR_GRANULARITY - 1);
+
+        let reason = if mtrrcap & (1 << 15) == 0 {
+            Some(tr("bios-seamrr-reason-unsupported"))
+        } else {
+            check_seamrr(base, mask, address_bits)
+        };
+
+        let mut evidence = vec![
+            format!("MSR 0xfe = {:#x}", mtrrcap),
+            format!("MSR 0x1400 = {:#x}", base),
+            format!("MSR 0x1401 = {:#x}", mask),
+        ];
+        if reason.is_none() {
+            let size = (!mask & address_mask) + SEAMRR_GRANULARITY;
+            evidence.push(format!(
+                "SEAMRR base = {:#x}, size = {} MiB",
+                base & address_mask,
+                size >> 20
+            ));
+        }
+
+        CheckResult {
+            action,
+            state: if reason.is_none() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            reason: reason.unwrap_or_default(),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosSgxRegServer;
+
+impl Check for BiosSgxRegServer {
+    fn id(&self) -> &'static str {
+        "bios.sgx_reg_server"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-sgx-reg-server-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Sgx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        // MSR_PLATFORM_INFO bit 27 selects the SBX registration server
+        let expected = probe.config().sgx_registration_server;
+        let msr_value = match probe.read_msr(0xce) {
+            Ok(v) => v,
+            Err(e) => {
+                let failed = msr_failed(probe, 0xce, tr("bios-sgx-reg-server-action"), e);
+                let name = expected.name().to_ascii_lowercase();
+                return bios_attribute_result(
+                    probe,
+                    self.id(),
+                    expected.name(),
+                    |value| value.to_ascii_lowercase().contains(&name),
+                    failed,
+                );
+            }
+        };
+        let (server, hint) = if msr_value & (1 << 27) > 0 {
+            (RegistrationServer::Sbx, tr("bios-sgx-reg-server-hint-sbx"))
+        } else {
+            (RegistrationServer::Liv, tr("bios-sgx-reg-server-hint-liv"))
+        };
+
+        CheckResult {
+            action: tr("bios-sgx-reg-server-action"),
+            reason: tr_args(
+                "bios-sgx-reg-server-reason",
+                &[
+                    ("server", server.name()),
+                    ("expected", expected.name()),
+                    ("path", config::CONFIG_PATH),
+                ],
+            ),
+            state: if server == expected {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            evidence: vec![format!("MSR 0xce = {:#x}", msr_value)],
+            hints: vec![format!("\t{}", hint)],
+            ..Default::default()
+        }
+    }
+}
+
+/// MSRs holding BIOS settings, which must be the same on every CPU
+const CONSISTENT_MSRS: [u32; 4] = [0x3a, 0x1401, 0x981, 0x982];
+
+pub(super) struct MsrConsistency;
+
+impl Check for MsrConsistency {
+    fn id(&self) -> &'static str {
+        "bios.msr_consistency"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-msr-consistency-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Cpu]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-msr-consistency-action");
+        let cpus = match probe.online_cpus() {
+            Ok(cpus) => cpus,
+            Err(e) => return probe_failed(action, e),
+        };
+
+        let mut evidence = vec![];
+        let mut differing = vec![];
+        for msr in CONSISTENT_MSRS {
+            let mut values = match read_msr_on(probe, &cpus, msr) {
+                Ok(values) => values,
+                Err(e) => return msr_failed(probe, msr, action, e),
+            };
+            if values.len() > 1 {
+                evidence.extend(msr_evidence(msr, &values));
+                // the value most CPUs hold is taken as the intended one
+                values.sort_by_key(|(_, cpus)| std::cmp::Reverse(cpus.len()));
+                differing.extend(values.into_iter().skip(1).flat_map(|(_, cpus)| cpus));
+            }
+        }
+
+        if differing.is_empty() {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence: vec![format!("{} CPUs", cpus.len())],
+                ..Default::default()
+            };
+        }
+
+        differing.sort_unstable();
+        differing.dedup();
+
+        CheckResult {
+            action,
+            reason: tr_args(
+                "bios-msr-consistency-reason",
+                &[("where", &cpu_location(probe, &differing))],
+            ),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct BiosVolatileMemory1lm;
+
+impl Check for BiosVolatileMemory1lm {
+    fn id(&self) -> &'static str {
+        "bios.volatile_memory_1lm"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-volatile-memory-1lm-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-volatile-memory-1lm-action");
+        // in 2LM, DRAM is a memory side cache in front of persistent memory,
+        // which the ACPI HMAT describes
+        let cache = "/sys/devices/system/node/node0/memory_side_cache";
+        if probe.exists(cache) {
+            return CheckResult {
+                action,
+                reason: tr("bios-volatile-memory-1lm-reason-2lm"),
+                evidence: vec![format!("{} exists", cache)],
+                ..Default::default()
+            };
+        }
+
+        // without persistent memory modules, memory can only be 1LM
+        if let Ok(devices) = probe.output("dmidecode", &["-t", "memory"]) {
+            let devices = String::from_utf8_lossy(&devices);
+            let technologies: Vec<&str> = devices
+                .lines()
+                .filter_map(|l| l.trim().strip_prefix("Memory Technology: "))
+                .collect();
+            let pmem = technologies.iter().find(|t| t.contains("persistent"));
+            if let Some(pmem) = pmem {
+                // app direct mode leaves DRAM as the volatile memory, which
+                // cannot be told apart from sysfs
+                return CheckResult {
+                    action,
+                    reason: tr("bios-volatile-memory-1lm-reason-pmem"),
+                    evidence: vec![format!("dmidecode: Memory Technology: {}", pmem)],
+                    state: TestState::Warning,
+                    ..Default::default()
+                };
+            }
+            if let Some(dram) = technologies.first() {
+                return CheckResult {
+                    action,
+                    evidence: vec![format!("dmidecode: Memory Technology: {}", dram)],
+                    state: TestState::Ok,
+                    ..Default::default()
+                };
+            }
+        }
+
+        let manual = CheckResult {
+            action,
+            state: TestState::Tbd,
+            operation: TestOperationState::Manual,
+            hints: vec![
+                format!("\t{}", tr("hint-check-bios-settings")),
+                String::from("\t\tSocket Configuration -> Memory Configuration -> Memory Map"),
+                format!("\t\t\t{}", tr("bios-volatile-memory-1lm-hint")),
+                format!("\t\t{}", tr("hint-bios-path-differs")),
+                format!("\t\t{}", tr("hint-bios-skip-missing")),
+            ],
+            ..Default::default()
+        };
+        bios_attribute_result(
+            probe,
+            self.id(),
+            "1LM",
+            |value| value.to_ascii_lowercase().contains("1lm"),
+            manual,
+        )
+    }
+}
+
+pub(super) struct BiosTmeBypass;
+
+impl Check for BiosTmeBypass {
+    fn id(&self) -> &'static str {
+        "bios.tme_bypass"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-tme-bypass-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let mut result = msr_bit_result(
+            probe,
+            0x982,
+            31,
+            "bios-tme-bypass-action",
+            "bios-tme-bypass-reason",
+        );
+
+        if result.state != TestState::Ok {
+            result
+                .hints
+                .push(format!("\t{}", tr("bios-tme-bypass-hint-disabled")));
+        }
+        result
+            .hints
+            .push(format!("\t{}", tr("bios-tme-bypass-hint")));
+        result
+    }
+}
+
+pub(super) struct BiosSeamLoader;
+
+impl Check for BiosSeamLoader {
+    fn id(&self) -> &'static str {
+        "bios.seam_loader"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-seam-loader-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-seam-loader-action");
+        // only the SEAM loader can have put an initialized module in place
+        if let Ok(Some(line)) = check_tdx_module(probe) {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence: vec![line],
+                ..Default::default()
+            };
+        }
+        if let Some(line) = tdx_log_lines(probe)
+            .into_iter()
+            .find(|l| l.contains("SEAMLDR"))
+        {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence: vec![line],
+                ..Default::default()
+            };
+        }
+
+        // the loader runs the module in the SEAM range, so without one it
+        // cannot be enabled
+        if let (Ok(base), Ok(mask)) = (probe.read_msr(0x1400), probe.read_msr(0x1401)) {
+            if base & (1 << 3) == 0 || mask & (1 << 11) == 0 {
+                return CheckResult {
+                    action,
+                    reason: tr("bios-seam-loader-reason-seamrr"),
+                    evidence: vec![
+                        format!("MSR 0x1400 = {:#x}", base),
+                        format!("MSR 0x1401 = {:#x}", mask),
+                    ],
+                    ..Default::default()
+                };
+            }
+        }
+
+        let manual = CheckResult {
+            action,
+            state: TestState::Tbd,
+            operation: TestOperationState::Manual,
+            ..Default::default()
+        };
+        bios_attribute_result(probe, self.id(), "Enable", bios_enabled, manual)
+    }
+}
+
+pub(super) struct BiosSeamldrVersion;
+
+impl Check for BiosSeamldrVersion {
+    fn id(&self) -> &'static str {
+        "bios.seamldr_version"
+    }
+
+    fn description(&self) -> String {
+        tr("bios-seamldr-version-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.seam_loader"]
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Bios, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("bios-seamldr-version-action");
+        let Some((version, (major, minor, _))) = seamldr_version(probe) else {
+            return CheckResult {
+                action,
+                reason: tr("bios-seamldr-version-reason-unknown"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+        let mut evidence = vec![format!("NP-SEAMLDR version = {}", version)];
+        let hints = vec![format!(
+            "\t{}",
+            tr_args("bios-seamldr-version-hint", &[("version", &version)])
+        )];
+
+        // a module release needs a loader of the same release or later
+        let module = TdxModuleRelease::detect(probe);
+        let Some(module) = module else {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                hints,
+                ..Default::default()
+            };
+        };
+        evidence.push(format!(
+            "TDX module version = {}.{}",
+            module.major, module.minor
+        ));
+
+        let older = (major, minor) < (module.major, module.minor);
+        CheckResult {
+            action,
+            reason: tr_args(
+                "bios-seamldr-version-reason",
+                &[
+                    ("version", &version),
+                    ("module", &format!("{}.{}", module.major, module.minor)),
+                ],
+            ),
+            state: if older {
+                TestState::Warning
+            } else {
+                TestState::Ok
+            },
+            evidence,
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    const ADDRESS_BITS: u32 = 46;
+    const ADDRESS_MASK: u64 = (1 << ADDRESS_BITS) - 1;
+    const CONFIGURED: u64 = 1 << 3;
+    const ENABLED: u64 = 1 << 11;
+
+    /// Mask of a SEAM range of `size` bytes, enabled.
+    fn seamrr_mask(size: u64) -> u64 {
+        (ADDRESS_MASK & !(size - 1)) | ENABLED
+    }
+
+    #[test]
+    fn seamrr() {
+        let size = 1 << 31;
+        assert_eq!(
+            check_seamrr(size | CONFIGURED, seamrr_mask(size), ADDRESS_BITS),
+            None
+        );
+        assert_eq!(
+            check_seamrr(size, seamrr_mask(size), ADDRESS_BITS),
+            Some(tr("bios-seamrr-reason-unconfigured"))
+        );
+        assert_eq!(
+            check_seamrr(
+                size | CONFIGURED,
+                seamrr_mask(size) & !ENABLED,
+                ADDRESS_BITS
+            ),
+            Some(tr("bios-seamrr-reason-disabled"))
+        );
+        // base not aligned to the size
+        assert_eq!(
+            check_seamrr((size >> 1) | CONFIGURED, seamrr_mask(size), ADDRESS_BITS),
+            Some(tr("bios-seamrr-reason-range"))
+        );
+        // mask with a hole
+        assert_eq!(
+            check_seamrr(
+                size | CONFIGURED,
+                seamrr_mask(size) & !(1 << 40),
+                ADDRESS_BITS
+            ),
+            Some(tr("bios-seamrr-reason-range"))
+        );
+        assert_eq!(
+            check_seamrr(size | CONFIGURED, ENABLED, ADDRESS_BITS),
+            Some(tr("bios-seamrr-reason-range"))
+        );
+    }
+
+    #[test]
+    fn tme_algorithm_names() {
+        assert_eq!(tme_algorithms(0b101), "AES-XTS-128, AES-XTS-256");
+        assert_eq!(tme_algorithms(0b010), "AES-XTS-128 with integrity");
+    }
+
+    fn bios_tdx(msr: u64) -> TestState {
+        let fixture = Fixture {
+            msrs: [("0x1401".to_string(), msr)].into(),
+            ..Default::default()
+        };
+        BiosTdx.execute(&Probe::new(fixture)).state
+    }
+
+    #[test]
+    fn tdx_enabled() {
+        assert_eq!(bios_tdx(ENABLED), TestState::Ok);
+        assert_eq!(bios_tdx(CONFIGURED), TestState::Fail);
+    }
+}
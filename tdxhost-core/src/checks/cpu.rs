@@ -0,0 +1,383 @@
+//! Checks of the CPU: its vendor and model, TME enumeration and known
+//! errata.
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::errata;
+use crate::i18n::{tr, tr_args};
+
+fn check_cpu_manufacturer_id(probe: &Probe) -> String {
+    let res = probe.cpuid(0x0000_0000);
+    let name: [u8; 12] = unsafe { std::mem::transmute([res.ebx, res.edx, res.ecx]) };
+    String::from_utf8_lossy(&name).into_owned()
+}
+
+/// Family 6 models of the server CPUs supporting TDX. Later families are
+/// assumed to support it too.
+const TDX_CPU_MODELS: [(u32, &str); 4] = [
+    (0x8f, "Sapphire Rapids"),
+    (0xcf, "Emerald Rapids"),
+    (0xad, "Granite Rapids"),
+    (0xae, "Granite Rapids-D"),
+];
+
+/// Display family, model and stepping from CPUID 0x1.
+pub(crate) fn cpu_signature(probe: &Probe) -> (u32, u32, u32) {
+    let eax = probe.cpuid(0x0000_0001).eax;
+    let stepping = eax & 0xf;
+    let mut model = (eax >> 4) & 0xf;
+    let mut family = (eax >> 8) & 0xf;
+    if family == 0xf {
+        family += (eax >> 20) & 0xff;
+    }
+    if family == 0x6 || family >= 0xf {
+        model += ((eax >> 16) & 0xf) << 4;
+    }
+    (family, model, stepping)
+}
+
+/// The processor brand string from CPUID 0x80000002-0x80000004, if the CPU
+/// has one.
+pub(crate) fn cpu_brand(probe: &Probe) -> Option<String> {
+    if probe.cpuid(0x8000_0000).eax < 0x8000_0004 {
+        return None;
+    }
+
+    let mut brand = vec![];
+    for leaf in 0x8000_0002..=0x8000_0004 {
+        let res = probe.cpuid(leaf);
+        for reg in [res.eax, res.ebx, res.ecx, res.edx] {
+            brand.extend(reg.to_le_bytes());
+        }
+    }
+    let brand = String::from_utf8_lossy(&brand);
+    let brand = brand.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!brand.is_empty()).then(|| brand.to_owned())
+}
+
+/// Whether the CPU enumerates Total Memory Encryption in CPUID.07H:ECX[13],
+/// which TDX builds on, with the ECX value.
+fn check_cpu_enumerates_tme(probe: &Probe) -> (bool, Option<u32>) {
+    if probe.cpuid(0x0000_0000).eax < 0x7 {
+        return (false, None);
+    }
+    let ecx = probe.cpuid(0x0000_0007).ecx;
+    (ecx & (1 << 13) != 0, Some(ecx))
+}
+
+/// Microcode revision of the first CPU, from /proc/cpuinfo.
+pub(crate) fn cpu_microcode(probe: &Probe) -> Option<u32> {
+    let cpuinfo = probe.read_file("/proc/cpuinfo").ok()?;
+    let (_, value) = cpuinfo
+        .lines()
+        .filter_map(|l| l.split_once(':'))
+        .find(|(key, _)| key.trim() == "microcode")?;
+    u32::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok()
+}
+
+pub(super) struct CpuManufacturer;
+
+impl Check for CpuManufacturer {
+    fn id(&self) -> &'static str {
+        "cpu.manufacturer"
+    }
+
+    fn description(&self) -> String {
+        tr("cpu-manufacturer-name")
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Cpu]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let manu_name = check_cpu_manufacturer_id(probe);
+        let state = if manu_name == "GenuineIntel" {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        };
+        CheckResult {
+            action: tr("cpu-manufacturer-action"),
+            reason: tr("cpu-manufacturer-reason"),
+            evidence: vec![format!("CPUID 0x0 Manufacturer ID = {}", manu_name)],
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct CpuBareMetal;
+
+impl Check for CpuBareMetal {
+    fn id(&self) -> &'static str {
+        "cpu.bare_metal"
+    }
+
+    fn description(&self) -> String {
+        tr("cpu-bare-metal-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["cpu.manufacturer"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Cpu, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("cpu-bare-metal-action");
+        let Some(hypervisor) = probe.hypervisor() else {
+            return CheckResult {
+                action,
+                evidence: vec!["CPUID 0x1 ECX[31] (hypervisor) = 0".to_string()],
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        };
+
+        let name = hypervisor
+            .name()
+            .map_or_else(|| tr("module-unknown"), str::to_string);
+        // the TDX module only initializes on bare metal, a VMM nested in a
+        // guest cannot run TDs whatever the CPU the guest is shown
+        let key = if hypervisor.is_td() {
+            "cpu-bare-metal-reason-td"
+        } else if hypervisor.nested_vmx {
+            "cpu-bare-metal-reason-nested"
+        } else {
+            "cpu-bare-metal-reason"
+        };
+        CheckResult {
+            action,
+            reason: tr_args(key, &[("hypervisor", &name)]),
+            evidence: vec![
+                "CPUID 0x1 ECX[31] (hypervisor) = 1".to_string(),
+                format!(
+                    "CPUID 0x1 ECX[5] (VMX) = {}",
+                    u8::from(hypervisor.nested_vmx)
+                ),
+                format!("hypervisor signature = {:?}", hypervisor.signature),
+            ],
+            state: TestState::Fail,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct CpuModel;
+
+impl Check for CpuModel {
+    fn id(&self) -> &'static str {
+        "cpu.model"
+    }
+
+    fn description(&self) -> String {
+        tr("cpu-model-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["cpu.manufacturer"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Cpu, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (family, model, stepping) = cpu_signature(probe);
+        let codename = TDX_CPU_MODELS
+            .iter()
+            .find(|(m, _)| family == 0x6 && *m == model)
+            .map(|(_, name)| *name);
+        // family 0x13, from Diamond Rapids on, is expected to keep TDX;
+        // 0xF is the NetBurst Pentium 4 and Xeon, and no TDX part was
+        // released in the families between
+        let state = if codename.is_some() || family >= 0x13 {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        };
+
+        let name = match (cpu_brand(probe), codename) {
+            (Some(brand), Some(codename)) => format!("{} ({})", brand, codename),
+            (Some(brand), None) => brand,
+            (None, Some(codename)) => codename.to_owned(),
+            (None, None) => format!("family {:#x} model {:#x}", family, model),
+        };
+
+        CheckResult {
+            action: tr("cpu-model-action"),
+            reason: tr_args("cpu-model-reason", &[("model", &name)]),
+            evidence: vec![
+                format!(
+                    "CPUID 0x1 family = {:#x}, model = {:#x}, stepping = {:#x}",
+                    family, model, stepping
+                ),
+                format!("CPU = {}", name),
+            ],
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+/// TME, not TDX itself: the CPU does not enumerate TDX in CPUID until the
+/// TDX module is loaded, which bios.seamrr and tdx.module check.
+pub(super) struct CpuTmeEnumeration;
+
+impl Check for CpuTmeEnumeration {
+    fn id(&self) -> &'static str {
+        "cpu.tme"
+    }
+
+    fn description(&self) -> String {
+        tr("cpu-tme-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["cpu.model"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Cpu, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (enumerated, ecx) = check_cpu_enumerates_tme(probe);
+        let evidence = match ecx {
+            Some(ecx) => format!("CPUID 0x7 ECX = {:#010x}", ecx),
+            None => format!("CPUID 0x0 max leaf = {:#x}", probe.cpuid(0x0000_0000).eax),
+        };
+        CheckResult {
+            action: tr("cpu-tme-action"),
+            reason: tr("cpu-tme-reason"),
+            evidence: vec![evidence],
+            state: if enumerated {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct CpuErrata;
+
+impl Check for CpuErrata {
+    fn id(&self) -> &'static str {
+        "cpu.errata"
+    }
+
+    fn description(&self) -> String {
+        tr("cpu-errata-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Cpu, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (family, model, stepping) = cpu_signature(probe);
+        let microcode = cpu_microcode(probe);
+        let applying: Vec<_> = errata::matching(family, model, stepping, microcode).collect();
+
+        let mut hints = vec![];
+        for erratum in &applying {
+            hints.push(format!("\t{}: {}", erratum.id, erratum.title));
+            hints.push(format!("\t\t{}", erratum.link));
+        }
+
+        CheckResult {
+            action: tr("cpu-errata-action"),
+            reason: tr_args(
+                "cpu-errata-reason",
+                &[(
+                    "errata",
+                    &applying.iter().map(|e| e.id).collect::<Vec<_>>().join(", "),
+                )],
+            ),
+            evidence: vec![
+                format!(
+                    "CPUID 0x1 family = {:#x}, model = {:#x}, stepping = {:#x}",
+                    family, model, stepping
+                ),
+                match microcode {
+                    Some(microcode) => format!("microcode = {:#x}", microcode),
+                    None => "microcode = unknown".to_string(),
+                },
+            ],
+            state: if applying.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    fn probe(leaves: &[(u32, [u32; 4])]) -> Probe {
+        Probe::new(Fixture {
+            cpuid: leaves
+                .iter()
+                .map(|(leaf, regs)| (format!("{:#x}", leaf), *regs))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn manufacturer() {
+        // "Genu", "ntel", "ineI" in ebx, ecx, edx
+        let intel = probe(&[(0, [0x20, 0x756e_6547, 0x6c65_746e, 0x4965_6e69])]);
+        assert_eq!(CpuManufacturer.execute(&intel).state, TestState::Ok);
+        // "Auth", "cAMD", "enti"
+        let amd = probe(&[(0, [0x10, 0x6874_7541, 0x444d_4163, 0x6974_6e65])]);
+        assert_eq!(CpuManufacturer.execute(&amd).state, TestState::Fail);
+    }
+
+    #[test]
+    fn signature() {
+        assert_eq!(
+            cpu_signature(&probe(&[(1, [0x806f8, 0, 0, 0])])),
+            (0x6, 0x8f, 0x8)
+        );
+        assert_eq!(
+            cpu_signature(&probe(&[(1, [0xf29, 0, 0, 0])])),
+            (0xf, 0x2, 0x9)
+        );
+        assert_eq!(
+            cpu_signature(&probe(&[(1, [0x40_0f00, 0, 0, 0])])),
+            (0x13, 0x0, 0x0)
+        );
+    }
+
+    #[test]
+    fn model() {
+        let state = |eax| CpuModel.execute(&probe(&[(1, [eax, 0, 0, 0])])).state;
+        // Sapphire Rapids, Granite Rapids
+        assert_eq!(state(0x806f8), TestState::Ok);
+        assert_eq!(state(0xa06d1), TestState::Ok);
+        // Cascade Lake
+        assert_eq!(state(0x50657), TestState::Fail);
+        // NetBurst
+        assert_eq!(state(0xf29), TestState::Fail);
+        // families 0x10 to 0x12 have no TDX parts
+        assert_eq!(state(0x10_0f00), TestState::Fail);
+        assert_eq!(state(0x20_0f00), TestState::Fail);
+        // later families
+        assert_eq!(state(0x40_0f00), TestState::Ok);
+    }
+}
@@ -0,0 +1,208 @@
+//! Checks of what launching a TD takes: QEMU and TDX guest firmware.
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::i18n::{tr, tr_args};
+
+use super::package_version;
+
+/// QEMU binaries, as installed by upstream and by RHEL derivatives.
+const QEMU_BINARIES: [&str; 3] = [
+    "qemu-system-x86_64",
+    "/usr/libexec/qemu-kvm",
+    "/usr/bin/qemu-kvm",
+];
+
+/// Machine properties a TD's q35 machine is started with, as in
+/// `-machine q35,kernel-irqchip=split,confidential-guest-support=tdx0`.
+const QEMU_TDX_MACHINE_PROPERTIES: [&str; 2] = ["confidential-guest-support", "kernel-irqchip"];
+
+/// First upstream QEMU release able to run TDs.
+const QEMU_MIN_TDX_VERSION: (u32, u32) = (10, 1);
+
+/// The first QEMU binary that runs, with its version line, e.g.
+/// "QEMU emulator version 10.1.0".
+pub(crate) fn qemu_binary(probe: &Probe) -> Option<(&'static str, String)> {
+    QEMU_BINARIES.into_iter().find_map(|binary| {
+        let output = probe.output(binary, &["--version"]).ok()?;
+        let version = String::from_utf8_lossy(&output)
+            .lines()
+            .next()?
+            .trim()
+            .to_string();
+        Some((binary, version))
+    })
+}
+
+/// What a QEMU binary lacks to run TDs: the tdx-guest object and q35
+/// machine properties.
+pub(super) fn qemu_tdx_missing(probe: &Probe, binary: &str) -> Vec<String> {
+    let mut missing = vec![];
+    let objects = probe
+        .output(binary, &["-object", "help"])
+        .unwrap_or_default();
+    let objects = String::from_utf8_lossy(&objects);
+    if !objects.lines().any(|l| l.trim() == "tdx-guest") {
+        missing.push("tdx-guest".to_string());
+    }
+    let properties = probe
+        .output(binary, &["-machine", "q35,help"])
+        .unwrap_or_default();
+    let properties = String::from_utf8_lossy(&properties);
+    for property in QEMU_TDX_MACHINE_PROPERTIES {
+        // e.g. "  kernel-irqchip=<OnOffSplit> - Configure KVM in-kernel irqchip"
+        let listed = properties.lines().any(|l| {
+            l.trim_start()
+                .split(['=', ' '])
+                .next()
+                .is_some_and(|name| name == property)
+        });
+        if !listed {
+            missing.push(format!("q35 {}", property));
+        }
+    }
+    missing
+}
+
+/// TDX guest firmware (TDVF) images at the distributions' paths, with the
+/// package providing them.
+const TDVF_IMAGES: [(&str, &str); 5] = [
+    ("/usr/share/edk2/ovmf/OVMF.inteltdx.fd", "edk2-ovmf"),
+    ("/usr/share/edk2/ovmf/OVMF.inteltdx.secboot.fd", "edk2-ovmf"),
+    ("/usr/share/ovmf/OVMF.inteltdx.fd", "ovmf"),
+    // Canonical's TDX builds replace the regular image
+    ("/usr/share/ovmf/OVMF.fd", "ovmf"),
+    ("/usr/share/qemu/ovmf-x86_64-tdx.bin", "qemu-ovmf-x86_64"),
+];
+
+/// Signature of the TDVF metadata, which tells the VMM how to lay out the
+/// firmware in a TD.
+const TDVF_SIGNATURE: &[u8] = b"TDVF";
+
+pub(super) struct GuestQemu;
+
+impl Check for GuestQemu {
+    fn id(&self) -> &'static str {
+        "guest.qemu"
+    }
+
+    fn description(&self) -> String {
+        tr("guest-qemu-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Guest, Tag::Kvm]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("guest-qemu-action");
+        let Some((binary, version)) = qemu_binary(probe) else {
+            return CheckResult {
+                action,
+                reason: tr("guest-qemu-reason-missing"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let evidence = vec![format!("{} --version: {}", binary, version)];
+        let missing = qemu_tdx_missing(probe, binary);
+
+        if missing.is_empty() {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                ..Default::default()
+            };
+        }
+
+        let (major, minor) = QEMU_MIN_TDX_VERSION;
+        CheckResult {
+            action,
+            reason: tr_args(
+                "guest-qemu-reason-unsupported",
+                &[("version", &version), ("missing", &missing.join(", "))],
+            ),
+            evidence,
+            hints: vec![format!(
+                "\t{}",
+                tr_args(
+                    "guest-qemu-hint",
+                    &[("min", &format!("{}.{}", major, minor))]
+                )
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct GuestFirmware;
+
+impl Check for GuestFirmware {
+    fn id(&self) -> &'static str {
+        "guest.firmware"
+    }
+
+    fn description(&self) -> String {
+        tr("guest-firmware-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Guest]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("guest-firmware-action");
+        let mut evidence = vec![];
+        let mut found = vec![];
+        for (path, package) in TDVF_IMAGES {
+            let Ok(image) = probe.read_bytes(path) else {
+                continue;
+            };
+            if !image
+                .windows(TDVF_SIGNATURE.len())
+                .any(|w| w == TDVF_SIGNATURE)
+            {
+                evidence.push(format!("{}: no TDVF metadata", path));
+                continue;
+            }
+
+            evidence.push(format!("{}: TDVF metadata", path));
+            if let Some(version) = package_version(probe, package) {
+                evidence.push(format!("{} = {}", package, version));
+            }
+            // the digest of the image, not the MRTD of a TD booting it
+            if let Ok(digest) = probe.output("sha384sum", &[path]) {
+                let digest = String::from_utf8_lossy(&digest);
+                if let Some(digest) = digest.split_whitespace().next() {
+                    evidence.push(format!("sha384({}) = {}", path, digest));
+                }
+            }
+            found.push(path);
+        }
+
+        if found.is_empty() {
+            return CheckResult {
+                action,
+                reason: tr("guest-firmware-reason-missing"),
+                evidence,
+                hints: vec![format!("\t{}", tr("guest-firmware-hint"))],
+                ..Default::default()
+            };
+        }
+        CheckResult {
+            action,
+            state: TestState::Ok,
+            evidence,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,658 @@
+//! Checks of KVM, which runs TDs on the TDX module.
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::i18n::{tr, tr_args};
+use crate::remediation::{Action, Remediation};
+use crate::{grub, kvm, modprobe};
+
+use super::os::cmdline_param;
+
+#[derive(Debug)]
+enum KvmParameter {
+    Tdx,
+    Sgx,
+}
+
+/// Whether kvm_intel is loaded or built into the kernel.
+fn kvm_intel_loaded(probe: &Probe) -> bool {
+    probe.exists("/sys/module/kvm_intel")
+        || probe.modules().is_ok_and(|modules| {
+            modules
+                .lines()
+                .any(|l| l.split_whitespace().next() == Some("kvm_intel"))
+        })
+}
+
+/// Why kvm_intel cannot be loaded, if a cause can be found, adding what the
+/// verdict is based on to `evidence`.
+fn kvm_intel_blocked(probe: &Probe, evidence: &mut Vec<String>) -> Option<String> {
+    if let Ok(cmdline) = probe.cmdline() {
+        for param in ["module_blacklist", "modprobe.blacklist"] {
+            let blacklisted = cmdline_param(&cmdline, param)
+                .is_some_and(|modules| modules.split(',').any(|m| m == "kvm_intel"));
+            if blacklisted {
+                evidence.push(format!("/proc/cmdline = {}", cmdline.trim()));
+                return Some(tr_args(
+                    "kvm-module-reason-blacklisted",
+                    &[("source", param)],
+                ));
+            }
+        }
+    }
+    if let Ok(config) = probe.output("modprobe", &["--showconfig"]) {
+        let config = String::from_utf8_lossy(&config);
+        // `install kvm_intel /bin/false` is the usual way to block loading
+        // even by name
+        let blacklist = config.lines().find(|l| {
+            let words: Vec<&str> = l.split_whitespace().collect();
+            match words[..] {
+                ["blacklist", "kvm_intel"] => true,
+                ["install", "kvm_intel", command, ..] => {
+                    command.ends_with("/false") || command.ends_with("/true")
+                }
+                _ => false,
+            }
+        });
+        if let Some(line) = blacklist {
+            evidence.push(format!("modprobe --showconfig: {}", line));
+            return Some(tr_args(
+                "kvm-module-reason-blacklisted",
+                &[("source", "modprobe.d")],
+            ));
+        }
+    }
+
+    let ecx = probe.cpuid(0x0000_0001).ecx;
+    evidence.push(format!("CPUID.1:ECX = {:#010x}", ecx));
+    let vmx = ecx & (1 << 5) != 0;
+    if let Some(hypervisor) = probe.hypervisor().filter(|h| !h.nested_vmx) {
+        let name = hypervisor
+            .name()
+            .map_or_else(|| tr("module-unknown"), str::to_string);
+        return Some(tr_args(
+            "kvm-module-reason-hypervisor",
+            &[("hypervisor", &name)],
+        ));
+    }
+
+    // IA32_FEATURE_CONTROL locked without VMX outside SMX
+    let disabled_by_bios = match probe.read_msr(0x3a) {
+        Ok(value) => {
+            evidence.push(format!("{:#x} = {:#x}", 0x3a, value));
+            value & 1 != 0 && value & (1 << 2) == 0
+        }
+        Err(_) => probe.kernel_log().is_ok_and(|log| {
+            log.lines()
+                .filter(|l| l.contains("kvm"))
+                .inspect(|l| evidence.push(l.to_string()))
+                .any(|l| l.contains("disabled by bios"))
+        }),
+    };
+    if disabled_by_bios {
+        Some(tr("kvm-module-reason-bios"))
+    } else if !vmx {
+        Some(tr("kvm-module-reason-no-vmx"))
+    } else {
+        None
+    }
+}
+
+fn check_kvm_supported(probe: &Probe) -> (TestState, String) {
+    match probe.kvm_api_version() {
+        Ok(api_version) if api_version < 0 => (TestState::Fail, tr("kvm-supported-inaccessible")),
+        Ok(_) => (TestState::Ok, String::new()),
+        Err(_) => (TestState::Fail, tr("kvm-supported-unreadable")),
+    }
+}
+
+fn check_kvm_module_supported(probe: &Probe, param: KvmParameter) -> (TestState, String, String) {
+    let param_loc = match param {
+        KvmParameter::Tdx => "/sys/module/kvm_intel/parameters/tdx",
+        KvmParameter::Sgx => "/sys/module/kvm_intel/parameters/sgx",
+    };
+
+    let (result, reason) = if probe.exists(param_loc) {
+        match probe.read_file(param_loc) {
+            Ok(result) => {
+                if result.trim() == "1" || result.trim() == "Y" {
+                    (TestState::Ok, String::new())
+                } else {
+                    (
+                        TestState::Fail,
+                        tr_args(
+                            "kvm-param-invalid",
+                            &[("path", param_loc), ("value", result.trim())],
+                        ),
+                    )
+                }
+            }
+            Err(e) => (
+                TestState::Fail,
+                tr_args("kvm-param-unreadable", &[("error", &e.to_string())]),
+            ),
+        }
+    } else {
+        (
+            TestState::Fail,
+            tr_args("kvm-param-missing", &[("path", param_loc)]),
+        )
+    };
+
+    let action = tr_args(
+        "kvm-param-action",
+        &[("param", &param_loc[param_loc.rfind('/').unwrap() + 1..])],
+    );
+
+    (result, action, reason)
+}
+
+//         kvm_intel is loaded
+//                  |
+//                  |
+//            KVM is enabled
+//                  |
+//                  |
+//      +----------------------+
+//      |                      |
+//     SGX                    TDX
+//  Mod Enabled           Mod Enabled
+//                             |
+//                             |
+//                        TD VM type
+//                          offered
+
+pub(super) struct KvmModule;
+
+impl Check for KvmModule {
+    fn id(&self) -> &'static str {
+        "kvm.module"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-module-name")
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("kvm-module-action");
+        if kvm_intel_loaded(probe) {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+
+        let mut evidence = vec![];
+        let reason = kvm_intel_blocked(probe, &mut evidence)
+            .unwrap_or_else(|| tr("kvm-module-reason-not-loaded"));
+        CheckResult {
+            action,
+            reason,
+            evidence,
+            ..Default::default()
+        }
+    }
+
+    fn remediation(&self, probe: &Probe) -> Option<Remediation> {
+        // a blacklisted module or VT-x being off needs a human
+        let mut evidence = vec![];
+        if kvm_intel_loaded(probe) || kvm_intel_blocked(probe, &mut evidence).is_some() {
+            return None;
+        }
+
+        Some(Remediation {
+            summary: tr("kvm-supported-fix"),
+            actions: vec![Action::command("modprobe", &["kvm_intel"])],
+            reboot_required: false,
+        })
+    }
+}
+
+pub(super) struct KvmSupported;
+
+impl Check for KvmSupported {
+    fn id(&self) -> &'static str {
+        "kvm.supported"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-supported-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.module"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (state, reason) = check_kvm_supported(probe);
+        CheckResult {
+            action: tr("kvm-supported-action"),
+            reason,
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct KvmSgxParam;
+
+impl Check for KvmSgxParam {
+    fn id(&self) -> &'static str {
+        "kvm.sgx_param"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-sgx-param-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.supported"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm, Tag::Sgx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (state, action, reason) = check_kvm_module_supported(probe, KvmParameter::Sgx);
+        CheckResult {
+            action,
+            reason,
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct KvmTdxParam;
+
+impl Check for KvmTdxParam {
+    fn id(&self) -> &'static str {
+        "kvm.tdx_param"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-tdx-param-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.supported"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (state, action, reason) = check_kvm_module_supported(probe, KvmParameter::Tdx);
+        CheckResult {
+            action,
+            reason,
+            state,
+            ..Default::default()
+        }
+    }
+
+    fn remediation(&self, probe: &Probe) -> Option<Remediation> {
+        modprobe::set_module_option(probe, "kvm_intel", "tdx=1")
+            .or_else(|| grub::append_kernel_params(probe, &["kvm_intel.tdx=1"]))
+    }
+}
+
+pub(super) struct KvmTdxVm;
+
+impl Check for KvmTdxVm {
+    fn id(&self) -> &'static str {
+        "kvm.tdx_vm"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-tdx-vm-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.tdx_param"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("kvm-tdx-vm-action");
+        // kvm_intel.tdx=1 is kept even when the TDX module did not
+        // initialize, KVM then only creates regular VMs
+        let types = match probe.kvm_vm_types() {
+            Ok(types) => types,
+            Err(e) => {
+                return CheckResult {
+                    action,
+                    reason: tr_args("kvm-tdx-vm-reason-unreadable", &[("error", &e.to_string())]),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let evidence = vec![format!("KVM_CAP_VM_TYPES = {:#x}", types)];
+        if types & (1 << kvm::KVM_X86_TDX_VM) != 0 {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                ..Default::default()
+            };
+        }
+
+        let offered: Vec<&str> = kvm::VM_TYPES
+            .iter()
+            .filter(|(vm_type, _)| types & (1 << vm_type) != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        CheckResult {
+            action,
+            reason: tr_args(
+                "kvm-tdx-vm-reason-missing",
+                &[("types", &offered.join(", "))],
+            ),
+            evidence,
+            hints: vec![format!("\t{}", tr("kvm-tdx-vm-hint"))],
+            ..Default::default()
+        }
+    }
+}
+
+/// Users libvirt runs QEMU as, on Debian and on Red Hat derivatives.
+const LIBVIRT_QEMU_USERS: [&str; 2] = ["libvirt-qemu", "qemu"];
+
+/// The SGX device nodes, which udev rules give their group and mode.
+const SGX_DEVICES: [&str; 2] = ["sgx_enclave", "sgx_provision"];
+
+const UDEV_RULES_DIRS: [&str; 3] = [
+    "/etc/udev/rules.d",
+    "/usr/lib/udev/rules.d",
+    "/lib/udev/rules.d",
+];
+
+/// Where the standard device rules are installed.
+const DEVICE_RULES_PATH: &str = "/etc/udev/rules.d/91-tdxhost-devices.rules";
+
+/// The KVM and SGX device rules, as shipped by the distros and Intel's SGX
+/// packages.
+const DEVICE_RULES: &str = r#"SUBSYSTEM=="misc", KERNEL=="kvm", GROUP="kvm", MODE="0660"
+SUBSYSTEM=="misc", KERNEL=="sgx_enclave", MODE="0666", SYMLINK+="sgx/enclave"
+SUBSYSTEM=="misc", KERNEL=="sgx_provision", GROUP="sgx_prv", MODE="0660", SYMLINK+="sgx/provision"
+"#;
+
+/// What keeps the VMM from the device nodes: the group and mode of
+/// /dev/kvm, missing SGX udev rules and VMM users outside the kvm group.
+/// The first two the standard rules fix, which is also returned.
+fn device_problems(
+    probe: &Probe,
+    evidence: &mut Vec<String>,
+    hints: &mut Vec<String>,
+) -> (Vec<String>, bool) {
+    let mut problems = vec![];
+    let mut fixable = false;
+
+    // e.g. "kvm 660"
+    let kvm = probe
+        .output("stat", &["-L", "-c", "%G %a", "/dev/kvm"])
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o).trim().to_string());
+    let (group, mode) = match kvm.as_deref().and_then(|k| k.split_once(' ')) {
+        Some((group, mode)) => (group.to_string(), u32::from_str_radix(mode, 8).unwrap_or(0)),
+        None => return (problems, fixable),
+    };
+    evidence.push(format!("/dev/kvm: group {}, mode {:o}", group, mode));
+    let world = mode & 0o006 == 0o006;
+    if !world && (group != "kvm" || mode & 0o060 != 0o060) {
+        problems.push(tr_args(
+            "kvm-devices-reason-kvm",
+            &[("group", &group), ("mode", &format!("{:o}", mode))],
+        ));
+        fixable = true;
+    }
+
+    if probe.exists("/dev/sgx_enclave") {
+        let rules: String = UDEV_RULES_DIRS
+            .iter()
+            .flat_map(|dir| {
+                probe
+                    .read_dir(dir)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|name| name.ends_with(".rules"))
+                    .map(move |name| format!("{}/{}", dir, name))
+            })
+            .filter_map(|path| probe.read_file(&path).ok())
+            .collect();
+        for device in SGX_DEVICES {
+            if !rules.contains(&format!("KERNEL==\"{}\"", device)) {
+                evidence.push(format!("no udev rule for /dev/{}", device));
+                problems.push(tr_args("kvm-devices-reason-rules", &[("device", device)]));
+                fixable = true;
+            }
+        }
+    }
+
+    // nobody else may open /dev/kvm: the VMM users must be in the kvm
+    // group, which it belongs to once fixed
+    if !world {
+        let users: Vec<String> = match &probe.config().vmm_user {
+            Some(user) => vec![user.clone()],
+            None => LIBVIRT_QEMU_USERS
+                .iter()
+                .map(|u| u.to_string())
+                .chain(probe.sudo_user().map(str::to_string))
+                .collect(),
+        };
+        for user in users {
+            // fails for users that do not exist
+            let Ok(groups) = probe.output("id", &["-nG", &user]) else {
+                continue;
+            };
+            let groups = String::from_utf8_lossy(&groups).trim().to_string();
+            evidence.push(format!("{}: groups {}", user, groups));
+            if !groups.split_whitespace().any(|g| g == "kvm") {
+                let args = [("user", user.as_str()), ("group", "kvm")];
+                problems.push(tr_args("kvm-devices-reason-user", &args));
+                hints.push(format!("\t{}", tr_args("kvm-devices-hint-user", &args)));
+            }
+        }
+    }
+    (problems, fixable)
+}
+
+pub(super) struct KvmDevices;
+
+impl Check for KvmDevices {
+    fn id(&self) -> &'static str {
+        "kvm.devices"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-devices-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.supported"]
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm, Tag::Sgx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let (mut evidence, mut hints) = (vec![], vec![]);
+        let (problems, _) = device_problems(probe, &mut evidence, &mut hints);
+        CheckResult {
+            action: tr("kvm-devices-action"),
+            reason: problems.join("; "),
+            evidence,
+            state: if problems.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            hints,
+            ..Default::default()
+        }
+    }
+
+    fn remediation(&self, probe: &Probe) -> Option<Remediation> {
+        let (_, fixable) = device_problems(probe, &mut vec![], &mut vec![]);
+        fixable.then(|| Remediation {
+            summary: tr_args("kvm-devices-fix", &[("path", DEVICE_RULES_PATH)]),
+            actions: vec![
+                Action::command("groupadd", &["-f", "-r", "kvm"]),
+                Action::command("groupadd", &["-f", "-r", "sgx_prv"]),
+                Action::WriteFile {
+                    path: DEVICE_RULES_PATH.into(),
+                    contents: DEVICE_RULES.to_string(),
+                },
+                Action::command("udevadm", &["control", "--reload"]),
+                Action::command("udevadm", &["trigger", "--subsystem-match=misc"]),
+            ],
+            reboot_required: false,
+        })
+    }
+}
+
+/// First kernel release with guest_memfd, which holds TDs' private memory.
+const GUEST_MEMFD_KERNEL: &str = "6.8";
+
+pub(super) struct KvmPrivateMemory;
+
+impl Check for KvmPrivateMemory {
+    fn id(&self) -> &'static str {
+        "kvm.private_memory"
+    }
+
+    fn description(&self) -> String {
+        tr("kvm-private-memory-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["kvm.supported"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Kvm, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("kvm-private-memory-action");
+        let memory = match probe.kvm_private_memory() {
+            Ok(memory) => memory,
+            Err(e) => {
+                return CheckResult {
+                    action,
+                    reason: tr_args(
+                        "kvm-private-memory-reason-unreadable",
+                        &[("error", &e.to_string())],
+                    ),
+                    ..Default::default()
+                }
+            }
+        };
+
+        // only VM types with private memory offer the attribute, /dev/kvm
+        // answers for the default one
+        let private =
+            !memory.td || memory.memory_attributes & kvm::KVM_MEMORY_ATTRIBUTE_PRIVATE != 0;
+        let mut evidence = vec![
+            format!("asked {}", if memory.td { "a TD" } else { "/dev/kvm" }),
+            format!("KVM_CAP_GUEST_MEMFD = {}", u8::from(memory.guest_memfd)),
+            format!("KVM_CAP_USER_MEMORY2 = {}", u8::from(memory.user_memory2)),
+            format!(
+                "KVM_CAP_MEMORY_ATTRIBUTES = {:#x}",
+                memory.memory_attributes
+            ),
+        ];
+        let missing: Vec<&str> = [
+            (memory.guest_memfd, "guest_memfd"),
+            (memory.user_memory2, "KVM_CAP_USER_MEMORY2"),
+            (private, "KVM_MEMORY_ATTRIBUTE_PRIVATE"),
+        ]
+        .into_iter()
+        .filter(|(offered, _)| !offered)
+        .map(|(_, name)| name)
+        .collect();
+        if missing.is_empty() {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                ..Default::default()
+            };
+        }
+
+        let release = probe
+            .read_file("/proc/sys/kernel/osrelease")
+            .map(|r| r.trim().to_string())
+            .unwrap_or_default();
+        evidence.push(format!("kernel = {}", release));
+        CheckResult {
+            action,
+            reason: tr_args(
+                "kvm-private-memory-reason",
+                &[("missing", &missing.join(", "))],
+            ),
+            evidence,
+            hints: vec![format!(
+                "\t{}",
+                tr_args(
+                    "kvm-private-memory-hint",
+                    &[
+                        ("release", &release),
+                        ("guest_memfd", GUEST_MEMFD_KERNEL),
+                        ("tdx", &probe.config().min_kernel_version),
+                    ]
+                )
+            )],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    #[test]
+    fn sudo_user_from_probe() {
+        let fixture = || Fixture {
+            commands: [
+                ("stat -L -c %G %a /dev/kvm", "kvm 660\n"),
+                ("id -nG alice", "alice wheel\n"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .into(),
+            ..Default::default()
+        };
+        let problems = |probe: &Probe| device_problems(probe, &mut vec![], &mut vec![]).0;
+
+        assert!(problems(&Probe::new(fixture())).is_empty());
+        let probe = Probe::new(fixture()).with_sudo_user(Some("alice".to_string()));
+        assert_eq!(
+            problems(&probe),
+            [tr_args(
+                "kvm-devices-reason-user",
+                &[("user", "alice"), ("group", "kvm")]
+            )]
+        );
+    }
+}
@@ -0,0 +1,515 @@
+//! The built-in checks, by the area of the host they inspect.
+
+use crate::check::{Check, CheckResult, Probe, TestState};
+use crate::error::{Result, TdxHostError};
+use crate::i18n::{tr, tr_args};
+
+mod attestation;
+mod bios;
+mod cpu;
+mod guest;
+mod kvm;
+mod os;
+mod sgx;
+mod tdx;
+
+pub(crate) use attestation::{DCAP_PACKAGES, QCNL_CONF};
+pub(crate) use cpu::{cpu_brand, cpu_microcode, cpu_signature};
+pub(crate) use guest::qemu_binary;
+pub(crate) use os::{kernel_config, os_release_value, secure_boot};
+pub(crate) use tdx::{
+    check_tdx_module, seamldr_version, tdx_cmrs, tdx_features0, tdx_log_lines, TdxModuleRelease,
+    TDX_MODULE_FEATURES,
+};
+
+/// Properties of a systemd unit, e.g. "ActiveState" to "inactive".
+pub(crate) fn unit_properties(
+    probe: &Probe,
+    unit: &str,
+    properties: &str,
+) -> Result<Vec<(String, String)>> {
+    let output = probe
+        .output("systemctl", &["show", "--property", properties, unit])
+        .map_err(|e| TdxHostError::Command {
+            command: format!("systemctl show {}", unit),
+            stderr: e.to_string(),
+        })?;
+    Ok(String::from_utf8_lossy(&output)
+        .lines()
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect())
+}
+
+/// A DMI field of the system, e.g. "sys_vendor", if it is set.
+pub(crate) fn dmi(probe: &Probe, field: &str) -> Option<String> {
+    probe
+        .read_file(&format!("/sys/class/dmi/id/{}", field))
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Version of an installed package, from dpkg or rpm.
+pub(crate) fn package_version(probe: &Probe, package: &str) -> Option<String> {
+    let output = probe
+        .output("dpkg-query", &["-W", "-f", "${Version}", package])
+        .or_else(|_| probe.output("rpm", &["-q", "--qf", "%{VERSION}", package]))
+        .ok()?;
+    let version = String::from_utf8_lossy(&output).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/// Major and minor release of a package version, e.g. (1, 21) for
+/// "1.21.100.3-jammy1".
+fn release(version: &str) -> Option<(u32, u32)> {
+    // skip the epoch
+    let version = version.rsplit(':').next()?;
+    let mut parts = version.split(['.', '-']);
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// Result of a check whose probe failed; the host state is unknown, which
+/// does not count as ready.
+fn probe_failed(action: String, e: TdxHostError) -> CheckResult {
+    CheckResult {
+        action,
+        reason: tr_args("probe-failed", &[("error", &e.to_string())]),
+        state: TestState::Fail,
+        ..Default::default()
+    }
+}
+
+/// Where the setting held by `msr` can be seen without reading the MSR.
+fn msr_alternative(msr: u32) -> Option<&'static str> {
+    match msr {
+        0x3a => Some("grep -w sgx /proc/cpuinfo"),
+        0xfe | 0x1400 | 0x1401 => Some("dmesg | grep virt/tdx"),
+        0x87 | 0x981 => Some("dmesg | grep 'private KeyID range'"),
+        0x982 => Some("dmesg | grep x86/tme"),
+        _ => None,
+    }
+}
+
+/// Like `probe_failed`, but a read blocked by kernel lockdown skips the
+/// check and points at alternatives.
+fn msr_failed(probe: &Probe, msr: u32, action: String, e: TdxHostError) -> CheckResult {
+    let Some(mode) = probe.lockdown() else {
+        return probe_failed(action, e);
+    };
+    if !matches!(
+        e,
+        TdxHostError::PermissionDenied(_) | TdxHostError::MsrUnavailable { .. }
+    ) {
+        return probe_failed(action, e);
+    }
+
+    CheckResult {
+        action,
+        reason: tr_args("reason-lockdown", &[("mode", &mode)]),
+        state: TestState::Skip,
+        hints: msr_alternative(msr)
+            .map(|cmd| {
+                format!(
+                    "\t{}",
+                    tr_args("hint-lockdown-alternative", &[("command", cmd)])
+                )
+            })
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Verify the BIOS setting of a manual check from outside the BIOS menu,
+/// e.g. from the BMC. `manual` stands when the setting cannot be read.
+fn bios_attribute_result(
+    probe: &Probe,
+    check_id: &str,
+    expected: &str,
+    accepts: impl Fn(&str) -> bool,
+    mut manual: CheckResult,
+) -> CheckResult {
+    let (Some(knob), Some(attributes)) = (crate::bios::knob(check_id), probe.bios_attributes())
+    else {
+        return manual;
+    };
+    let attribute = match attributes.as_ref() {
+        Ok(attributes) => knob.attribute(attributes),
+        Err(e) => {
+            manual.hints.push(format!(
+                "\t{}",
+                tr_args("hint-bios-attributes-failed", &[("error", &e.to_string())])
+            ));
+            return manual;
+        }
+    };
+    let Some(attribute) = attribute else {
+        manual.hints.push(format!(
+            "\t{}",
+            tr_args("hint-bios-attribute-missing", &[("setting", knob.setting)])
+        ));
+        return manual;
+    };
+
+    let mut evidence = vec![format!(
+        "BIOS attribute {} = {}",
+        attribute.name, attribute.value
+    )];
+    evidence.append(&mut manual.evidence);
+    CheckResult {
+        action: manual.action,
+        reason: tr_args(
+            "reason-bios-attribute",
+            &[
+                ("setting", knob.setting),
+                ("value", &attribute.value),
+                ("expected", expected),
+            ],
+        ),
+        state: if accepts(&attribute.value) {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        },
+        evidence,
+        ..Default::default()
+    }
+}
+
+/// Whether a BIOS setting's value enables it, e.g. "Enabled".
+fn bios_enabled(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "enable" | "enabled" | "on" | "true"
+    )
+}
+
+/// "0-3,8" for CPUs 0, 1, 2, 3 and 8.
+fn cpu_list(cpus: &[u16]) -> String {
+    let mut ranges: Vec<(u16, u16)> = vec![];
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+
+    ranges
+        .iter()
+        .map(|&(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{}-{}", first, last)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Where `cpus` are, e.g. "package 1 (CPUs 32-63)".
+fn cpu_location(probe: &Probe, cpus: &[u16]) -> String {
+    let mut packages: Vec<u16> = cpus.iter().filter_map(|&c| probe.package(c)).collect();
+    packages.sort_unstable();
+    packages.dedup();
+
+    let count = if cpus.len() == 1 { "one" } else { "other" };
+    if packages.is_empty() {
+        return tr_args(
+            "cpu-location-unknown",
+            &[("cpus", &cpu_list(cpus)), ("count", count)],
+        );
+    }
+    let packages = packages
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    tr_args(
+        "cpu-location",
+        &[
+            ("packages", &packages),
+            ("cpus", &cpu_list(cpus)),
+            ("count", count),
+        ],
+    )
+}
+
+/// Values of `msr` on `cpus`, each with the CPUs holding it.
+fn read_msr_on(probe: &Probe, cpus: &[u16], msr: u32) -> Result<Vec<(u64, Vec<u16>)>> {
+    let mut values: Vec<(u64, Vec<u16>)> = vec![];
+    for &cpu in cpus {
+        let value = probe.read_msr_on(cpu, msr)?;
+        match values.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, cpus)) => cpus.push(cpu),
+            None => values.push((value, vec![cpu])),
+        }
+    }
+    Ok(values)
+}
+
+fn msr_evidence(msr: u32, values: &[(u64, Vec<u16>)]) -> Vec<String> {
+    match values {
+        [(value, _)] => vec![format!("MSR {:#x} = {:#x}", msr, value)],
+        _ => values
+            .iter()
+            .map(|(value, cpus)| {
+                let label = if cpus.len() == 1 { "CPU" } else { "CPUs" };
+                format!(
+                    "MSR {:#x} = {:#x} on {} {}",
+                    msr,
+                    value,
+                    label,
+                    cpu_list(cpus)
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Pass when `bit` is set in `msr` on every CPU the probe reads MSRs on,
+/// recording the register as evidence. On multi-socket hosts the reason
+/// names the misconfigured packages.
+fn msr_bit_result(probe: &Probe, msr: u32, bit: u32, action: &str, reason: &str) -> CheckResult {
+    let values = match probe
+        .msr_cpus()
+        .and_then(|cpus| read_msr_on(probe, &cpus, msr))
+    {
+        Ok(values) => values,
+        Err(e) => return msr_failed(probe, msr, tr(action), e),
+    };
+    let unset = msr_bit_unset(probe, &values, bit, reason);
+
+    CheckResult {
+        action: tr(action),
+        state: if unset.is_none() {
+            TestState::Ok
+        } else {
+            TestState::Fail
+        },
+        reason: unset.unwrap_or_else(|| tr(reason)),
+        evidence: msr_evidence(msr, &values),
+        ..Default::default()
+    }
+}
+
+/// The translated `reason` if `bit` is unset in any of `values`, naming the
+/// CPUs when it is set on others.
+fn msr_bit_unset(
+    probe: &Probe,
+    values: &[(u64, Vec<u16>)],
+    bit: u32,
+    reason: &str,
+) -> Option<String> {
+    let mut unset: Vec<u16> = values
+        .iter()
+        .filter(|(v, _)| v & (1 << bit) == 0)
+        .flat_map(|(_, cpus)| cpus.iter().copied())
+        .collect();
+    unset.sort_unstable();
+
+    if unset.is_empty() {
+        None
+    } else if values.len() > 1 {
+        Some(tr_args(
+            "reason-msr-partial",
+            &[
+                ("reason", &tr(reason)),
+                ("where", &cpu_location(probe, &unset)),
+            ],
+        ))
+    } else {
+        Some(tr(reason))
+    }
+}
+
+const ENABLING_GUIDE: &str =
+    "https://cc-enabling.trustedservices.intel.com/intel-tdx-enabling-guide";
+
+/// Where to read up on the built-in checks, by check id or id prefix, the
+/// longest match winning.
+const DOCS: [(&str, &str); 14] = [
+    ("cpu.", "03/hardware_selection/"),
+    ("bios.", "04/hardware_setup/"),
+    ("bios.sgx_reg_server", "02/infrastructure_setup/"),
+    ("os.", "05/host_os_setup/"),
+    ("os.kexec", "https://docs.kernel.org/arch/x86/tdx.html"),
+    ("os.hibernate", "https://docs.kernel.org/arch/x86/tdx.html"),
+    ("tdx.", "https://docs.kernel.org/arch/x86/tdx.html"),
+    ("sgx.", "https://docs.kernel.org/arch/x86/sgx.html"),
+    ("sgx.registration", "02/infrastructure_setup/"),
+    ("sgx.platform_manifest", "02/infrastructure_setup/"),
+    (
+        "kvm.",
+        "https://docs.kernel.org/virt/kvm/x86/intel-tdx.html",
+    ),
+    ("attestation.", "02/infrastructure_setup/"),
+    (
+        "guest.qemu",
+        "https://www.qemu.org/docs/master/system/i386/tdx.html",
+    ),
+    ("guest.firmware", "06/guest_os_setup/"),
+];
+
+/// Documentation of a built-in check; relative links are sections of the
+/// Intel TDX enabling guide.
+pub fn docs_url(id: &str) -> Option<String> {
+    let (_, url) = DOCS
+        .iter()
+        .filter(|(prefix, _)| id.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())?;
+    Some(if url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("{}/{}", ENABLING_GUIDE, url)
+    })
+}
+
+//                       CPU Manufacturer ID
+//                                |
+//                                |
+//                          CPU supports TDX
+//                                |
+//                                |
+//                        CPUID enumerates TME
+//                                |
+//                                |
+//                          OS is supported
+//                                |
+//                                +--------------------+
+//                                |                    |
+//                          SGX is enabled        Kernel supports
+//                                |                 TDX hosts
+//                                |                    |
+//                                |                    +----------------+----------------+
+//                                |                    |                |                |
+//                                |              Kernel built for  Kernel command   Hibernation is
+//                                |                 TDX hosts       line is set        disabled
+//                                |
+//                                +--------------------+----------------+
+//                                |                    |                |
+//                    Feature control is locked    EPC is large    SGX driver is
+//                                |                  enough          in-kernel
+//                                |
+//                                |
+//                          TDX is enabled
+//                                |
+//                                |
+//      +-------------------------+-----------------------+-----------+-----------+-----------+
+//      |             |           |          |            |           |           |           |
+//    TDX Mod.       TME       TME-MT     TDX Key      SGX Reg.     MSRs      SEAMRR    System RAM
+//  Initialized    Enabled    Enabled    Split != 0    Server     agree on    valid    convertible
+//      |             |                      |                    all CPUs
+//      |             |                      |
+//   Module    +------+------+           TDX KeyIDs
+//   version   |             |              > 0
+//   recent  TME config   TME algorithm
+//   enough supported by  as expected
+//              CPU             |
+//                              |
+//                          Memory integrity
+//                           as expected
+
+/// The built-in checks, each after the checks it depends on.
+pub fn builtin() -> Vec<Box<dyn Check>> {
+    let mut checks: Vec<Box<dyn Check>> = vec![
+        Box::new(cpu::CpuManufacturer),
+        Box::new(cpu::CpuBareMetal),
+        Box::new(cpu::CpuModel),
+        Box::new(cpu::CpuTmeEnumeration),
+        Box::new(os::OsDistro),
+        Box::new(os::OsKernel),
+        Box::new(os::OsKernelConfig),
+        Box::new(os::OsCmdline),
+        Box::new(os::OsHibernate),
+        Box::new(bios::BiosFirmware),
+        Box::new(bios::BiosSgx),
+        Box::new(bios::BiosFeatureControl),
+        Box::new(sgx::SgxEpc),
+        Box::new(sgx::SgxDriver),
+        Box::new(bios::BiosTdx),
+        Box::new(tdx::TdxModule),
+        Box::new(tdx::TdxModuleVersion),
+        Box::new(tdx::TdxCmr),
+        Box::new(tdx::TdxRuntimeUpdate),
+        Box::new(bios::BiosTme),
+        Box::new(bios::BiosTmeCapability),
+        Box::new(bios::BiosTmeAlgorithm),
+        Box::new(bios::BiosTdxIntegrity),
+        Box::new(bios::BiosTmeMt),
+        Box::new(bios::BiosTdxKeySplit),
+        Box::new(bios::BiosTdxKeyIds),
+        Box::new(bios::BiosSgxRegServer),
+        Box::new(bios::MsrConsistency),
+        Box::new(bios::BiosSeamrr),
+        Box::new(kvm::KvmModule),
+        Box::new(kvm::KvmSupported),
+        Box::new(kvm::KvmSgxParam),
+        Box::new(kvm::KvmTdxParam),
+        Box::new(kvm::KvmTdxVm),
+        Box::new(kvm::KvmPrivateMemory),
+        Box::new(kvm::KvmDevices),
+        Box::new(bios::BiosVolatileMemory1lm),
+        Box::new(bios::BiosTmeBypass),
+        Box::new(bios::BiosSeamLoader),
+        Box::new(bios::BiosSeamldrVersion),
+        Box::new(cpu::CpuErrata),
+        Box::new(os::OsKexec),
+        Box::new(os::OsSecureBoot),
+        Box::new(os::OsMacPolicy),
+        Box::new(os::OsSwap),
+        Box::new(os::OsOvercommit),
+        Box::new(os::OsMemlock),
+        Box::new(sgx::SgxRegistration),
+        Box::new(sgx::SgxPlatformManifest),
+        Box::new(attestation::AttestationQcnl),
+        Box::new(attestation::AttestationCollateral),
+        Box::new(attestation::AttestationAesmd),
+        Box::new(attestation::AttestationDcapVersions),
+        Box::new(guest::GuestQemu),
+        Box::new(guest::GuestFirmware),
+    ];
+    checks.extend(
+        tdx::TDX_MODULE_FEATURES
+            .into_iter()
+            .map(|f| Box::new(f) as Box<dyn Check>),
+    );
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_releases() {
+        assert_eq!(release("1.21.100.3-jammy1"), Some((1, 21)));
+        assert_eq!(release("1:8.2.0-11.el9"), Some((8, 2)));
+        assert_eq!(release("9"), None);
+        assert_eq!(release("latest"), None);
+    }
+
+    #[test]
+    fn docs_urls() {
+        assert_eq!(
+            docs_url("sgx.registration").as_deref(),
+            Some("https://cc-enabling.trustedservices.intel.com/intel-tdx-enabling-guide/02/infrastructure_setup/")
+        );
+        assert_eq!(
+            docs_url("sgx.epc").as_deref(),
+            Some("https://docs.kernel.org/arch/x86/sgx.html")
+        );
+        assert_eq!(docs_url("plugin.example"), None);
+    }
+
+    #[test]
+    fn builtin_ids_are_unique() {
+        let checks = builtin();
+        let mut ids: Vec<&str> = checks.iter().map(|c| c.id()).collect();
+        ids.sort_unstable();
+        let count = ids.len();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+}
@@ -0,0 +1,1342 @@
+//! Checks of the host OS: distro, kernel build and command line, and the
+//! settings that keep TDs running.
+
+use std::path::Path;
+
+use super::guest::{qemu_binary, qemu_tdx_missing};
+use super::{probe_failed, unit_properties};
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::config::Severity;
+use crate::error::TdxHostError;
+use crate::i18n::{tr, tr_args};
+use crate::remediation::Remediation;
+use crate::stack::Requirement;
+use crate::{config, grub};
+
+/// Distros supporting TDX hosts, by their os-release ID, with the
+/// VERSION_ID range of the releases that do.
+const SUPPORTED_DISTROS: [(&str, &str, &str); 3] = [
+    ("ubuntu", "Ubuntu", ">=23.10"),
+    ("rhel", "Red Hat Enterprise Linux", ">=9.2"),
+    ("centos", "CentOS Stream", "=9"),
+];
+
+/// Value of a key of /etc/os-release, unquoted.
+pub(crate) fn os_release_value(os_release: &str, key: &str) -> Option<String> {
+    os_release.lines().find_map(|l| {
+        let value = l.strip_prefix(key)?.strip_prefix('=')?;
+        Some(value.trim().trim_matches(['"', '\'']).to_string())
+    })
+}
+
+/// The built-in and configured distros supporting TDX hosts, by ID, with
+/// their name and VERSION_ID range.
+fn supported_distros(probe: &Probe) -> Vec<(String, String, Requirement)> {
+    let builtin = SUPPORTED_DISTROS.iter().map(|(id, name, range)| {
+        let range = Requirement::try_from(range.to_string()).expect("the distro ranges are valid");
+        (id.to_string(), name.to_string(), range)
+    });
+    let configured = probe.config().distros.iter().map(|d| {
+        let name = d.name.clone().unwrap_or_else(|| d.id.clone());
+        (d.id.clone(), name, d.versions.clone())
+    });
+    builtin.chain(configured).collect()
+}
+
+/// The supported distro a release is, by its ID or, for derivatives, the
+/// first of ID_LIKE listed, with the entry whose range the version is in,
+/// else the first. None for distros not known to support TDX hosts.
+fn check_os<'a>(
+    distros: &'a [(String, String, Requirement)],
+    ids: &[&str],
+    version: &str,
+) -> Option<(&'a str, &'a Requirement, bool)> {
+    ids.iter().find_map(|id| {
+        let entries: Vec<_> = distros.iter().filter(|(d, _, _)| d == id).collect();
+        let (_, name, range) = entries
+            .iter()
+            .find(|(_, _, range)| range.matches(version) == Some(true))
+            .or(entries.first())?;
+        Some((name.as_str(), range, range.matches(version) == Some(true)))
+    })
+}
+
+/// What the host lacks to run TDs, whatever the distro: the kernel options
+/// and QEMU support. Also returns what was inspected.
+fn missing_capabilities(probe: &Probe) -> (Vec<String>, Vec<String>) {
+    let mut evidence = vec![];
+    let mut missing = vec![];
+    let release = probe
+        .read_file("/proc/sys/kernel/osrelease")
+        .map(|r| r.trim().to_string())
+        .unwrap_or_default();
+    match kernel_config(probe, &release) {
+        Some((source, config)) => {
+            evidence.push(format!("kernel config = {}", source));
+            missing.extend(
+                KERNEL_OPTIONS
+                    .into_iter()
+                    .filter(|(option, enabled)| {
+                        !kernel_option(&config, option).is_some_and(|v| enabled.contains(&v))
+                    })
+                    .map(|(option, _)| option.to_string()),
+            );
+        }
+        None => missing.push(format!("kernel {} build configuration", release)),
+    }
+    match qemu_binary(probe) {
+        Some((binary, version)) => {
+            evidence.push(format!("{} --version: {}", binary, version));
+            missing.extend(
+                qemu_tdx_missing(probe, binary)
+                    .into_iter()
+                    .map(|m| format!("QEMU {}", m)),
+            );
+        }
+        None => missing.push("QEMU".to_string()),
+    }
+    (evidence, missing)
+}
+
+/// Major and minor version of the running kernel.
+pub(super) fn kernel_version(probe: &Probe) -> Option<(u32, u32)> {
+    let release = probe.read_file("/proc/sys/kernel/osrelease").ok()?;
+    release_version(release.trim())
+}
+
+/// Major and minor version of a kernel release, e.g. (6, 8) for
+/// "6.8.0-1004-intel".
+fn release_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Kernel options a TDX host needs, with the values that enable them.
+const KERNEL_OPTIONS: [(&str, &[&str]); 5] = [
+    ("CONFIG_INTEL_TDX_HOST", &["y"]),
+    ("CONFIG_KVM_INTEL", &["y", "m"]),
+    ("CONFIG_KVM_INTEL_TDX", &["y"]),
+    ("CONFIG_X86_SGX", &["y"]),
+    ("CONFIG_X86_SGX_KVM", &["y"]),
+];
+
+/// The build configuration of the running kernel, from /boot or
+/// /proc/config.gz, with where it was read from.
+pub(crate) fn kernel_config(probe: &Probe, release: &str) -> Option<(String, String)> {
+    let boot = format!("/boot/config-{}", release);
+    if let Ok(config) = probe.read_file(&boot) {
+        return Some((boot, config));
+    }
+    let config = probe.output("zcat", &["/proc/config.gz"]).ok()?;
+    Some((
+        "/proc/config.gz".to_string(),
+        String::from_utf8_lossy(&config).into_owned(),
+    ))
+}
+
+/// Value of a kernel build option, e.g. "y" for CONFIG_KVM_INTEL_TDX.
+fn kernel_option<'a>(config: &'a str, option: &str) -> Option<&'a str> {
+    config
+        .lines()
+        .find_map(|l| l.strip_prefix(option)?.strip_prefix('='))
+}
+
+/// Kernel parameters that are recommended on every TDX host.
+const RECOMMENDED_KERNEL_PARAMS: [&str; 1] = ["nohibernate"];
+
+/// Value of `param` on the kernel command line, the last one winning. A flag
+/// without a value has an empty one; module parameters match with dashes or
+/// underscores in the module name, like the kernel does.
+pub(super) fn cmdline_param<'a>(cmdline: &'a str, param: &str) -> Option<&'a str> {
+    let normalize = |p: &str| match p.split_once('.') {
+        Some((module, name)) => format!("{}.{}", module.replace('-', "_"), name),
+        None => p.to_string(),
+    };
+    let param = normalize(param);
+    cmdline.split_whitespace().rev().find_map(|p| {
+        let (key, value) = p.split_once('=').unwrap_or((p, ""));
+        (normalize(key) == param).then_some(value)
+    })
+}
+
+/// Kernel parameters missing from the command line, required and
+/// recommended.
+fn missing_kernel_params(probe: &Probe, cmdline: &str) -> (Vec<&'static str>, Vec<&'static str>) {
+    let mut required = vec![];
+    // a built-in kvm_intel has no initstate and takes its parameters from
+    // the command line only, modprobe.d does not apply
+    let builtin =
+        probe.exists("/sys/module/kvm_intel") && !probe.exists("/sys/module/kvm_intel/initstate");
+    if builtin
+        && !matches!(
+            cmdline_param(cmdline, "kvm_intel.tdx"),
+            Some("1" | "Y" | "y")
+        )
+    {
+        required.push("kvm_intel.tdx=1");
+    }
+
+    let recommended = RECOMMENDED_KERNEL_PARAMS
+        .into_iter()
+        .filter(|p| cmdline_param(cmdline, p).is_none())
+        .collect();
+    (required, recommended)
+}
+
+/// UEFI variable holding whether Secure Boot is on.
+const SECURE_BOOT_VAR: &str =
+    "/sys/firmware/efi/efivars/SecureBoot-8be4df61-93ca-11d2-aa0d-00e098032b8c";
+
+/// Whether UEFI Secure Boot is on, by its EFI variable or else mokutil,
+/// with what shows it. Hosts booted by legacy BIOS have it off.
+pub(crate) fn secure_boot(probe: &Probe) -> Option<(bool, String)> {
+    if !probe.exists("/sys/firmware/efi") {
+        return Some((false, "/sys/firmware/efi does not exist".to_string()));
+    }
+    // four bytes of attributes, then the value
+    let var = probe.read_bytes(SECURE_BOOT_VAR).unwrap_or_default();
+    if let Some(&value) = var.get(4) {
+        return Some((value == 1, format!("{} = {}", SECURE_BOOT_VAR, value)));
+    }
+    let output = probe.output("mokutil", &["--sb-state"]).ok()?;
+    let state = String::from_utf8_lossy(&output)
+        .lines()
+        .next()?
+        .trim()
+        .to_string();
+    Some((
+        state.eq_ignore_ascii_case("SecureBoot enabled"),
+        format!("mokutil --sb-state: {}", state),
+    ))
+}
+
+/// Kernel modules of the TDX stack: KVM, the MSR driver and the in-tree
+/// and out-of-tree SGX drivers.
+const STACK_MODULES: [&str; 5] = ["kvm", "kvm_intel", "msr", "intel_sgx", "isgx"];
+
+/// Whether a module is out of tree and whether it is signed, by its taint
+/// when it is loaded, else by modinfo. None for built-in modules and those
+/// not installed.
+fn module_signing(probe: &Probe, module: &str) -> Option<(bool, bool)> {
+    if let Ok(taint) = probe.read_file(&format!("/sys/module/{}/taint", module)) {
+        // O: out-of-tree, E: unsigned
+        return Some((taint.contains('O'), !taint.contains('E')));
+    }
+    let info = probe.output("modinfo", &[module]).ok()?;
+    let info = String::from_utf8_lossy(&info);
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|l| l.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    if field("filename")? == "(builtin)" {
+        return None;
+    }
+    Some((field("intree") != Some("Y"), field("signer").is_some()))
+}
+
+/// Kernel releases of distros that backported TDX host support.
+const KERNEL_BACKPORTS: [&str; 1] = ["6.8.0-*-intel"];
+
+/// The backport pattern, built-in or configured, a kernel release matches.
+pub(crate) fn kernel_backport<'a>(probe: &'a Probe, release: &str) -> Option<&'a str> {
+    KERNEL_BACKPORTS
+        .iter()
+        .copied()
+        .chain(probe.config().kernel_backports.iter().map(String::as_str))
+        .find(|pattern| glob_match(pattern, release))
+}
+
+/// Whether `text` matches `pattern`, in which `*` matches anything.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+pub(super) struct OsDistro;
+
+impl Check for OsDistro {
+    fn id(&self) -> &'static str {
+        "os.distro"
+    }
+
+    fn description(&self) -> String {
+        tr("os-distro-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["cpu.tme"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-distro-action");
+        let os_release = match probe.os_release() {
+            Ok(os_release) => os_release,
+            Err(e) => return probe_failed(action, e),
+        };
+        let value = |key: &str| os_release_value(&os_release, key).unwrap_or_default();
+        let (id, id_like, version) = (value("ID"), value("ID_LIKE"), value("VERSION_ID"));
+        let pretty_name = os_release_value(&os_release, "PRETTY_NAME")
+            .unwrap_or_else(|| format!("{} {}", id, version));
+        let mut evidence = vec![
+            format!("ID={}", id),
+            format!("ID_LIKE={}", id_like),
+            format!("VERSION_ID={}", version),
+            format!("PRETTY_NAME={}", pretty_name),
+        ];
+
+        let distros = supported_distros(probe);
+        let ids: Vec<&str> = std::iter::once(id.as_str())
+            .chain(id_like.split_whitespace())
+            .collect();
+        let distro = check_os(&distros, &ids, &version);
+
+        let mut hints = vec![
+            format!(
+                "\t{}",
+                tr_args("os-distro-hint-current", &[("os", &pretty_name)])
+            ),
+            format!("\t{}", tr("os-distro-hint-supported")),
+        ];
+        for (_, name, range) in &distros {
+            hints.push(format!("\t\t{} {}", name, range));
+        }
+
+        let (state, reason) = match distro {
+            Some((_, _, true)) => (TestState::Ok, String::new()),
+            // whatever the release says, the host may have what TDX needs
+            _ => {
+                let (found, missing) = missing_capabilities(probe);
+                evidence.extend(found);
+                if missing.is_empty() {
+                    hints.push(format!("\t{}", tr("os-distro-hint-capable")));
+                    (TestState::Ok, String::new())
+                } else {
+                    hints.push(format!(
+                        "\t{}",
+                        tr_args(
+                            "os-distro-hint-missing",
+                            &[("missing", &missing.join(", "))]
+                        )
+                    ));
+                    match distro {
+                        Some((name, range, _)) => (
+                            TestState::Fail,
+                            tr_args(
+                                "os-distro-reason-version",
+                                &[
+                                    ("os", &pretty_name),
+                                    ("range", &format!("{} {}", name, range)),
+                                ],
+                            ),
+                        ),
+                        None => (
+                            TestState::Warning,
+                            tr_args("os-distro-reason-unknown", &[("os", &pretty_name)]),
+                        ),
+                    }
+                }
+            }
+        };
+        hints.push(format!("\t{}", tr("os-distro-hint-no-guarantee")));
+
+        CheckResult {
+            action,
+            reason,
+            evidence,
+            state,
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct OsKernel;
+
+impl Check for OsKernel {
+    fn id(&self) -> &'static str {
+        "os.kernel"
+    }
+
+    fn description(&self) -> String {
+        tr("os-kernel-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.distro"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-kernel-action");
+        let path = "/proc/sys/kernel/osrelease";
+        let release = match probe.read_file(path) {
+            Ok(release) => release.trim().to_string(),
+            Err(e) => return probe_failed(action, TdxHostError::io(Path::new(path), e)),
+        };
+        let min = &probe.config().min_kernel_version;
+
+        let backport = kernel_backport(probe, &release);
+        let supported = match (release_version(&release), release_version(min)) {
+            (Some(version), Some(min)) => version >= min,
+            _ => false,
+        };
+
+        let mut evidence = vec![format!("kernel = {}", release)];
+        if let Some(pattern) = backport {
+            evidence.push(format!("backport = {}", pattern));
+        }
+
+        CheckResult {
+            action,
+            reason: tr_args("os-kernel-reason", &[("release", &release), ("min", min)]),
+            evidence,
+            state: if supported || backport.is_some() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            hints: if supported || backport.is_some() {
+                vec![]
+            } else {
+                vec![format!(
+                    "\t{}",
+                    tr_args(
+                        "os-kernel-hint",
+                        &[("min", min), ("path", config::CONFIG_PATH)]
+                    )
+                )]
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct OsKernelConfig;
+
+impl Check for OsKernelConfig {
+    fn id(&self) -> &'static str {
+        "os.kernel_config"
+    }
+
+    fn description(&self) -> String {
+        tr("os-kernel-config-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.kernel"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-kernel-config-action");
+        let path = "/proc/sys/kernel/osrelease";
+        let release = match probe.read_file(path) {
+            Ok(release) => release.trim().to_string(),
+            Err(e) => return probe_failed(action, TdxHostError::io(Path::new(path), e)),
+        };
+        let Some((source, config)) = kernel_config(probe, &release) else {
+            return CheckResult {
+                action,
+                reason: tr_args("os-kernel-config-reason-missing", &[("release", &release)]),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let mut evidence = vec![format!("source = {}", source)];
+        let mut missing = vec![];
+        for (option, enabled) in KERNEL_OPTIONS {
+            let value = kernel_option(&config, option);
+            match value {
+                Some(value) => evidence.push(format!("{}={}", option, value)),
+                None => evidence.push(format!("{} is not set", option)),
+            }
+            if !value.is_some_and(|v| enabled.contains(&v)) {
+                missing.push(option);
+            }
+        }
+
+        CheckResult {
+            action,
+            reason: tr_args(
+                "os-kernel-config-reason",
+                &[("options", &missing.join(", "))],
+            ),
+            evidence,
+            state: if missing.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct OsCmdline;
+
+impl Check for OsCmdline {
+    fn id(&self) -> &'static str {
+        "os.cmdline"
+    }
+
+    fn description(&self) -> String {
+        tr("os-cmdline-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.kernel"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Kvm, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-cmdline-action");
+        let cmdline = match probe.cmdline() {
+            Ok(cmdline) => cmdline,
+            Err(e) => return probe_failed(action, e),
+        };
+        let evidence = vec![format!("/proc/cmdline = {}", cmdline.trim())];
+
+        let (required, recommended) = missing_kernel_params(probe, &cmdline);
+        let (state, reason) = if !required.is_empty() {
+            (
+                TestState::Fail,
+                tr_args(
+                    "os-cmdline-reason-required",
+                    &[("params", &required.join(" "))],
+                ),
+            )
+        } else if !recommended.is_empty() {
+            (
+                TestState::Warning,
+                tr_args(
+                    "os-cmdline-reason-recommended",
+                    &[("params", &recommended.join(" "))],
+                ),
+            )
+        } else {
+            (TestState::Ok, String::new())
+        };
+
+        CheckResult {
+            action,
+            reason,
+            state,
+            evidence,
+            ..Default::default()
+        }
+    }
+
+    fn remediation(&self, probe: &Probe) -> Option<Remediation> {
+        let cmdline = probe.cmdline().ok()?;
+        let (mut params, recommended) = missing_kernel_params(probe, &cmdline);
+        params.extend(recommended);
+        grub::append_kernel_params(probe, &params)
+    }
+}
+
+pub(super) struct OsHibernate;
+
+impl Check for OsHibernate {
+    fn id(&self) -> &'static str {
+        "os.hibernate"
+    }
+
+    fn description(&self) -> String {
+        tr("os-hibernate-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["os.kernel"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-hibernate-action");
+        let cmdline = match probe.cmdline() {
+            Ok(cmdline) => cmdline,
+            Err(e) => return probe_failed(action, e),
+        };
+        let mut evidence = vec![format!("/proc/cmdline = {}", cmdline.trim())];
+
+        // the kernel turns hibernation off for nohibernate and under lockdown
+        let mut enabled = cmdline_param(&cmdline, "nohibernate").is_none();
+        if let Some(mode) = probe.lockdown() {
+            evidence.push(format!("lockdown = {}", mode));
+            enabled = false;
+        }
+        // without CONFIG_HIBERNATION, there is no /sys/power/disk
+        match probe.read_file("/sys/power/disk") {
+            Ok(disk) => {
+                evidence.push(format!("/sys/power/disk = {}", disk.trim()));
+                enabled &= disk.trim() != "[disabled]";
+            }
+            Err(_) => enabled = false,
+        }
+
+        if !enabled {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                ..Default::default()
+            };
+        }
+
+        CheckResult {
+            action,
+            reason: tr("os-hibernate-reason"),
+            evidence,
+            hints: vec![format!("\t{}", tr("os-hibernate-hint"))],
+            ..Default::default()
+        }
+    }
+
+    fn remediation(&self, probe: &Probe) -> Option<Remediation> {
+        grub::append_kernel_params(probe, &["nohibernate"])
+    }
+}
+
+pub(super) struct OsKexec;
+
+impl Check for OsKexec {
+    fn id(&self) -> &'static str {
+        "os.kexec"
+    }
+
+    fn description(&self) -> String {
+        tr("os-kexec-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-kexec-action");
+        let cmdline = match probe.cmdline() {
+            Ok(cmdline) => cmdline,
+            Err(e) => return probe_failed(action, e),
+        };
+
+        let mut evidence = vec![];
+        if let Some(size) = cmdline_param(&cmdline, "crashkernel") {
+            evidence.push(format!("crashkernel={}", size));
+        }
+        for path in ["/sys/kernel/kexec_loaded", "/sys/kernel/kexec_crash_loaded"] {
+            if let Ok(loaded) = probe.read_file(path) {
+                if loaded.trim() == "1" {
+                    evidence.push(format!("{} = 1", path));
+                }
+            }
+        }
+
+        if evidence.is_empty() {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+
+        CheckResult {
+            action,
+            reason: tr("os-kexec-reason"),
+            state: match probe.config().kexec_severity {
+                Severity::Info => TestState::Ok,
+                Severity::Warning => TestState::Warning,
+                Severity::Fail => TestState::Fail,
+            },
+            evidence,
+            hints: vec![
+                format!("\t{}", tr("os-kexec-hint-dump")),
+                format!("\t{}", tr("os-kexec-hint-module")),
+                format!(
+                    "\t{}",
+                    tr_args("os-kexec-hint-severity", &[("path", config::CONFIG_PATH)])
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct OsSecureBoot;
+
+impl Check for OsSecureBoot {
+    fn id(&self) -> &'static str {
+        "os.secure_boot"
+    }
+
+    fn description(&self) -> String {
+        tr("os-secure-boot-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-secure-boot-action");
+        let Some((enabled, source)) = secure_boot(probe) else {
+            return CheckResult {
+                action,
+                reason: tr("os-secure-boot-reason-unknown"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let mut evidence = vec![source];
+        let mut out_of_tree = vec![];
+        let mut unsigned = vec![];
+        for module in STACK_MODULES {
+            let Some((external, signed)) = module_signing(probe, module) else {
+                continue;
+            };
+            evidence.push(format!(
+                "{}: {}, {}",
+                module,
+                if external { "out-of-tree" } else { "in-tree" },
+                if signed { "signed" } else { "unsigned" }
+            ));
+            if external {
+                out_of_tree.push(module);
+            }
+            if !signed {
+                unsigned.push(module);
+            }
+        }
+        let lockdown = probe.lockdown();
+        if let Some(mode) = &lockdown {
+            evidence.push(format!("lockdown = {}", mode));
+        }
+
+        if !enabled {
+            return CheckResult {
+                action,
+                evidence,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+
+        let mut hints = vec![];
+        if !out_of_tree.is_empty() {
+            hints.push(format!(
+                "\t{}",
+                tr_args(
+                    "os-secure-boot-hint-mok",
+                    &[("modules", &out_of_tree.join(", "))]
+                )
+            ));
+        }
+        if let Some(mode) = &lockdown {
+            hints.push(format!(
+                "\t{}",
+                tr_args("os-secure-boot-hint-lockdown", &[("mode", mode)])
+            ));
+        }
+        CheckResult {
+            action,
+            reason: tr_args(
+                "os-secure-boot-reason-unsigned",
+                &[("modules", &unsigned.join(", "))],
+            ),
+            evidence,
+            state: if unsigned.is_empty() {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+/// Device nodes QEMU opens to run TDs and give guests SGX.
+const VMM_DEVICES: [&str; 2] = ["/dev/kvm", "/dev/sgx_vepc"];
+
+/// SELinux types of device nodes no policy rule covers, which confined
+/// QEMU (svirt_t) is denied.
+const SELINUX_UNLABELED: [&str; 2] = ["device_t", "unlabeled_t"];
+
+/// The AppArmor abstraction libvirt confines QEMU with, and its local
+/// additions.
+const APPARMOR_LIBVIRT_QEMU: [&str; 2] = [
+    "/etc/apparmor.d/abstractions/libvirt-qemu",
+    "/etc/apparmor.d/local/abstractions/libvirt-qemu",
+];
+
+const AUDIT_LOG: &str = "/var/log/audit/audit.log";
+
+pub(super) struct OsMacPolicy;
+
+impl Check for OsMacPolicy {
+    fn id(&self) -> &'static str {
+        "os.mac_policy"
+    }
+
+    fn description(&self) -> String {
+        tr("os-mac-policy-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Guest]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-mac-policy-action");
+        let devices: Vec<&str> = VMM_DEVICES
+            .into_iter()
+            .filter(|d| probe.exists(d))
+            .collect();
+        let mut evidence = vec![];
+        let mut denied = vec![];
+        // devices QEMU was denied before, which may have been fixed since
+        let mut logged = vec![];
+        let mut hints = vec![];
+
+        // 1 enforcing, 0 permissive
+        if let Ok(enforce) = probe.read_file("/sys/fs/selinux/enforce") {
+            let enforcing = enforce.trim() == "1";
+            evidence.push(format!(
+                "SELinux = {}",
+                if enforcing { "enforcing" } else { "permissive" }
+            ));
+            let mut mislabeled = vec![];
+            for device in &devices {
+                let Ok(context) = probe.output("stat", &["-c", "%C", device]) else {
+                    continue;
+                };
+                let context = String::from_utf8_lossy(&context).trim().to_string();
+                // user:role:type:level
+                let type_ = context.split(':').nth(2).unwrap_or_default();
+                if SELINUX_UNLABELED.contains(&type_) {
+                    mislabeled.push(*device);
+                }
+                evidence.push(format!("{}: {}", device, context));
+            }
+            // QEMU's denials on the device nodes, e.g. "avc:  denied  { read
+            // write } for pid=1234 comm="qemu-kvm" name="kvm" ..."
+            if let Ok(log) = probe.read_file(AUDIT_LOG) {
+                for device in &devices {
+                    let name = format!("name=\"{}\"", device.trim_start_matches("/dev/"));
+                    let denials = log
+                        .lines()
+                        .filter(|l| {
+                            l.contains("avc:  denied")
+                                && l.contains("comm=\"qemu")
+                                && l.contains(&name)
+                        })
+                        .count();
+                    if denials > 0 {
+                        evidence.push(format!("{}: {} denials of {}", AUDIT_LOG, denials, device));
+                        logged.push(*device);
+                    }
+                }
+            }
+            if enforcing && !mislabeled.is_empty() {
+                hints.push(format!(
+                    "\t{}",
+                    tr_args(
+                        "os-mac-policy-hint-restorecon",
+                        &[("devices", &mislabeled.join(" "))]
+                    )
+                ));
+                denied.extend(mislabeled);
+            }
+        }
+
+        if probe
+            .read_file("/sys/module/apparmor/parameters/enabled")
+            .is_ok_and(|enabled| enabled.trim() == "Y")
+        {
+            evidence.push("AppArmor = enabled".to_string());
+            let rules: String = APPARMOR_LIBVIRT_QEMU
+                .iter()
+                .filter_map(|path| probe.read_file(path).ok())
+                .collect::<Vec<_>>()
+                .join("\n");
+            // without libvirt, nothing confines QEMU
+            if !rules.is_empty() {
+                // e.g. "  /dev/kvm rw,"
+                let missing: Vec<&str> = devices
+                    .iter()
+                    .copied()
+                    .filter(|d| {
+                        !rules.lines().any(|l| {
+                            l.trim_start()
+                                .strip_prefix(d)
+                                .is_some_and(|rest| rest.trim_start().starts_with("rw"))
+                        })
+                    })
+                    .collect();
+                for device in &missing {
+                    evidence.push(format!(
+                        "{}: no {} rw rule",
+                        APPARMOR_LIBVIRT_QEMU[0], device
+                    ));
+                }
+                if !missing.is_empty() {
+                    hints.push(format!(
+                        "\t{}",
+                        tr_args(
+                            "os-mac-policy-hint-apparmor",
+                            &[
+                                ("rule", &format!("{} rw,", missing[0])),
+                                ("path", APPARMOR_LIBVIRT_QEMU[1]),
+                            ]
+                        )
+                    ));
+                }
+                denied.extend(missing);
+            }
+        }
+
+        if !logged.is_empty() {
+            hints.push(format!("\t{}", tr("os-mac-policy-hint-ausearch")));
+        }
+        let (state, reason) = if !denied.is_empty() {
+            (
+                TestState::Fail,
+                tr_args("os-mac-policy-reason", &[("devices", &denied.join(", "))]),
+            )
+        } else if !logged.is_empty() {
+            (
+                TestState::Warning,
+                tr_args(
+                    "os-mac-policy-reason-logged",
+                    &[("devices", &logged.join(", "))],
+                ),
+            )
+        } else {
+            (TestState::Ok, String::new())
+        };
+        CheckResult {
+            action,
+            reason,
+            evidence,
+            state,
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+/// Highest vm.swappiness at which the kernel leaves VM memory in RAM
+/// unless it runs short.
+const MAX_SWAPPINESS: u64 = 10;
+
+/// libvirt's QEMU daemons, modular and monolithic, whose limits their QEMU
+/// processes start with.
+const LIBVIRT_DAEMONS: [&str; 2] = ["virtqemud.service", "libvirtd.service"];
+
+/// Value of a sysctl, e.g. "vm.swappiness".
+fn sysctl(probe: &Probe, name: &str) -> Option<String> {
+    let path = format!("/proc/sys/{}", name.replace('.', "/"));
+    probe.read_file(&path).ok().map(|v| v.trim().to_string())
+}
+
+pub(super) struct OsSwap;
+
+impl Check for OsSwap {
+    fn id(&self) -> &'static str {
+        "os.swap"
+    }
+
+    fn description(&self) -> String {
+        tr("os-swap-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Guest]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-swap-action");
+        let path = "/proc/swaps";
+        let swaps = match probe.read_file(path) {
+            Ok(swaps) => swaps,
+            Err(e) => return probe_failed(action, TdxHostError::io(Path::new(path), e)),
+        };
+        // "Filename Type Size Used Priority", sizes in KiB
+        let mut evidence: Vec<String> = swaps.lines().skip(1).map(str::to_string).collect();
+        let kib: u64 = swaps
+            .lines()
+            .skip(1)
+            .filter_map(|l| l.split_whitespace().nth(2)?.parse::<u64>().ok())
+            .sum();
+        let swappiness = sysctl(probe, "vm.swappiness").and_then(|v| v.parse::<u64>().ok());
+        if let Some(swappiness) = swappiness {
+            evidence.push(format!("vm.swappiness = {}", swappiness));
+        }
+
+        if kib == 0 || swappiness.is_some_and(|s| s <= MAX_SWAPPINESS) {
+            return CheckResult {
+                action,
+                evidence,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+        CheckResult {
+            action,
+            reason: tr_args(
+                "os-swap-reason",
+                &[
+                    ("mib", &(kib >> 10).to_string()),
+                    (
+                        "swappiness",
+                        &swappiness.map_or_else(|| tr("module-unknown"), |s| s.to_string()),
+                    ),
+                ],
+            ),
+            evidence,
+            state: TestState::Warning,
+            hints: vec![
+                format!("\t{}", tr("os-swap-hint-private")),
+                format!(
+                    "\t{}",
+                    tr_args("os-swap-hint", &[("max", &MAX_SWAPPINESS.to_string())])
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct OsOvercommit;
+
+impl Check for OsOvercommit {
+    fn id(&self) -> &'static str {
+        "os.overcommit"
+    }
+
+    fn description(&self) -> String {
+        tr("os-overcommit-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Guest]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-overcommit-action");
+        let Some(mode) = sysctl(probe, "vm.overcommit_memory") else {
+            return CheckResult {
+                action,
+                reason: tr("os-overcommit-reason-unreadable"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+        let evidence = vec![format!("vm.overcommit_memory = {}", mode)];
+        // 0: heuristic, 1: always, 2: never beyond the commit limit
+        if mode != "1" {
+            return CheckResult {
+                action,
+                evidence,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+        CheckResult {
+            action,
+            reason: tr("os-overcommit-reason"),
+            evidence,
+            state: TestState::Warning,
+            hints: vec![format!("\t{}", tr("os-overcommit-hint"))],
+            ..Default::default()
+        }
+    }
+}
+
+/// "Max locked memory" of /proc/self/limits in bytes, None if unlimited.
+fn parse_memlock_limit(limits: &str) -> Option<Option<u64>> {
+    // "Max locked memory         8388608              8388608              bytes"
+    let line = limits
+        .lines()
+        .find(|l| l.starts_with("Max locked memory"))?;
+    let soft = line
+        .trim_start_matches("Max locked memory")
+        .split_whitespace()
+        .next()?;
+    Some(soft.parse().ok())
+}
+
+pub(super) struct OsMemlock;
+
+impl Check for OsMemlock {
+    fn id(&self) -> &'static str {
+        "os.memlock"
+    }
+
+    fn description(&self) -> String {
+        tr("os-memlock-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Os, Tag::Guest]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("os-memlock-action");
+        let Some(td_mib) = probe.config().td_memory_mib else {
+            return CheckResult {
+                action,
+                reason: tr_args(
+                    "os-memlock-reason-no-size",
+                    &[("path", config::CONFIG_PATH)],
+                ),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        // libvirt's QEMU inherits the daemon's limit, QEMU run by hand the
+        // one of the session
+        let daemon = LIBVIRT_DAEMONS.iter().find_map(|unit| {
+            let properties = unit_properties(probe, unit, "LoadState,LimitMEMLOCK").ok()?;
+            let property = |name: &str| {
+                properties
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.clone())
+            };
+            (property("LoadState")? == "loaded").then_some(())?;
+            Some((unit, property("LimitMEMLOCK")?))
+        });
+        let (source, bytes) = match daemon {
+            // bytes, or "infinity"
+            Some((unit, limit)) => (
+                format!("{} LimitMEMLOCK", unit),
+                Some(limit.parse::<u64>().ok()),
+            ),
+            None => (
+                "/proc/self/limits".to_string(),
+                probe
+                    .read_file("/proc/self/limits")
+                    .ok()
+                    .and_then(|limits| parse_memlock_limit(&limits)),
+            ),
+        };
+        let Some(bytes) = bytes else {
+            return CheckResult {
+                action,
+                reason: tr_args("os-memlock-reason-unreadable", &[("source", &source)]),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let limit = bytes.map_or_else(|| "unlimited".to_string(), |b| format!("{} MiB", b >> 20));
+        let evidence = vec![
+            format!("{} = {}", source, limit),
+            format!("td_memory_mib = {}", td_mib),
+        ];
+        if bytes.is_none_or(|b| b >> 20 >= td_mib) {
+            return CheckResult {
+                action,
+                evidence,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+        CheckResult {
+            action,
+            reason: tr_args(
+                "os-memlock-reason",
+                &[
+                    ("limit", &limit),
+                    ("source", &source),
+                    ("size", &td_mib.to_string()),
+                ],
+            ),
+            evidence,
+            state: TestState::Warning,
+            hints: vec![format!("\t{}", tr("os-memlock-hint"))],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("6.8.0-*-intel", "6.8.0-1004-intel"));
+        assert!(!glob_match("6.8.0-*-intel", "6.8.0-1004-generic"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxc"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn cmdline_params() {
+        let cmdline = "BOOT_IMAGE=/vmlinuz ro kvm-intel.tdx=1 nohibernate kvm_intel.tdx=0\n";
+        assert_eq!(cmdline_param(cmdline, "nohibernate"), Some(""));
+        assert_eq!(cmdline_param(cmdline, "kvm_intel.tdx"), Some("0"));
+        assert_eq!(cmdline_param(cmdline, "kvm-intel.tdx"), Some("0"));
+        assert_eq!(cmdline_param(cmdline, "ro"), Some(""));
+        assert_eq!(cmdline_param(cmdline, "tdx"), None);
+    }
+
+    #[test]
+    fn kernel_releases() {
+        assert_eq!(release_version("6.8.0-1004-intel"), Some((6, 8)));
+        assert_eq!(release_version("6.16.3"), Some((6, 16)));
+        assert_eq!(release_version("6"), None);
+    }
+
+    #[test]
+    fn os_release_values() {
+        let os_release = "NAME=\"Ubuntu\"\nID=ubuntu\nID_LIKE=debian\nVERSION_ID='24.04'\n";
+        assert_eq!(
+            os_release_value(os_release, "ID").as_deref(),
+            Some("ubuntu")
+        );
+        assert_eq!(
+            os_release_value(os_release, "VERSION_ID").as_deref(),
+            Some("24.04")
+        );
+        assert_eq!(os_release_value(os_release, "VERSION"), None);
+    }
+
+    #[test]
+    fn supported_os() {
+        let distros: Vec<_> = [
+            ("ubuntu", "Ubuntu", ">=23.10"),
+            ("rhel", "Red Hat Enterprise Linux", ">=9.2"),
+        ]
+        .into_iter()
+        .map(|(id, name, range)| {
+            let range = Requirement::try_from(range.to_string()).unwrap();
+            (id.to_string(), name.to_string(), range)
+        })
+        .collect();
+
+        let (name, _, supported) = check_os(&distros, &["ubuntu"], "24.04").unwrap();
+        assert_eq!((name, supported), ("Ubuntu", true));
+        let (name, _, supported) = check_os(&distros, &["ubuntu"], "22.04").unwrap();
+        assert_eq!((name, supported), ("Ubuntu", false));
+        // derivatives by ID_LIKE
+        let (name, _, supported) = check_os(&distros, &["rocky", "rhel"], "9.4").unwrap();
+        assert_eq!((name, supported), ("Red Hat Enterprise Linux", true));
+        assert!(check_os(&distros, &["arch"], "rolling").is_none());
+    }
+
+    #[test]
+    fn memlock_limits() {
+        let limits = "Limit                     Soft Limit           Hard Limit           Units\n\
+                      Max locked memory         8388608              8388608              bytes\n";
+        assert_eq!(parse_memlock_limit(limits), Some(Some(8388608)));
+        let unlimited =
+            "Max locked memory         unlimited            unlimited            bytes\n";
+        assert_eq!(parse_memlock_limit(unlimited), Some(None));
+        assert_eq!(
+            parse_memlock_limit("Max open files 1024 4096 files\n"),
+            None
+        );
+    }
+
+    fn hibernate(files: &[(&str, &str)]) -> TestState {
+        let fixture = Fixture {
+            files: files
+                .iter()
+                .map(|(path, contents)| (path.to_string(), contents.to_string()))
+                .collect(),
+            ..Default::default()
+        };
+        OsHibernate.execute(&Probe::new(fixture)).state
+    }
+
+    #[test]
+    fn hibernation() {
+        let disk = ("/sys/power/disk", "[platform] shutdown reboot\n");
+        assert_eq!(
+            hibernate(&[("/proc/cmdline", "ro quiet\n"), disk]),
+            TestState::Fail
+        );
+        assert_eq!(
+            hibernate(&[("/proc/cmdline", "ro nohibernate\n"), disk]),
+            TestState::Ok
+        );
+        assert_eq!(
+            hibernate(&[
+                ("/proc/cmdline", "ro quiet\n"),
+                disk,
+                (
+                    "/sys/kernel/security/lockdown",
+                    "none [integrity] confidentiality\n"
+                ),
+            ]),
+            TestState::Ok
+        );
+        // built without CONFIG_HIBERNATION
+        assert_eq!(hibernate(&[("/proc/cmdline", "ro quiet\n")]), TestState::Ok);
+    }
+}
@@ -0,0 +1,349 @@
+//! Checks of SGX, which the TDX quoting enclave runs on.
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::i18n::{tr, tr_args};
+
+use super::os::kernel_version;
+use super::{probe_failed, unit_properties};
+
+/// EPC sections enumerated in CPUID leaf 0x12 from subleaf 2 on, as base
+/// and size.
+fn epc_sections(probe: &Probe) -> Vec<(u64, u64)> {
+    // CPUID.07H:EBX[2] enumerates SGX
+    if probe.cpuid(0x0000_0000).eax < 0x12 || probe.cpuid(0x0000_0007).ebx & (1 << 2) == 0 {
+        return vec![];
+    }
+
+    let mut sections = vec![];
+    // bounded, the list end may be missing on broken hypervisors
+    for subleaf in 2..66 {
+        let res = probe.cpuid_count(0x12, subleaf);
+        // type 0 ends the list, 1 is an EPC section
+        if res.eax & 0xf != 1 {
+            break;
+        }
+        let base = (res.eax & 0xffff_f000) as u64 | ((res.ebx & 0xf_ffff) as u64) << 32;
+        let size = (res.ecx & 0xffff_f000) as u64 | ((res.edx & 0xf_ffff) as u64) << 32;
+        sections.push((base, size));
+    }
+    sections
+}
+
+/// Out-of-tree SGX drivers, by module name, superseded by the driver in
+/// Linux 5.11 and later.
+const LEGACY_SGX_DRIVERS: [(&str, &str); 2] = [("isgx", "isgx"), ("intel_sgx", "DCAP")];
+
+const MPA_SERVICE: &str = "mpa_registration_tool.service";
+
+const MPA_LOG: &str = "/var/log/mpa_registration.log";
+
+/// Whether a line the MP registration agent wrote reports a registered
+/// platform.
+fn mpa_registered(line: &str) -> bool {
+    let line = line.to_lowercase();
+    (line.contains("completed") && !line.contains("not completed"))
+        || line.contains("nothing to do")
+}
+
+/// Whether the MP registration agent registered the platform, judging by
+/// the status it cached in UEFI variables, then its log.
+fn mpa_registration(probe: &Probe, evidence: &mut Vec<String>) -> bool {
+    let status = probe
+        .output("mpa_manage", &["-get_registration_status"])
+        .map(|o| String::from_utf8_lossy(&o).trim().to_string());
+    if let Ok(status) = &status {
+        evidence.push(format!("mpa_manage: {}", status));
+    }
+    let log_line = probe.read_file(MPA_LOG).ok().and_then(|log| {
+        log.lines()
+            .rev()
+            .find(|l| l.to_lowercase().contains("registration"))
+            .map(str::to_owned)
+    });
+    if let Some(line) = &log_line {
+        evidence.push(format!("{}: {}", MPA_LOG, line));
+    }
+
+    status.as_deref().is_ok_and(mpa_registered) || log_line.as_deref().is_some_and(mpa_registered)
+}
+
+/// The platform manifest BIOS publishes for multi-package registration.
+const PLATFORM_MANIFEST: &str =
+    "/sys/firmware/efi/efivars/SgxRegistrationServerRequest-304e0796-d515-4698-ac6e-e76cb1a71c28";
+
+pub(super) struct SgxEpc;
+
+impl Check for SgxEpc {
+    fn id(&self) -> &'static str {
+        "sgx.epc"
+    }
+
+    fn description(&self) -> String {
+        tr("sgx-epc-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.sgx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Sgx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let sections = epc_sections(probe);
+        let total_mib = sections.iter().map(|(_, size)| size).sum::<u64>() >> 20;
+        let min_mib = probe.config().min_epc_mib;
+
+        let (state, reason) = if sections.is_empty() {
+            (TestState::Fail, tr("sgx-epc-reason-missing"))
+        } else if total_mib < min_mib {
+            (
+                TestState::Warning,
+                tr_args(
+                    "sgx-epc-reason-small",
+                    &[
+                        ("size", &total_mib.to_string()),
+                        ("min", &min_mib.to_string()),
+                    ],
+                ),
+            )
+        } else {
+            (TestState::Ok, String::new())
+        };
+
+        let mut evidence: Vec<String> = sections
+            .iter()
+            .map(|(base, size)| {
+                format!("EPC section base = {:#x}, size = {} MiB", base, size >> 20)
+            })
+            .collect();
+        evidence.push(format!("EPC total = {} MiB", total_mib));
+
+        CheckResult {
+            action: tr("sgx-epc-action"),
+            reason,
+            evidence,
+            state,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct SgxDriver;
+
+impl Check for SgxDriver {
+    fn id(&self) -> &'static str {
+        "sgx.driver"
+    }
+
+    fn description(&self) -> String {
+        tr("sgx-driver-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.sgx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Sgx, Tag::Os]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("sgx-driver-action");
+        let modules = match probe.modules() {
+            Ok(modules) => modules,
+            Err(e) => return probe_failed(action, e),
+        };
+        let legacy = LEGACY_SGX_DRIVERS.iter().find(|(module, _)| {
+            modules
+                .lines()
+                .any(|l| l.split_whitespace().next() == Some(*module))
+        });
+        let in_kernel = probe.exists("/dev/sgx_enclave");
+        let version = kernel_version(probe);
+        let kernel_has_driver = version.is_some_and(|v| v >= (5, 11));
+
+        let mut evidence = vec![format!("/dev/sgx_enclave exists = {}", in_kernel)];
+        if let Some((module, _)) = legacy {
+            evidence.push(format!("module {} is loaded", module));
+        }
+        if let Some((major, minor)) = version {
+            evidence.push(format!("kernel = {}.{}", major, minor));
+        }
+
+        let mut hints = vec![];
+        let (state, reason) = match legacy {
+            Some((module, flavor)) if kernel_has_driver => {
+                hints.push(format!(
+                    "\t{}",
+                    tr_args("sgx-driver-hint-remove", &[("module", module)])
+                ));
+                (
+                    TestState::Fail,
+                    tr_args("sgx-driver-reason-legacy", &[("driver", flavor)]),
+                )
+            }
+            Some(_) => (TestState::Ok, String::new()),
+            None if in_kernel => (TestState::Ok, String::new()),
+            None => (TestState::Fail, tr("sgx-driver-reason-missing")),
+        };
+
+        CheckResult {
+            action,
+            reason,
+            evidence,
+            state,
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct SgxRegistration;
+
+impl Check for SgxRegistration {
+    fn id(&self) -> &'static str {
+        "sgx.registration"
+    }
+
+    fn description(&self) -> String {
+        tr("sgx-registration-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Sgx, Tag::Attestation]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("sgx-registration-action");
+        let unit = match unit_properties(probe, MPA_SERVICE, "LoadState,ActiveState,Result") {
+            Ok(unit) => unit,
+            Err(e) => return probe_failed(action, e),
+        };
+        let mut evidence: Vec<String> = unit
+            .iter()
+            .map(|(k, v)| format!("{} {}={}", MPA_SERVICE, k, v))
+            .collect();
+        let property = |name: &str| {
+            unit.iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.as_str())
+        };
+
+        if property("LoadState") == Some("not-found") {
+            return CheckResult {
+                action,
+                reason: tr("sgx-registration-reason-missing"),
+                evidence,
+                hints: vec![format!("\t{}", tr("sgx-registration-hint-install"))],
+                state: TestState::Fail,
+                ..Default::default()
+            };
+        }
+
+        let registered = mpa_registration(probe, &mut evidence);
+        let reason = if property("Result").is_some_and(|r| r != "success") {
+            tr("sgx-registration-reason-failed")
+        } else {
+            tr("sgx-registration-reason-pending")
+        };
+
+        CheckResult {
+            action,
+            reason,
+            evidence,
+            state: if registered {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            hints: if registered {
+                vec![]
+            } else {
+                vec![format!(
+                    "\t{}",
+                    tr_args("sgx-registration-hint-log", &[("path", MPA_LOG)])
+                )]
+            },
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct SgxPlatformManifest;
+
+impl Check for SgxPlatformManifest {
+    fn id(&self) -> &'static str {
+        "sgx.platform_manifest"
+    }
+
+    fn description(&self) -> String {
+        tr("sgx-platform-manifest-name")
+    }
+
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Sgx, Tag::Attestation]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("sgx-platform-manifest-action");
+        // without topology information, assume a single package
+        let packages = probe.packages().map_or(1, |p| p.len());
+        let mut evidence = vec![format!("packages = {}", packages)];
+        // single package platforms register without a manifest
+        if packages < 2 {
+            return CheckResult {
+                action,
+                evidence,
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+
+        let mut hints = vec![];
+        let state = if probe.exists(PLATFORM_MANIFEST) {
+            evidence.push(format!("{} exists", PLATFORM_MANIFEST));
+            if let Ok(age) = probe
+                .modified(PLATFORM_MANIFEST)
+                .map(|m| m.elapsed().unwrap_or_default())
+            {
+                let days = (age.as_secs() / 86400).to_string();
+                evidence.push(format!("manifest age = {} days", days));
+                hints.push(format!(
+                    "\t{}",
+                    tr_args(
+                        "sgx-platform-manifest-hint-found",
+                        &[("path", PLATFORM_MANIFEST), ("days", &days)],
+                    )
+                ));
+            }
+            TestState::Ok
+        } else if mpa_registration(probe, &mut evidence) {
+            // registered directly, the manifest was consumed
+            TestState::Ok
+        } else {
+            TestState::Fail
+        };
+
+        CheckResult {
+            action,
+            reason: tr_args(
+                "sgx-platform-manifest-reason",
+                &[("path", PLATFORM_MANIFEST)],
+            ),
+            evidence,
+            state,
+            hints,
+            ..Default::default()
+        }
+    }
+}
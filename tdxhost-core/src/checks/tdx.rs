@@ -0,0 +1,732 @@
+//! Checks of the TDX module the BIOS loads and the kernel initializes.
+
+use std::path::Path;
+
+use super::probe_failed;
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::error::{Result, TdxHostError};
+use crate::i18n::{tr, tr_args};
+use crate::{config, module_update};
+
+/// Where kernels with the TDX host sysfs interface export the module's
+/// state and version.
+const TDX_MODULE_SYSFS: &str = "/sys/firmware/tdx/tdx_module";
+
+/// The module state, e.g. "initialized".
+const TDX_MODULE_STATUS: &str = "/sys/firmware/tdx/tdx_module/status";
+
+/// Returns what shows the module is initialized, if it is.
+///
+/// The module state in sysfs is preferred. KVM turns its tdx parameter off
+/// when the module fails to initialize, so a set one shows the module is
+/// up. Otherwise the kernel log is searched for the line announcing the
+/// initialized module; on long running hosts it may have left the ring
+/// buffer, so the kernel messages of the current boot in the journal are
+/// searched too.
+pub(crate) fn check_tdx_module(probe: &Probe) -> Result<Option<String>> {
+    if let Ok(status) = probe.read_file(TDX_MODULE_STATUS) {
+        let status = status.trim();
+        return Ok((status == "initialized").then(|| format!("{} = {}", TDX_MODULE_STATUS, status)));
+    }
+    let param = "/sys/module/kvm_intel/parameters/tdx";
+    if let Ok(tdx) = probe.read_file(param) {
+        if matches!(tdx.trim(), "Y" | "1") {
+            return Ok(Some(format!("{} = {}", param, tdx.trim())));
+        }
+    }
+
+    let find = |log: &str| {
+        log.lines()
+            .find(|l| l.contains("virt/tdx: module initialized"))
+            .map(str::to_owned)
+    };
+
+    let ring_buffer = probe.kernel_log();
+    if let Ok(Some(line)) = ring_buffer.as_deref().map(find) {
+        return Ok(Some(line));
+    }
+
+    match probe.kernel_journal() {
+        Ok(journal) => Ok(find(&journal).map(|l| format!("journal: {}", l))),
+        Err(_) => ring_buffer.map(|_| None),
+    }
+}
+
+/// Causes of TDX module initialization failures, by a fragment of the
+/// `virt/tdx:` kernel log line reporting them, and the message explaining
+/// each, the most specific first.
+const TDX_INIT_FAILURES: [(&str, &str); 8] = [
+    ("not TDX convertible memory", "cmr"),
+    ("CMR", "cmr"),
+    ("hot-add", "hotplug"),
+    ("private KeyIDs", "keyids"),
+    ("Hibernation", "hibernate"),
+    ("ACPI S3", "s3"),
+    ("not loaded", "not-loaded"),
+    ("SEAMCALL", "seamcall"),
+];
+
+/// The `virt/tdx:` kernel log lines of the current boot, from the ring
+/// buffer or else the journal.
+pub(crate) fn tdx_log_lines(probe: &Probe) -> Vec<String> {
+    let find = |log: &str| -> Vec<String> {
+        log.lines()
+            .filter(|l| l.contains("virt/tdx:"))
+            .map(str::to_owned)
+            .collect()
+    };
+
+    match probe.kernel_log().as_deref().map(find) {
+        Ok(lines) if !lines.is_empty() => lines,
+        _ => probe
+            .kernel_journal()
+            .map(|journal| find(&journal))
+            .unwrap_or_default(),
+    }
+}
+
+/// Version of the loaded TDX module.
+pub(crate) struct TdxModuleRelease {
+    pub major: u32,
+    pub minor: u32,
+    pub update: u32,
+    pub build_date: Option<String>,
+    pub build_num: Option<String>,
+    /// Module attributes, e.g. whether it is a debug build
+    pub attributes: Option<String>,
+}
+
+impl TdxModuleRelease {
+    pub fn release(&self) -> (u32, u32, u32) {
+        (self.major, self.minor, self.update)
+    }
+
+    /// From the module attributes the kernel logs while initializing it,
+    /// e.g. "major_version 1, minor_version 5, build_date 20240129,
+    /// build_num 698", or "TDX module 1.5.06.00.0744 (build_date 20240129)".
+    pub fn from_log(line: &str) -> Option<Self> {
+        let (_, message) = line.split_once("virt/tdx:")?;
+        let field = |name: &str| {
+            let mut words = message
+                .split([' ', ',', '(', ')'])
+                .filter(|w| !w.is_empty());
+            words.find(|w| *w == name)?;
+            words.next().map(str::to_owned)
+        };
+
+        if let Some(major) = field("major_version") {
+            return Some(Self {
+                major: major.parse().ok()?,
+                minor: field("minor_version")?.parse().ok()?,
+                update: field("update_version")
+                    .and_then(|u| u.parse().ok())
+                    .unwrap_or(0),
+                build_date: field("build_date"),
+                build_num: field("build_num"),
+                attributes: field("attributes"),
+            });
+        }
+
+        let version = field("module").or_else(|| field("module:"))?;
+        let mut parts = version.split('.').map(str::parse::<u32>);
+        Some(Self {
+            major: parts.next()?.ok()?,
+            minor: parts.next()?.ok()?,
+            update: parts.next().and_then(|u| u.ok()).unwrap_or(0),
+            build_date: field("build_date"),
+            build_num: parts.nth(1).and_then(|b| b.ok()).map(|b| b.to_string()),
+            attributes: field("attributes"),
+        })
+    }
+
+    /// From sysfs, or else the kernel log.
+    pub fn detect(probe: &Probe) -> Option<Self> {
+        Self::from_sysfs(probe)
+            .or_else(|| tdx_log_lines(probe).iter().find_map(|l| Self::from_log(l)))
+    }
+
+    pub fn from_sysfs(probe: &Probe) -> Option<Self> {
+        let field = |name: &str| {
+            probe
+                .read_file(&format!("{}/{}", TDX_MODULE_SYSFS, name))
+                .ok()
+                .map(|v| v.trim().to_owned())
+        };
+        Some(Self {
+            major: field("major_version")?.parse().ok()?,
+            minor: field("minor_version")?.parse().ok()?,
+            update: field("update_version")
+                .and_then(|u| u.parse().ok())
+                .unwrap_or(0),
+            build_date: field("build_date"),
+            build_num: field("build_num"),
+            attributes: field("attributes"),
+        })
+    }
+}
+
+/// TDX_FEATURES0 of the loaded TDX module, the features it was built with.
+pub(crate) fn tdx_features0(probe: &Probe) -> Option<u64> {
+    let hex = |v: &str| u64::from_str_radix(v.trim().trim_start_matches("0x"), 16).ok();
+    if let Ok(features) = probe.read_file(&format!("{}/features0", TDX_MODULE_SYSFS)) {
+        return hex(&features);
+    }
+    tdx_log_lines(probe).iter().find_map(|l| {
+        let mut words = l.split([' ', ',', ':']).filter(|w| !w.is_empty());
+        words.find(|w| w.eq_ignore_ascii_case("features0"))?;
+        hex(words.next()?)
+    })
+}
+
+/// Where kernels with the TDX host sysfs interface export the version of the
+/// NP-SEAMLDR, the loader that installs the TDX module.
+const SEAMLDR_VERSION: &str = "/sys/firmware/tdx/seamldr/version";
+
+/// The NP-SEAMLDR version as reported, and its major, minor and update
+/// numbers.
+pub(crate) fn seamldr_version(probe: &Probe) -> Option<(String, (u32, u32, u32))> {
+    let parse = |version: &str| {
+        let mut parts = version.split('.').map(str::parse::<u32>);
+        Some((
+            parts.next()?.ok()?,
+            parts.next()?.ok()?,
+            parts.next().and_then(|u| u.ok()).unwrap_or(0),
+        ))
+    };
+    if let Ok(version) = probe.read_file(SEAMLDR_VERSION) {
+        let version = version.trim().to_owned();
+        let release = parse(&version)?;
+        return Some((version, release));
+    }
+
+    // e.g. "virt/tdx: NP-SEAMLDR version 1.5.01.00"
+    tdx_log_lines(probe)
+        .iter()
+        .filter(|l| l.contains("SEAMLDR"))
+        .flat_map(|l| l.split([' ', ',', ':', '(', ')']))
+        .find_map(|w| Some((w.to_owned(), parse(w).filter(|_| w.contains('.'))?)))
+}
+
+/// Parse a module version as set in the config, e.g. "1.5" or "1.5.6".
+fn parse_module_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.split('.').map(str::parse::<u32>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let update = parts.next().transpose().ok()?.unwrap_or(0);
+    parts.next().is_none().then_some((major, minor, update))
+}
+
+/// Convertible memory regions the BIOS published to the TDX module, as
+/// start and end, from kernel log lines like "virt/tdx: CMR: [0x100000,
+/// 0x77800000)".
+pub(crate) fn tdx_cmrs(probe: &Probe) -> Vec<(u64, u64)> {
+    let hex = |v: &str| u64::from_str_radix(v.trim().trim_start_matches("0x"), 16).ok();
+    tdx_log_lines(probe)
+        .iter()
+        .filter_map(|l| {
+            let (_, range) = l.split_once("CMR")?.1.split_once('[')?;
+            let (start, end) = range.split_once(')')?.0.split_once(',')?;
+            Some((hex(start)?, hex(end)?))
+        })
+        .collect()
+}
+
+/// System RAM ranges in /proc/iomem, as start and exclusive end. Without
+/// root, the kernel zeroes the addresses.
+fn system_ram(probe: &Probe) -> Result<Vec<(u64, u64)>> {
+    let path = "/proc/iomem";
+    let iomem = probe
+        .read_file(path)
+        .map_err(|e| TdxHostError::io(Path::new(path), e))?;
+    Ok(iomem
+        .lines()
+        // nested resources are indented
+        .filter(|l| !l.starts_with(' '))
+        .filter_map(|l| {
+            let (range, name) = l.split_once(" : ")?;
+            (name.trim() == "System RAM").then_some(())?;
+            let (start, end) = range.split_once('-')?;
+            let start = u64::from_str_radix(start, 16).ok()?;
+            let end = u64::from_str_radix(end, 16).ok()?;
+            Some((start, end + 1))
+        })
+        .collect())
+}
+
+/// Bytes of `ranges` not covered by any of `cover`.
+fn uncovered(ranges: &[(u64, u64)], cover: &[(u64, u64)]) -> u64 {
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            let covered: u64 = cover
+                .iter()
+                .map(|&(c_start, c_end)| c_end.min(end).saturating_sub(c_start.max(start)))
+                .sum();
+            (end - start).saturating_sub(covered)
+        })
+        .sum()
+}
+
+/// Least system RAM outside the CMRs worth warning about; the first
+/// megabyte is usually not convertible.
+const CMR_UNCOVERED_WARN: u64 = 1 << 30;
+
+pub(super) struct TdxModule;
+
+impl Check for TdxModule {
+    fn id(&self) -> &'static str {
+        "tdx.module"
+    }
+
+    fn description(&self) -> String {
+        tr("tdx-module-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let module_initialized = match check_tdx_module(probe) {
+            Ok(line) => line,
+            Err(e) => return probe_failed(tr("tdx-module-action"), e),
+        };
+        if let Some(line) = module_initialized {
+            return CheckResult {
+                action: tr("tdx-module-action"),
+                evidence: vec![line],
+                state: TestState::Ok,
+                ..Default::default()
+            };
+        }
+
+        // classify the errors the kernel logged while initializing the module
+        let evidence = tdx_log_lines(probe);
+        let mut causes = vec![];
+        for line in &evidence {
+            let cause = TDX_INIT_FAILURES
+                .iter()
+                .find(|(fragment, _)| line.contains(fragment))
+                .map(|(_, cause)| *cause);
+            if let Some(cause) = cause.filter(|c| !causes.contains(c)) {
+                causes.push(cause);
+            }
+        }
+
+        let reason = if causes.is_empty() {
+            tr("tdx-module-reason")
+        } else {
+            let causes: Vec<String> = causes
+                .iter()
+                .map(|c| tr(&format!("tdx-module-cause-{}", c)))
+                .collect();
+            tr_args(
+                "tdx-module-reason-failed",
+                &[("causes", &causes.join("; "))],
+            )
+        };
+        CheckResult {
+            action: tr("tdx-module-action"),
+            reason,
+            hints: causes
+                .iter()
+                .map(|c| format!("\t{}", tr(&format!("tdx-module-hint-{}", c))))
+                .collect(),
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct TdxCmr;
+
+impl Check for TdxCmr {
+    fn id(&self) -> &'static str {
+        "tdx.cmr"
+    }
+
+    fn description(&self) -> String {
+        tr("tdx-cmr-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["bios.tdx"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Tdx]
+    }
+
+    fn privileged(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("tdx-cmr-action");
+        let cmrs = tdx_cmrs(probe);
+        if cmrs.is_empty() {
+            return CheckResult {
+                action,
+                reason: tr("tdx-cmr-reason-unknown"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        }
+        let ram = match system_ram(probe) {
+            Ok(ram) => ram,
+            Err(e) => return probe_failed(action, e),
+        };
+
+        let mut evidence: Vec<String> = cmrs
+            .iter()
+            .map(|(start, end)| format!("CMR [{:#x}, {:#x})", start, end))
+            .collect();
+        let ram_mib = ram.iter().map(|(start, end)| end - start).sum::<u64>() >> 20;
+        let cmr_mib = cmrs.iter().map(|(start, end)| end - start).sum::<u64>() >> 20;
+        let uncovered = uncovered(&ram, &cmrs);
+        evidence.push(format!("System RAM = {} MiB", ram_mib));
+        evidence.push(format!("CMR total = {} MiB", cmr_mib));
+        evidence.push(format!("System RAM outside CMRs = {} MiB", uncovered >> 20));
+
+        CheckResult {
+            action,
+            reason: tr_args(
+                "tdx-cmr-reason",
+                &[
+                    ("uncovered", &(uncovered >> 20).to_string()),
+                    ("ram", &ram_mib.to_string()),
+                ],
+            ),
+            state: if uncovered < CMR_UNCOVERED_WARN {
+                TestState::Ok
+            } else {
+                TestState::Warning
+            },
+            evidence,
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct TdxModuleVersion;
+
+impl Check for TdxModuleVersion {
+    fn id(&self) -> &'static str {
+        "tdx.module_version"
+    }
+
+    fn description(&self) -> String {
+        tr("tdx-module-version-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["tdx.module"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Tdx]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("tdx-module-version-action");
+        let version = TdxModuleRelease::detect(probe);
+        let Some(version) = version else {
+            return CheckResult {
+                action,
+                reason: tr("tdx-module-version-reason-unknown"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let (major, minor, update) = version.release();
+        let release = format!("{}.{}.{}", major, minor, update);
+        let mut evidence = vec![format!("version = {}", release)];
+        evidence.extend(
+            version
+                .build_num
+                .iter()
+                .map(|b| format!("build_num = {}", b)),
+        );
+        evidence.extend(
+            version
+                .build_date
+                .iter()
+                .map(|d| format!("build_date = {}", d)),
+        );
+        let hints = vec![format!(
+            "\t{}",
+            tr_args(
+                "tdx-module-version-hint",
+                &[
+                    ("version", &release),
+                    ("build", version.build_num.as_deref().unwrap_or("?")),
+                    ("date", version.build_date.as_deref().unwrap_or("?")),
+                ],
+            )
+        )];
+
+        let Some(min) = &probe.config().min_tdx_module_version else {
+            return CheckResult {
+                action,
+                state: TestState::Ok,
+                evidence,
+                hints,
+                ..Default::default()
+            };
+        };
+        let Some(min_release) = parse_module_version(min) else {
+            return CheckResult {
+                action,
+                reason: tr_args(
+                    "tdx-module-version-reason-invalid",
+                    &[("min", min), ("path", config::CONFIG_PATH)],
+                ),
+                evidence,
+                hints,
+                ..Default::default()
+            };
+        };
+
+        CheckResult {
+            action,
+            reason: tr_args(
+                "tdx-module-version-reason",
+                &[("version", &release), ("min", min)],
+            ),
+            state: if version.release() >= min_release {
+                TestState::Ok
+            } else {
+                TestState::Fail
+            },
+            evidence,
+            hints,
+            ..Default::default()
+        }
+    }
+}
+
+/// An optional TDX module feature, by its bit in TDX_FEATURES0.
+#[derive(Clone, Copy)]
+pub(crate) struct TdxModuleFeature {
+    id: &'static str,
+    pub name: &'static str,
+    pub bit: u32,
+}
+
+pub(crate) const TDX_MODULE_FEATURES: [TdxModuleFeature; 4] = [
+    TdxModuleFeature {
+        id: "tdx.feature.td_migration",
+        name: "TD migration",
+        bit: 0,
+    },
+    TdxModuleFeature {
+        id: "tdx.feature.td_preserving",
+        name: "TD-preserving updates",
+        bit: 1,
+    },
+    TdxModuleFeature {
+        id: "tdx.feature.service_td",
+        name: "service TDs",
+        bit: 2,
+    },
+    TdxModuleFeature {
+        id: "tdx.feature.td_partitioning",
+        name: "TD partitioning",
+        bit: 7,
+    },
+];
+
+impl Check for TdxModuleFeature {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn description(&self) -> String {
+        tr_args("tdx-feature-name", &[("feature", self.name)])
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["tdx.module"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Tdx]
+    }
+
+    /// Informational, the module works without any of them.
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr_args("tdx-feature-action", &[("feature", self.name)]);
+        let Some(features) = tdx_features0(probe) else {
+            return CheckResult {
+                action,
+                reason: tr("tdx-feature-reason-unknown"),
+                state: TestState::Skip,
+                ..Default::default()
+            };
+        };
+
+        let supported = features & (1 << self.bit) != 0;
+        CheckResult {
+            action,
+            reason: tr_args("tdx-feature-reason", &[("feature", self.name)]),
+            // unsupported features are not a problem, just unavailable
+            state: if supported {
+                TestState::Ok
+            } else {
+                TestState::Skip
+            },
+            evidence: vec![format!("TDX_FEATURES0 = {:#x}", features)],
+            ..Default::default()
+        }
+    }
+}
+
+pub(super) struct TdxRuntimeUpdate;
+
+impl Check for TdxRuntimeUpdate {
+    fn id(&self) -> &'static str {
+        "tdx.runtime_update"
+    }
+
+    fn description(&self) -> String {
+        tr("tdx-runtime-update-name")
+    }
+
+    fn dependencies(&self) -> &[&'static str] {
+        &["tdx.module"]
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Tdx]
+    }
+
+    /// Informational, for planning how to patch the module.
+    fn optional(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        let action = tr("tdx-runtime-update-action");
+        let mut evidence = vec![];
+        // TD-preserving updates are bit 1 of TDX_FEATURES0
+        if let Some(features) = tdx_features0(probe) {
+            evidence.push(format!("TDX_FEATURES0 = {:#x}", features));
+            if features & (1 << 1) == 0 {
+                return CheckResult {
+                    action,
+                    reason: tr("tdx-runtime-update-reason-module"),
+                    state: TestState::Skip,
+                    evidence,
+                    ..Default::default()
+                };
+            }
+        }
+        if !probe.exists(module_update::FW_UPLOAD_DIR) {
+            return CheckResult {
+                action,
+                reason: tr("tdx-runtime-update-reason-kernel"),
+                state: TestState::Skip,
+                evidence,
+                ..Default::default()
+            };
+        }
+
+        evidence.push(format!("{} exists", module_update::FW_UPLOAD_DIR));
+        CheckResult {
+            action,
+            state: TestState::Ok,
+            evidence,
+            hints: vec![format!("\t{}", tr("tdx-runtime-update-hint"))],
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    #[test]
+    fn module_release_from_log() {
+        let release = TdxModuleRelease::from_log(
+            "[    8.5] virt/tdx: TDX module: attributes 0x0, vendor_id 0x8086, major_version 1, minor_version 5, build_date 20240129, build_num 698",
+        )
+        .unwrap();
+        assert_eq!(release.release(), (1, 5, 0));
+        assert_eq!(release.build_date.as_deref(), Some("20240129"));
+        assert_eq!(release.build_num.as_deref(), Some("698"));
+        assert_eq!(release.attributes.as_deref(), Some("0x0"));
+
+        let release =
+            TdxModuleRelease::from_log("virt/tdx: TDX module 1.5.06.00.0744 (build_date 20240129)")
+                .unwrap();
+        assert_eq!(release.release(), (1, 5, 6));
+        assert_eq!(release.build_date.as_deref(), Some("20240129"));
+        assert_eq!(release.build_num.as_deref(), Some("744"));
+
+        assert!(TdxModuleRelease::from_log("virt/tdx: module initialized").is_none());
+        assert!(TdxModuleRelease::from_log("kvm: TDX module 1.5.06").is_none());
+    }
+
+    #[test]
+    fn module_versions() {
+        assert_eq!(parse_module_version("1.5"), Some((1, 5, 0)));
+        assert_eq!(parse_module_version("1.5.6"), Some((1, 5, 6)));
+        assert_eq!(parse_module_version("1.5.6.7"), None);
+        assert_eq!(parse_module_version("1.x"), None);
+        assert_eq!(parse_module_version("1"), None);
+    }
+
+    #[test]
+    fn uncovered_memory() {
+        let ram = [(0, 0x1000), (0x10_0000, 0x8000_0000)];
+        assert_eq!(uncovered(&ram, &[(0, 0x8000_0000)]), 0);
+        assert_eq!(uncovered(&ram, &[(0x10_0000, 0x8000_0000)]), 0x1000);
+        assert_eq!(
+            uncovered(&ram, &[(0x10_0000, 0x4000_0000)]),
+            0x1000 + 0x4000_0000
+        );
+        assert_eq!(uncovered(&ram, &[]), 0x1000 + 0x8000_0000 - 0x10_0000);
+    }
+
+    fn module_state(fixture: Fixture) -> TestState {
+        TdxModule.execute(&Probe::new(fixture)).state
+    }
+
+    #[test]
+    fn module_initialized() {
+        let sysfs = |status: &str| Fixture {
+            files: [(TDX_MODULE_STATUS.to_string(), status.to_string())].into(),
+            ..Default::default()
+        };
+        assert_eq!(module_state(sysfs("initialized\n")), TestState::Ok);
+        assert_eq!(module_state(sysfs("uninitialized\n")), TestState::Fail);
+
+        let log = |log: &str| Fixture {
+            kernel_log: Some(log.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            module_state(log("[    9.1] virt/tdx: module initialized\n")),
+            TestState::Ok
+        );
+        assert_eq!(
+            module_state(log("[    9.1] virt/tdx: initialization failed (-5)\n")),
+            TestState::Fail
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::bios_tool::BiosTool;
+use crate::error::{Result, TdxHostError};
+use crate::stack::Requirement;
+
+pub const CONFIG_PATH: &str = "/etc/tdxhost/config.toml";
+
+/// Site settings, read from `CONFIG_PATH`.
+///
+/// ```toml
+/// # leave loading kernel modules to the administrator
+/// load_msr_module = false
+/// # warn unless TME encrypts memory with this algorithm
+/// tme_algorithm = "AES-XTS-256"
+/// # warn when there is less EPC
+/// min_epc_mib = 256
+/// # a pre-production platform
+/// sgx_registration_server = "sbx"
+/// # FMSPC of the platform, to look up its attestation collateral
+/// fmspc = "90c06f000000"
+/// # distro kernels with TDX host support backported
+/// kernel_backports = ["5.14.0-*.el9.x86_64"]
+/// # fail when kexec or kdump is set up, instead of warning
+/// kexec_severity = "fail"
+/// # oldest TDX module to accept
+/// min_tdx_module_version = "1.5.6"
+/// # warn unless TDX memory integrity is cryptographic
+/// integrity_mode = "cryptographic"
+/// # the user QEMU runs TDs as
+/// vmm_user = "tdvm"
+/// # warn unless the VMM may lock this much memory
+/// td_memory_mib = 16384
+/// # read the BIOS settings the host cannot see with Dell's racadm
+/// bios_tool = "racadm"
+/// # an in-house distro supporting TDX hosts
+/// [[distros]]
+/// id = "acme"
+/// name = "ACME Linux"
+/// versions = ">=4.2"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Run `modprobe msr` when the MSR device nodes are missing
+    pub load_msr_module: bool,
+    /// Expected TME encryption algorithm, e.g. "AES-XTS-128"
+    pub tme_algorithm: Option<String>,
+    /// Least SGX EPC memory, in MiB, for the quoting enclaves
+    pub min_epc_mib: u64,
+    /// Registration server the BIOS should point SGX at
+    pub sgx_registration_server: RegistrationServer,
+    /// Family-Model-Stepping-Platform-CustomSKU of the platform, as in its
+    /// PCK certificate
+    pub fmspc: Option<String>,
+    /// First kernel release with TDX host support, e.g. "6.16"
+    pub min_kernel_version: String,
+    /// Older kernel releases with TDX host support, `*` matches anything
+    pub kernel_backports: Vec<String>,
+    /// How to report kexec and kdump being set up on a TDX host
+    pub kexec_severity: Severity,
+    /// Oldest TDX module release to accept, e.g. "1.5" or "1.5.6"
+    pub min_tdx_module_version: Option<String>,
+    /// Expected TDX memory integrity mode
+    pub integrity_mode: Option<IntegrityMode>,
+    /// Distro releases supporting TDX hosts besides the built-in ones
+    pub distros: Vec<Distro>,
+    /// User QEMU runs TDs as, instead of libvirt's and the one invoking
+    /// tdxhost through sudo
+    pub vmm_user: Option<String>,
+    /// Memory of the largest TD planned, in MiB, which the VMM may have to
+    /// lock
+    pub td_memory_mib: Option<u64>,
+    /// Vendor utility to read the BIOS settings with, unless --redfish
+    /// reads them from the BMC
+    pub bios_tool: Option<BiosTool>,
+}
+
+/// Releases of a distro supporting TDX hosts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Distro {
+    /// ID in /etc/os-release, also matching derivatives listing it in ID_LIKE
+    pub id: String,
+    /// Name shown in hints, the ID if unset
+    pub name: Option<String>,
+    /// Range of VERSION_ID, e.g. ">=4.2"
+    pub versions: Requirement,
+}
+
+/// How TDX protects the integrity of private memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityMode {
+    /// Li, a TD-owner bit per cache line
+    Logical,
+    /// Ci, a MAC per cache line
+    Cryptographic,
+}
+
+impl IntegrityMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegrityMode::Logical => "logical",
+            IntegrityMode::Cryptographic => "cryptographic",
+        }
+    }
+}
+
+/// How an advisory is reported.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Pass, listing the advice
+    Info,
+    #[default]
+    Warning,
+    Fail,
+}
+
+/// SGX registration server, selected in the BIOS.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationServer {
+    /// Production
+    #[default]
+    Liv,
+    /// Pre-production
+    Sbx,
+}
+
+impl RegistrationServer {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegistrationServer::Liv => "LIV",
+            RegistrationServer::Sbx => "SBX",
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            load_msr_module: true,
+            tme_algorithm: None,
+            min_epc_mib: 64,
+            sgx_registration_server: RegistrationServer::default(),
+            fmspc: None,
+            min_kernel_version: "6.16".to_string(),
+            kernel_backports: vec![],
+            kexec_severity: Severity::default(),
+            min_tdx_module_version: None,
+            integrity_mode: None,
+            distros: vec![],
+            vmm_user: None,
+            td_memory_mib: None,
+            bios_tool: None,
+        }
+    }
+}
+
+impl Config {
+    /// The settings in `CONFIG_PATH`, or the defaults if there is none.
+    pub fn load() -> Result<Self> {
+        Self::load_from(Path::new(CONFIG_PATH))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(TdxHostError::io(path, e)),
+        };
+
+        toml::from_str(&contents)
+            .map_err(|e| TdxHostError::io(path, std::io::Error::new(ErrorKind::InvalidData, e)))
+    }
+}
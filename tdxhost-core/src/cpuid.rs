@@ -0,0 +1,267 @@
+//! The CPUID leaves that enumerate TDX, SGX and memory encryption, and their
+//! fields.
+
+use serde::Serialize;
+use std::arch::x86_64::CpuidResult;
+
+use crate::check::Probe;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Register {
+    Eax,
+    Ebx,
+    Ecx,
+    Edx,
+}
+
+impl Register {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Register::Eax => "eax",
+            Register::Ebx => "ebx",
+            Register::Ecx => "ecx",
+            Register::Edx => "edx",
+        }
+    }
+
+    fn of(self, res: &CpuidResult) -> u32 {
+        match self {
+            Register::Eax => res.eax,
+            Register::Ebx => res.ebx,
+            Register::Ecx => res.ecx,
+            Register::Edx => res.edx,
+        }
+    }
+}
+
+use Register::{Eax, Ebx, Ecx, Edx};
+
+/// Fields of a subleaf, each by register, lowest and highest bit.
+type Fields = &'static [(Register, u32, u32, &'static str)];
+
+/// A leaf and the fields of its subleaf.
+pub struct Leaf {
+    pub leaf: u32,
+    pub subleaf: u32,
+    pub name: &'static str,
+    pub fields: Fields,
+}
+
+/// Leaves of the features TDX needs, as defined by the SDM.
+pub const LEAVES: [Leaf; 7] = [
+    Leaf {
+        leaf: 0x0,
+        subleaf: 0,
+        name: "Vendor and maximum leaf",
+        fields: &[(Eax, 0, 31, "Maximum basic leaf")],
+    },
+    Leaf {
+        leaf: 0x1,
+        subleaf: 0,
+        name: "Version and features",
+        fields: &[
+            (Eax, 0, 3, "Stepping"),
+            (Eax, 4, 7, "Model"),
+            (Eax, 8, 11, "Family"),
+            (Eax, 16, 19, "Extended model"),
+            (Eax, 20, 27, "Extended family"),
+            (Ecx, 5, 5, "VMX"),
+            (Ecx, 6, 6, "SMX"),
+        ],
+    },
+    Leaf {
+        leaf: 0x7,
+        subleaf: 0,
+        name: "Structured extended features",
+        fields: &[
+            (Ebx, 2, 2, "SGX"),
+            (Ecx, 13, 13, "TME"),
+            (Ecx, 30, 30, "SGX launch control"),
+            (Edx, 18, 18, "PCONFIG"),
+        ],
+    },
+    Leaf {
+        leaf: 0x12,
+        subleaf: 0,
+        name: "SGX capabilities",
+        fields: &[
+            (Eax, 0, 0, "SGX1"),
+            (Eax, 1, 1, "SGX2"),
+            (Eax, 5, 5, "ENCLV"),
+            (Eax, 6, 6, "ENCLS ETRACKC"),
+            (Ebx, 0, 31, "MISCSELECT"),
+            (Edx, 0, 7, "Max enclave size, 32-bit (log2)"),
+            (Edx, 8, 15, "Max enclave size, 64-bit (log2)"),
+        ],
+    },
+    Leaf {
+        leaf: 0x12,
+        subleaf: 1,
+        name: "SGX enclave attributes",
+        fields: &[
+            (Eax, 0, 31, "ATTRIBUTES[31:0]"),
+            (Ebx, 0, 31, "ATTRIBUTES[63:32]"),
+            (Ecx, 0, 31, "XFRM[31:0]"),
+            (Edx, 0, 31, "XFRM[63:32]"),
+        ],
+    },
+    Leaf {
+        leaf: 0x1b,
+        subleaf: 0,
+        name: "PCONFIG targets",
+        fields: &[
+            (Eax, 0, 11, "Subleaf type"),
+            (Ebx, 0, 31, "Target 1"),
+            (Ecx, 0, 31, "Target 2"),
+            (Edx, 0, 31, "Target 3"),
+        ],
+    },
+    Leaf {
+        leaf: 0x21,
+        subleaf: 0,
+        name: "TDX enumeration",
+        fields: &[(Eax, 0, 31, "Maximum TDX subleaf")],
+    },
+];
+
+/// Fields of the EPC section subleaves of leaf 0x12, from subleaf 2 on.
+const EPC_SECTION: [(Register, u32, u32, &str); 6] = [
+    (Eax, 0, 3, "Type"),
+    (Eax, 12, 31, "Base[31:12]"),
+    (Ebx, 0, 19, "Base[51:32]"),
+    (Ecx, 0, 3, "Properties"),
+    (Ecx, 12, 31, "Size[31:12]"),
+    (Edx, 0, 19, "Size[51:32]"),
+];
+
+/// The name and fields of a subleaf, if it is known.
+fn layout(leaf: u32, subleaf: u32) -> Option<(&'static str, Fields)> {
+    if leaf == 0x12 && subleaf >= 2 {
+        return Some(("SGX EPC section", &EPC_SECTION));
+    }
+    LEAVES
+        .iter()
+        .find(|l| l.leaf == leaf && l.subleaf == subleaf)
+        .map(|l| (l.name, l.fields))
+}
+
+/// A leaf or subleaf given in hex, e.g. "0x12", or in decimal.
+pub fn parse(number: &str) -> Option<u32> {
+    let number = number.trim();
+    match number
+        .strip_prefix("0x")
+        .or_else(|| number.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => number.parse().ok(),
+    }
+}
+
+/// A field of a subleaf.
+#[derive(Debug, Clone, Serialize)]
+pub struct Field {
+    pub register: Register,
+    pub name: &'static str,
+    /// Lowest and highest bit of the field
+    pub bits: (u32, u32),
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reading {
+    pub leaf: u32,
+    pub subleaf: u32,
+    pub name: Option<&'static str>,
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
+    /// Vendor string of leaves 0x0 and 0x21, e.g. "GenuineIntel"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<Field>,
+}
+
+/// Execute CPUID, decoding its fields if `decode` is set and the subleaf is
+/// known.
+pub fn read(probe: &Probe, leaf: u32, subleaf: u32, decode: bool) -> Reading {
+    let res = probe.cpuid_count(leaf, subleaf);
+    let layout = layout(leaf, subleaf);
+    let fields = match layout {
+        Some((_, fields)) if decode => fields
+            .iter()
+            .map(|&(register, low, high, name)| {
+                let width = high - low + 1;
+                let mask = if width == 32 {
+                    u32::MAX
+                } else {
+                    (1 << width) - 1
+                };
+                Field {
+                    register,
+                    name,
+                    bits: (low, high),
+                    value: (register.of(&res) >> low) & mask,
+                }
+            })
+            .collect(),
+        _ => vec![],
+    };
+    // both spell their vendor in EBX, EDX and ECX
+    let signature = matches!(leaf, 0x0 | 0x21).then(|| {
+        [res.ebx, res.edx, res.ecx]
+            .iter()
+            .flat_map(|r| r.to_le_bytes())
+            .map(char::from)
+            .collect()
+    });
+
+    Reading {
+        leaf,
+        subleaf,
+        name: layout.map(|(name, _)| name),
+        eax: res.eax,
+        ebx: res.ebx,
+        ecx: res.ecx,
+        edx: res.edx,
+        signature,
+        fields,
+    }
+}
+
+/// Execute every known leaf the CPU implements: the SGX leaves, with each
+/// EPC section, only with SGX, and the PCONFIG leaf only with PCONFIG.
+pub fn dump(probe: &Probe, decode: bool) -> Vec<Reading> {
+    let max_leaf = probe.cpuid(0x0).eax;
+    let features = probe.cpuid_count(0x7, 0);
+    let sgx = features.ebx & (1 << 2) != 0;
+    let pconfig = features.edx & (1 << 18) != 0;
+
+    let mut readings = vec![];
+    for leaf in &LEAVES {
+        let applicable = leaf.leaf <= max_leaf
+            && match leaf.leaf {
+                0x12 => sgx,
+                0x1b => pconfig,
+                _ => true,
+            };
+        if !applicable {
+            continue;
+        }
+        readings.push(read(probe, leaf.leaf, leaf.subleaf, decode));
+        if leaf.leaf == 0x12 && leaf.subleaf == 1 {
+            // bounded like the EPC check, type 0 ends the list and 1 is an
+            // EPC section
+            for subleaf in 2..66 {
+                let section = read(probe, 0x12, subleaf, decode);
+                if section.eax & 0xf != 1 {
+                    break;
+                }
+                readings.push(section);
+            }
+        }
+    }
+    readings
+}
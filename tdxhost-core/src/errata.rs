@@ -0,0 +1,62 @@
+/// A published erratum or advisory relevant to running TDX on a CPU.
+pub struct Erratum {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// CPUID display family and model the erratum applies to
+    pub family: u32,
+    pub model: u32,
+    /// Affected steppings, all of them if empty
+    pub steppings: &'static [u32],
+    /// Microcode revision fixing the erratum, if a microcode update does
+    pub fixed_in_microcode: Option<u32>,
+    pub link: &'static str,
+}
+
+pub const ERRATA: [Erratum; 2] = [
+    Erratum {
+        id: "TDX_PW_MCE",
+        title: "A partial write to TDX private memory poisons the cache line; \
+                kexec and kdump are disabled while TDX is enabled",
+        family: 0x6,
+        model: 0x8f,
+        steppings: &[],
+        fixed_in_microcode: None,
+        link: "https://docs.kernel.org/arch/x86/tdx.html#erratum",
+    },
+    Erratum {
+        id: "TDX_PW_MCE",
+        title: "A partial write to TDX private memory poisons the cache line; \
+                kexec and kdump are disabled while TDX is enabled",
+        family: 0x6,
+        model: 0xcf,
+        steppings: &[],
+        fixed_in_microcode: None,
+        link: "https://docs.kernel.org/arch/x86/tdx.html#erratum",
+    },
+];
+
+impl Erratum {
+    /// Whether the erratum affects a CPU. An unknown microcode revision is
+    /// assumed to be affected.
+    pub fn applies(&self, family: u32, model: u32, stepping: u32, microcode: Option<u32>) -> bool {
+        self.family == family
+            && self.model == model
+            && (self.steppings.is_empty() || self.steppings.contains(&stepping))
+            && match (self.fixed_in_microcode, microcode) {
+                (Some(fixed), Some(microcode)) => microcode < fixed,
+                _ => true,
+            }
+    }
+}
+
+/// Errata affecting a CPU.
+pub fn matching(
+    family: u32,
+    model: u32,
+    stepping: u32,
+    microcode: Option<u32>,
+) -> impl Iterator<Item = &'static Erratum> {
+    ERRATA
+        .iter()
+        .filter(move |e| e.applies(family, model, stepping, microcode))
+}
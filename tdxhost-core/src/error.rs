@@ -0,0 +1,61 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::smoke_test::Stage;
+
+/// Errors of the tdxhost library API.
+///
+/// `CheckFailed` means the host was inspected and is not ready; every other
+/// variant means tdxhost could not determine whether it is.
+#[derive(Debug, thiserror::Error)]
+pub enum TdxHostError {
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    /// The msr kernel module is not loaded or the register could not be read
+    #[error("MSR {index:#x} is unavailable: {reason}")]
+    MsrUnavailable { index: u32, reason: String },
+    /// The host lacks something tdxhost needs to inspect it, e.g. an MSR the
+    /// CPU does not implement
+    #[error("unsupported platform: {0}")]
+    UnsupportedPlatform(String),
+    #[error("checks did not pass: {}", ids.join(", "))]
+    CheckFailed { ids: Vec<String> },
+    /// A remediation command exited unsuccessfully
+    #[error("{command} failed: {stderr}")]
+    Command { command: String, stderr: String },
+    /// The kernel did not install a new TDX module
+    #[error("TDX module update failed: {0}")]
+    ModuleUpdate(String),
+    /// A stage of creating or running a test TD failed
+    #[error("TD smoke test failed to {stage}: {reason}")]
+    SmokeTest { stage: Stage, reason: String },
+    #[error("plugin {}: {reason}", path.display())]
+    Plugin { path: PathBuf, reason: String },
+    #[error(transparent)]
+    Io(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, TdxHostError>;
+
+impl From<std::io::Error> for TdxHostError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            ErrorKind::PermissionDenied => TdxHostError::PermissionDenied(e.to_string()),
+            _ => TdxHostError::Io(e),
+        }
+    }
+}
+
+impl TdxHostError {
+    /// An I/O error on `path`, with the path in the message.
+    pub(crate) fn io(path: &Path, e: std::io::Error) -> Self {
+        std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)).into()
+    }
+
+    pub(crate) fn plugin(path: &Path, reason: impl ToString) -> Self {
+        TdxHostError::Plugin {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+}
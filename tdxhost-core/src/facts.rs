@@ -0,0 +1,92 @@
+//! The results as Ansible facts, for playbooks to gate enrolling a host on.
+
+use serde::Serialize;
+
+use crate::check::{Probe, TestState};
+use crate::checks;
+use crate::report::{Report, ReportEntry};
+use crate::stack::{self, Component};
+use crate::td;
+
+/// Result of `tdxhost ok --format ansible-facts`: a flat dictionary, every
+/// key prefixed with "tdxhost_", of what a playbook decides on. Unknown
+/// values are null.
+#[derive(Debug, Clone, Serialize)]
+pub struct Facts {
+    /// True when every required check passed
+    pub tdxhost_ready: bool,
+    /// Privileged checks were skipped; the host is not known to be ready
+    pub tdxhost_degraded: bool,
+    /// Required checks that failed, holding the host back
+    pub tdxhost_failing_checks: Vec<String>,
+    /// Optional checks that failed
+    pub tdxhost_failing_optional_checks: Vec<String>,
+    /// Checks, required or optional, that passed with a warning
+    pub tdxhost_warning_checks: Vec<String>,
+    /// Version of tdxhost that gathered the facts
+    pub tdxhost_version: String,
+    /// e.g. "Dell Inc. PowerEdge R760"
+    pub tdxhost_hardware_model: Option<String>,
+    pub tdxhost_bios_version: Option<String>,
+    pub tdxhost_kernel_version: Option<String>,
+    pub tdxhost_module_initialized: bool,
+    /// e.g. "1.5.6"
+    pub tdxhost_module_version: Option<String>,
+    pub tdxhost_qemu_version: Option<String>,
+    pub tdxhost_libvirt_version: Option<String>,
+    pub tdxhost_dcap_version: Option<String>,
+    /// KeyIDs for MKTME, besides KeyID 0 of TME
+    pub tdxhost_keyids_mktme: Option<u64>,
+    /// KeyIDs reserved for TDX
+    pub tdxhost_keyids_tdx: Option<u64>,
+    /// TDX KeyIDs left for TDs, each TD using one
+    pub tdxhost_keyids_td_capacity: Option<u64>,
+    /// TDX KeyIDs running TDs use
+    pub tdxhost_keyids_in_use: u64,
+    /// How many more TDs the KeyIDs allow
+    pub tdxhost_keyids_free: Option<u64>,
+}
+
+fn ids(entries: &[ReportEntry], state: TestState) -> Vec<String> {
+    entries
+        .iter()
+        .filter(|e| e.result.state == state)
+        .map(|e| e.id.clone())
+        .collect()
+}
+
+/// The facts of the host `probe` reads, given the report of its checks.
+pub fn collect(probe: &Probe, report: &Report) -> Facts {
+    let components = stack::collect(probe);
+    let version = |component: Component| {
+        components
+            .iter()
+            .find(|c| c.component == component)
+            .and_then(|c| c.version.clone())
+    };
+    let keyids = td::keyids(probe);
+    let mut warnings = ids(&report.required, TestState::Warning);
+    warnings.extend(ids(&report.optional, TestState::Warning));
+
+    Facts {
+        tdxhost_ready: report.ready,
+        tdxhost_degraded: report.degraded,
+        tdxhost_failing_checks: ids(&report.required, TestState::Fail),
+        tdxhost_failing_optional_checks: ids(&report.optional, TestState::Fail),
+        tdxhost_warning_checks: warnings,
+        tdxhost_version: env!("CARGO_PKG_VERSION").to_string(),
+        tdxhost_hardware_model: report.hardware.model(),
+        tdxhost_bios_version: report.hardware.bios_version.clone(),
+        tdxhost_kernel_version: version(Component::Kernel),
+        tdxhost_module_initialized: checks::check_tdx_module(probe).is_ok_and(|s| s.is_some()),
+        tdxhost_module_version: version(Component::TdxModule),
+        tdxhost_qemu_version: version(Component::Qemu),
+        tdxhost_libvirt_version: version(Component::Libvirt),
+        tdxhost_dcap_version: version(Component::Dcap),
+        tdxhost_keyids_mktme: keyids.mktme,
+        tdxhost_keyids_tdx: keyids.tdx,
+        tdxhost_keyids_td_capacity: keyids.td_capacity,
+        tdxhost_keyids_in_use: keyids.in_use,
+        tdxhost_keyids_free: keyids.free,
+    }
+}
@@ -0,0 +1,66 @@
+//! The first BIOS releases able to enable TDX, per platform.
+
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::{Result, TdxHostError};
+use crate::stack::Requirement;
+
+/// Site entries, matched before the bundled ones.
+pub const FIRMWARE_PATH: &str = "/etc/tdxhost/firmware.toml";
+const BUNDLED_FIRMWARE: &str = include_str!("../compat/firmware.toml");
+
+/// The BIOS a platform needs for TDX.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Firmware {
+    /// Prefix of the DMI sys_vendor
+    pub vendor: String,
+    /// Prefix of the DMI product_name or board_name, any product if unset
+    pub product: Option<String>,
+    /// Range of the DMI bios_version, e.g. ">=1.6.6"
+    pub version: Option<Requirement>,
+    /// Earliest BIOS release date, "YYYY-MM-DD"
+    pub released: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FirmwareTable {
+    #[serde(rename = "firmware", default)]
+    pub entries: Vec<Firmware>,
+}
+
+impl FirmwareTable {
+    /// The table shipped with tdxhost.
+    pub fn bundled() -> Self {
+        toml::from_str(BUNDLED_FIRMWARE).expect("the bundled firmware table is valid")
+    }
+
+    /// The entries in `FIRMWARE_PATH`, if there are any, followed by the
+    /// bundled ones.
+    pub fn load() -> Result<Self> {
+        let path = Path::new(FIRMWARE_PATH);
+        let mut table = match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).map_err(|e| {
+                TdxHostError::io(path, std::io::Error::new(ErrorKind::InvalidData, e))
+            })?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(TdxHostError::io(path, e)),
+        };
+        table.entries.extend(Self::bundled().entries);
+        Ok(table)
+    }
+
+    /// The first entry for a platform, by its DMI sys_vendor and its
+    /// product_name and board_name.
+    pub fn find(&self, vendor: &str, products: &[&str]) -> Option<&Firmware> {
+        self.entries.iter().find(|f| {
+            vendor.starts_with(&f.vendor)
+                && f.product
+                    .as_ref()
+                    .is_none_or(|p| products.iter().any(|product| product.starts_with(p)))
+        })
+    }
+}
@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use crate::check::Probe;
+use crate::i18n::tr_args;
+use crate::remediation::{in_path, shell_quote, Action, Remediation};
+
+const DEFAULT_GRUB: &str = "/etc/default/grub";
+
+/// Debian and Ubuntu keep user parameters in GRUB_CMDLINE_LINUX_DEFAULT and
+/// ship update-grub; the RHEL family uses GRUB_CMDLINE_LINUX.
+fn is_debian_family(probe: &Probe) -> bool {
+    probe.exists("/etc/debian_version") || in_path(probe, "update-grub")
+}
+
+/// grub.cfg that grub2-mkconfig should regenerate. Pre-RHEL 9 UEFI installs
+/// boot a grub.cfg on the ESP; newer ones keep a stub there that chains to
+/// /boot/grub2/grub.cfg.
+fn rhel_grub_cfg(probe: &Probe) -> String {
+    if probe.exists("/sys/firmware/efi") {
+        if let Ok(entries) = probe.read_dir("/boot/efi/EFI") {
+            for entry in entries {
+                let cfg = format!("/boot/efi/EFI/{}/grub.cfg", entry);
+                match probe.read_file(&cfg) {
+                    Ok(contents) if !contents.contains("configfile") => return cfg,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    "/boot/grub2/grub.cfg".to_string()
+}
+
+/// Append `params` to `variable` in the contents of /etc/default/grub,
+/// returning None when all of them are present already.
+pub(crate) fn with_params(contents: &str, variable: &str, params: &[&str]) -> Option<String> {
+    let prefix = format!("{}=", variable);
+    let mut lines: Vec<String> = contents.lines().map(str::to_string).collect();
+    // like the shell, the last assignment wins
+    let index = lines
+        .iter()
+        .rposition(|l| l.trim_start().starts_with(&prefix));
+
+    let current = index.map(|i| {
+        lines[i].trim_start()[prefix.len()..]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string()
+    });
+    let existing: Vec<&str> = current
+        .as_deref()
+        .map(|c| c.split_whitespace().collect())
+        .unwrap_or_default();
+    let missing: Vec<&str> = params
+        .iter()
+        .filter(|p| !existing.contains(p))
+        .copied()
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+
+    let value = existing
+        .into_iter()
+        .chain(missing)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = format!("{}\"{}\"", prefix, value);
+    match index {
+        Some(i) => lines[i] = line,
+        None => lines.push(line),
+    }
+
+    Some(lines.join("\n") + "\n")
+}
+
+/// Escape `text` for a sed extended regular expression delimited by '/'.
+fn sed_regex(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '\\' | '.' | '[' | ']' | '(' | ')' | '*' | '+' | '?' | '{' | '}' | '|' | '^' | '$'
+            | '/' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+/// Escape `text` for the replacement of a sed s/// command.
+fn sed_replacement(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('&', "\\&")
+        .replace('/', "\\/")
+}
+
+/// Shell commands doing what `with_params` does to the file at `path` as it
+/// is when they run, so a script run later keeps the edits made since.
+pub(crate) fn add_params_script(path: &str, variable: &str, params: &[String]) -> String {
+    let assignment = format!("^[[:space:]]*{}=", sed_regex(variable));
+    // quote an unquoted value, then append each parameter the line lacks
+    // inside the closing quote
+    let edits: String = params
+        .iter()
+        .map(|p| {
+            format!(
+                " /[=\" ']{}([\" ']|$)/! s/([\"'])[[:space:]]*$/ {}\\1/;",
+                sed_regex(p),
+                sed_replacement(p)
+            )
+        })
+        .collect();
+    let path = shell_quote(path);
+    format!(
+        "if grep -Eq {assignment} {path}; then\n    sed -i -E {sed} {path}\nelse\n    echo {line} >> {path}\nfi",
+        assignment = shell_quote(&assignment),
+        sed = shell_quote(&format!(
+            "/{a}/{{ s/({a})([^\"'].*)?$/\\1\"\\2\"/;{e} }}",
+            a = assignment,
+            e = edits
+        )),
+        line = shell_quote(&format!("{}=\"{}\"", variable, params.join(" "))),
+        path = path
+    )
+}
+
+/// Remediation that makes `params` part of the kernel command line of
+/// `probe`'s host on the next boot.
+pub fn append_kernel_params(probe: &Probe, params: &[&str]) -> Option<Remediation> {
+    let contents = probe.read_file(DEFAULT_GRUB).ok()?;
+    let add_params = |variable: &str| {
+        with_params(&contents, variable, params)?;
+        Some(Action::AddKernelParams {
+            path: PathBuf::from(DEFAULT_GRUB),
+            variable: variable.to_string(),
+            params: params.iter().map(|p| p.to_string()).collect(),
+        })
+    };
+
+    let mut actions = vec![];
+    if is_debian_family(probe) {
+        actions.push(add_params("GRUB_CMDLINE_LINUX_DEFAULT")?);
+        actions.push(Action::command("update-grub", &[]));
+    } else {
+        actions.push(add_params("GRUB_CMDLINE_LINUX")?);
+        if in_path(probe, "grubby") {
+            // BLS entries carry their own options, so update the installed
+            // kernels directly; /etc/default/grub covers future ones
+            let args = format!("--args={}", params.join(" "));
+            actions.push(Action::command(
+                "grubby",
+                &["--update-kernel=ALL", args.as_str()],
+            ));
+        } else {
+            let cfg = rhel_grub_cfg(probe);
+            actions.push(Action::command("grub2-mkconfig", &["-o", cfg.as_str()]));
+        }
+    }
+
+    Some(Remediation {
+        summary: tr_args("grub-fix", &[("params", &params.join(" "))]),
+        actions,
+        reboot_required: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    fn probe(files: &[(&str, &str)]) -> Probe {
+        Probe::new(Fixture {
+            files: files
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// The commands `append_kernel_params` runs on `probe`'s host.
+    fn commands(probe: &Probe) -> Vec<String> {
+        append_kernel_params(probe, &["nohibernate"])
+            .unwrap()
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Command { program, args } => Some(
+                    std::iter::once(program)
+                        .chain(args)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const VARIABLE: &str = "GRUB_CMDLINE_LINUX_DEFAULT";
+
+    #[test]
+    fn params_appended() {
+        let grub = "GRUB_DEFAULT=0\nGRUB_CMDLINE_LINUX_DEFAULT=\"quiet splash\"\n";
+        assert_eq!(
+            with_params(grub, VARIABLE, &["nohibernate", "kvm_intel.tdx=1"]).as_deref(),
+            Some("GRUB_DEFAULT=0\nGRUB_CMDLINE_LINUX_DEFAULT=\"quiet splash nohibernate kvm_intel.tdx=1\"\n")
+        );
+        assert_eq!(
+            with_params(grub, VARIABLE, &["quiet"]),
+            None,
+            "present params are not added again"
+        );
+    }
+
+    #[test]
+    fn last_assignment_wins() {
+        let grub = "GRUB_CMDLINE_LINUX_DEFAULT='quiet'\n# local\nGRUB_CMDLINE_LINUX_DEFAULT=\"\"\n";
+        assert_eq!(
+            with_params(grub, VARIABLE, &["nohibernate"]).as_deref(),
+            Some("GRUB_CMDLINE_LINUX_DEFAULT='quiet'\n# local\nGRUB_CMDLINE_LINUX_DEFAULT=\"nohibernate\"\n")
+        );
+    }
+
+    #[test]
+    fn variable_added() {
+        assert_eq!(
+            with_params("GRUB_TIMEOUT=5", VARIABLE, &["nohibernate"]).as_deref(),
+            Some("GRUB_TIMEOUT=5\nGRUB_CMDLINE_LINUX_DEFAULT=\"nohibernate\"\n")
+        );
+    }
+
+    #[test]
+    fn host_from_probe() {
+        assert!(append_kernel_params(&probe(&[]), &["nohibernate"]).is_none());
+
+        let debian = probe(&[
+            (DEFAULT_GRUB, "GRUB_CMDLINE_LINUX_DEFAULT=\"quiet\"\n"),
+            ("/etc/debian_version", "12.5\n"),
+        ]);
+        assert_eq!(commands(&debian), ["update-grub"]);
+
+        let rhel = probe(&[
+            (DEFAULT_GRUB, "GRUB_CMDLINE_LINUX=\"quiet\"\n"),
+            ("/sys/firmware/efi/fw_platform_size", "64\n"),
+            ("/boot/efi/EFI/redhat/grub.cfg", "set root=(hd0,gpt2)\n"),
+        ]);
+        assert_eq!(
+            commands(&rhel),
+            ["grub2-mkconfig -o /boot/efi/EFI/redhat/grub.cfg"]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+const FALLBACK_LOCALE: &str = "en-US";
+
+/// Message catalogs compiled into the binary.
+const CATALOGS: [(&str, &str); 2] = [
+    ("en-US", include_str!("../i18n/en-US/tdxhost.ftl")),
+    ("zh-CN", include_str!("../i18n/zh-CN/tdxhost.ftl")),
+];
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+struct Localizer {
+    /// Selected locale first, en-US last
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    fn new(requested: Option<&str>) -> Self {
+        let mut locales = vec![];
+        if let Some(locale) = requested.and_then(negotiate) {
+            locales.push(locale);
+        }
+        if !locales.contains(&FALLBACK_LOCALE) {
+            locales.push(FALLBACK_LOCALE);
+        }
+
+        let bundles = locales
+            .into_iter()
+            .map(|locale| {
+                let (_, source) = CATALOGS.iter().find(|(l, _)| *l == locale).unwrap();
+                let resource = FluentResource::try_new(source.to_string())
+                    .expect("bundled message catalog is invalid");
+                let mut bundle = FluentBundle::new_concurrent(vec![locale.parse().unwrap()]);
+                // Unicode isolation marks show up as garbage on most terminals
+                bundle.set_use_isolating(false);
+                bundle
+                    .add_resource(resource)
+                    .expect("bundled message catalog has duplicate messages");
+                bundle
+            })
+            .collect();
+
+        Localizer { bundles }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        for bundle in &self.bundles {
+            if let Some(pattern) = bundle.get_message(id).and_then(|m| m.value()) {
+                let mut errors = vec![];
+                return bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned();
+            }
+        }
+
+        id.to_string()
+    }
+}
+
+/// Map a POSIX locale ("zh_CN.UTF-8") or BCP 47 tag ("zh-CN") to one of the
+/// bundled catalogs, matching on the language alone as a last resort.
+fn negotiate(requested: &str) -> Option<&'static str> {
+    let tag = requested
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    let requested: LanguageIdentifier = tag.parse().ok()?;
+
+    let available = CATALOGS
+        .iter()
+        .map(|(l, _)| (*l, l.parse::<LanguageIdentifier>().unwrap()));
+
+    available
+        .clone()
+        .find(|(_, l)| *l == requested)
+        .or_else(|| {
+            available
+                .clone()
+                .find(|(_, l)| l.language == requested.language)
+        })
+        .map(|(l, _)| l)
+}
+
+fn locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Select the message locale; `--lang` wins over the environment.
+pub fn init(lang: Option<&str>) {
+    let requested = lang.map(str::to_string).or_else(locale_from_env);
+    let _ = LOCALIZER.set(Localizer::new(requested.as_deref()));
+}
+
+fn localizer() -> &'static Localizer {
+    LOCALIZER.get_or_init(|| Localizer::new(locale_from_env().as_deref()))
+}
+
+/// Look up a message by id.
+pub fn tr(id: &str) -> String {
+    localizer().format(id, None)
+}
+
+/// Look up a message by id, substituting `{ $name }` placeables.
+pub fn tr_args(id: &str, args: &[(&str, &str)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, *value);
+    }
+
+    localizer().format(id, Some(&fluent_args))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn message_ids(source: &str) -> BTreeSet<&str> {
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_lowercase()))
+            .filter_map(|line| line.split_once(" ="))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    #[test]
+    fn catalogs_parse() {
+        for (locale, source) in CATALOGS {
+            let resource = FluentResource::try_new(source.to_string());
+            assert!(resource.is_ok(), "{locale} catalog has syntax errors");
+        }
+    }
+
+    #[test]
+    fn catalogs_translate_every_message() {
+        let english = message_ids(CATALOGS[0].1);
+        for (locale, source) in &CATALOGS[1..] {
+            let translated = message_ids(source);
+            let missing: Vec<_> = english.difference(&translated).collect();
+            let extra: Vec<_> = translated.difference(&english).collect();
+            assert!(
+                missing.is_empty() && extra.is_empty(),
+                "{locale} catalog is missing {missing:?} and has unknown {extra:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn negotiate_locales() {
+        assert_eq!(negotiate("zh_CN.UTF-8"), Some("zh-CN"));
+        assert_eq!(negotiate("zh-TW"), Some("zh-CN"));
+        assert_eq!(negotiate("en_GB"), Some("en-US"));
+        assert_eq!(negotiate("fr_FR"), None);
+        assert_eq!(negotiate("C"), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! A summary of the host's TDX platform, without judging it.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::check::Probe;
+use crate::checks;
+use crate::stack::{self, Installed};
+use crate::td::{self, KeyIdUsage};
+
+/// Services quotes for TDs are generated and their collateral fetched with.
+const ATTESTATION_SERVICES: [&str; 3] = ["qgsd.service", "aesmd.service", "pccs.service"];
+
+/// A systemd unit and its state.
+#[derive(Debug, Clone, Serialize)]
+pub struct Service {
+    pub unit: String,
+    /// The unit's ActiveState, e.g. "active", or None if it is not installed
+    pub state: Option<String>,
+}
+
+/// The hardware model, from SMBIOS, to group the results of a fleet by.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Hardware {
+    /// System manufacturer, e.g. "Dell Inc."
+    pub vendor: Option<String>,
+    /// System product name, e.g. "PowerEdge R760"
+    pub product: Option<String>,
+    /// Baseboard product name
+    pub board: Option<String>,
+    pub bios_vendor: Option<String>,
+    pub bios_version: Option<String>,
+    /// BIOS release date, as in SMBIOS, e.g. "03/14/2024"
+    pub bios_date: Option<String>,
+}
+
+impl Hardware {
+    /// The system, e.g. "Dell Inc. PowerEdge R760", if it is known.
+    pub fn model(&self) -> Option<String> {
+        let model: Vec<&str> = [&self.vendor, &self.product]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!model.is_empty()).then(|| model.join(" "))
+    }
+
+    /// The BIOS, e.g. "Dell Inc. 1.6.6 03/14/2024", if its version is known.
+    pub fn bios(&self) -> Option<String> {
+        let version = self.bios_version.as_deref()?;
+        let bios: Vec<&str> = [
+            self.bios_vendor.as_deref(),
+            Some(version),
+            self.bios_date.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        Some(bios.join(" "))
+    }
+}
+
+/// Read the SMBIOS tables the kernel exports in /sys/class/dmi/id.
+pub fn hardware(probe: &Probe) -> Hardware {
+    Hardware {
+        vendor: checks::dmi(probe, "sys_vendor"),
+        product: checks::dmi(probe, "product_name"),
+        board: checks::dmi(probe, "board_name"),
+        bios_vendor: checks::dmi(probe, "bios_vendor"),
+        bios_version: checks::dmi(probe, "bios_version"),
+        bios_date: checks::dmi(probe, "bios_date"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformInfo {
+    pub hardware: Hardware,
+    /// Brand string of the CPU, or its family, model and stepping
+    pub cpu: String,
+    pub sockets: Option<usize>,
+    pub microcode: Option<String>,
+    /// PRETTY_NAME of the distribution
+    pub os: Option<String>,
+    pub secure_boot: Option<bool>,
+    /// Whether BIOS activated TME, by IA32_TME_ACTIVATE
+    pub tme_enabled: Option<bool>,
+    pub keyids: KeyIdUsage,
+    pub tdx_module_initialized: bool,
+    /// Versions of the kernel, TDX module, QEMU, libvirt and DCAP
+    pub components: Vec<Installed>,
+    pub attestation_services: Vec<Service>,
+}
+
+fn service(probe: &Probe, unit: &str) -> Service {
+    let state = checks::unit_properties(probe, unit, "LoadState,ActiveState")
+        .ok()
+        .and_then(|properties| {
+            let property = |name: &str| {
+                properties
+                    .iter()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.clone())
+            };
+            (property("LoadState")? != "not-found").then(|| property("ActiveState"))?
+        });
+    Service {
+        unit: unit.to_string(),
+        state,
+    }
+}
+
+/// Gather the summary. What cannot be read is left out rather than failing.
+pub fn collect(probe: &Probe) -> PlatformInfo {
+    let cpu = checks::cpu_brand(probe).unwrap_or_else(|| {
+        let (family, model, stepping) = checks::cpu_signature(probe);
+        format!(
+            "family {:#x} model {:#x} stepping {:#x}",
+            family, model, stepping
+        )
+    });
+    let os = probe
+        .os_release()
+        .ok()
+        .and_then(|os_release| checks::os_release_value(&os_release, "PRETTY_NAME"));
+
+    PlatformInfo {
+        hardware: hardware(probe),
+        cpu,
+        sockets: probe.packages().ok().map(|p| p.len()).filter(|&n| n > 0),
+        microcode: checks::cpu_microcode(probe).map(|m| format!("{:#x}", m)),
+        os,
+        secure_boot: checks::secure_boot(probe).map(|(enabled, _)| enabled),
+        // IA32_TME_ACTIVATE bit 1: TME enabled
+        tme_enabled: probe.read_msr(0x982).ok().map(|v| v & (1 << 1) != 0),
+        keyids: td::keyids(probe),
+        tdx_module_initialized: checks::check_tdx_module(probe).is_ok_and(|s| s.is_some()),
+        components: stack::collect(probe),
+        attestation_services: ATTESTATION_SERVICES
+            .iter()
+            .map(|unit| service(probe, unit))
+            .collect(),
+    }
+}
@@ -0,0 +1,254 @@
+//! TDX support of KVM, queried through /dev/kvm.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+const KVM_CREATE_VM: libc::c_ulong = 0xae01;
+const KVM_CHECK_EXTENSION: libc::c_ulong = 0xae03;
+/// Bitmap of the VM types KVM_CREATE_VM accepts
+const KVM_CAP_VM_TYPES: libc::c_ulong = 235;
+/// _IOWR(KVMIO, 0xba, unsigned long)
+pub(crate) const KVM_MEMORY_ENCRYPT_OP: libc::c_ulong = 0xc008_aeba;
+/// VM type of TDs
+pub const KVM_X86_TDX_VM: u32 = 5;
+/// VM types, by their value for KVM_CREATE_VM.
+pub const VM_TYPES: [(u32, &str); 6] = [
+    (0, "default"),
+    (1, "sw-protected"),
+    (2, "SEV"),
+    (3, "SEV-ES"),
+    (4, "SEV-SNP"),
+    (KVM_X86_TDX_VM, "TDX"),
+];
+/// Command of KVM_MEMORY_ENCRYPT_OP
+const KVM_TDX_CAPABILITIES: u32 = 0;
+/// Memslots with a guest_memfd backing their private memory
+const KVM_CAP_USER_MEMORY2: libc::c_ulong = 231;
+/// Bitmap of the memory attributes KVM_SET_MEMORY_ATTRIBUTES takes
+const KVM_CAP_MEMORY_ATTRIBUTES: libc::c_ulong = 233;
+const KVM_CAP_GUEST_MEMFD: libc::c_ulong = 234;
+/// Memory attribute of private pages
+pub const KVM_MEMORY_ATTRIBUTE_PRIVATE: u64 = 1 << 3;
+
+/// Size of struct kvm_tdx_capabilities up to its CPUID entries: the
+/// supported attributes and XFAM, four TDVMCALL masks and reserved space.
+const CAPABILITIES_HEADER: usize = 256 * 8;
+/// Size of struct kvm_cpuid2 without its entries
+pub(crate) const CPUID2_HEADER: usize = 8;
+/// Size of struct kvm_cpuid_entry2
+pub(crate) const CPUID_ENTRY: usize = 40;
+
+/// TD attributes, by bit, as defined by the TDX module ABI.
+pub const TD_ATTRIBUTES: [(u32, &str); 12] = [
+    (0, "DEBUG"),
+    (4, "HGS_PLUS_PROF"),
+    (5, "PERF_PROF"),
+    (6, "PMT_PROF"),
+    (16, "ICSSD"),
+    (27, "LASS"),
+    (28, "SEPT_VE_DISABLE"),
+    (29, "MIGRATABLE"),
+    (30, "PKS"),
+    (31, "KL"),
+    (62, "TPA"),
+    (63, "PERFMON"),
+];
+
+/// Extended features a TD may use (XFAM), by their bit in XCR0 and IA32_XSS.
+pub const XFAM_FEATURES: [(u32, &str); 20] = [
+    (0, "x87"),
+    (1, "SSE"),
+    (2, "AVX"),
+    (3, "MPX bounds"),
+    (4, "MPX CSR"),
+    (5, "AVX-512 opmask"),
+    (6, "AVX-512 ZMM_Hi256"),
+    (7, "AVX-512 Hi16_ZMM"),
+    (8, "PT"),
+    (9, "PKRU"),
+    (10, "PASID"),
+    (11, "CET_U"),
+    (12, "CET_S"),
+    (13, "HDC"),
+    (14, "UINTR"),
+    (15, "LBR"),
+    (16, "HWP"),
+    (17, "AMX TILECFG"),
+    (18, "AMX TILEDATA"),
+    (19, "APX"),
+];
+
+/// Names of the bits set in `bits`, listed in `names`, and the bits that are
+/// set but not named, in hex.
+pub fn decode(bits: u64, names: &[(u32, &'static str)]) -> Vec<String> {
+    let mut decoded = vec![];
+    let mut unnamed = bits;
+    for &(bit, name) in names {
+        if bits & (1 << bit) != 0 {
+            decoded.push(name.to_string());
+            unnamed &= !(1 << bit);
+        }
+    }
+    if unnamed != 0 {
+        decoded.push(format!("{:#x}", unnamed));
+    }
+    decoded
+}
+
+/// A CPUID leaf, and the bits of it a TD's owner may configure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuidEntry {
+    pub leaf: u32,
+    pub subleaf: u32,
+    pub eax: u32,
+    pub ebx: u32,
+    pub ecx: u32,
+    pub edx: u32,
+}
+
+/// What KVM_TDX_CAPABILITIES reports: the TD configurations KVM accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TdxCapabilities {
+    /// TD attributes that may be set
+    pub supported_attrs: u64,
+    /// XFAM bits that may be set
+    pub supported_xfam: u64,
+    /// CPUID bits that may be configured
+    pub cpuid: Vec<CpuidEntry>,
+}
+
+/// What KVM offers for the private memory of TDs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivateMemory {
+    /// KVM_CAP_GUEST_MEMFD: guest_memfd files hold private memory
+    pub guest_memfd: bool,
+    /// KVM_CAP_USER_MEMORY2: memslots take a guest_memfd
+    pub user_memory2: bool,
+    /// KVM_CAP_MEMORY_ATTRIBUTES: the attributes pages may be given
+    pub memory_attributes: u64,
+    /// Whether a TD was asked, else /dev/kvm when no TD could be created
+    pub td: bool,
+}
+
+/// struct kvm_tdx_cmd, the argument of KVM_MEMORY_ENCRYPT_OP for TDs
+#[repr(C)]
+pub(crate) struct KvmTdxCmd {
+    pub id: u32,
+    pub flags: u32,
+    pub data: u64,
+    /// SEAMCALL error, when the TDX module failed the command
+    pub hw_error: u64,
+}
+
+pub(crate) fn last_error() -> std::io::Error {
+    std::io::Error::last_os_error()
+}
+
+/// Create a TD, failing when KVM does not support them.
+pub fn create_td(kvm: &File) -> std::io::Result<OwnedFd> {
+    let vm = unsafe { libc::ioctl(kvm.as_raw_fd(), KVM_CREATE_VM, KVM_X86_TDX_VM) };
+    if vm < 0 {
+        return Err(last_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(vm) })
+}
+
+/// Bitmap of the VM types KVM creates, by KVM_CAP_VM_TYPES.
+///
+/// Kernels predating the capability report 0, for them creating a TD is
+/// tried instead.
+pub fn vm_types() -> std::io::Result<u64> {
+    let kvm = File::open("/dev/kvm")?;
+    let types = unsafe { libc::ioctl(kvm.as_raw_fd(), KVM_CHECK_EXTENSION, KVM_CAP_VM_TYPES) };
+    if types < 0 {
+        return Err(last_error());
+    }
+    if types > 0 {
+        return Ok(types as u64);
+    }
+
+    let mut types = 1 << 0;
+    if create_td(&kvm).is_ok() {
+        types |= 1 << KVM_X86_TDX_VM;
+    }
+    Ok(types)
+}
+
+/// Issue KVM_TDX_CAPABILITIES on a new TD.
+pub fn tdx_capabilities() -> std::io::Result<TdxCapabilities> {
+    let kvm = File::open("/dev/kvm")?;
+    let vm = create_td(&kvm)?;
+
+    let mut nent = 64;
+    loop {
+        // u64 for the alignment of the struct
+        let size = CAPABILITIES_HEADER + CPUID2_HEADER + nent * CPUID_ENTRY;
+        let mut buffer = vec![0u64; size / 8];
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8)
+        };
+        bytes[CAPABILITIES_HEADER..CAPABILITIES_HEADER + 4]
+            .copy_from_slice(&(nent as u32).to_ne_bytes());
+
+        let mut cmd = KvmTdxCmd {
+            id: KVM_TDX_CAPABILITIES,
+            flags: 0,
+            data: bytes.as_mut_ptr() as u64,
+            hw_error: 0,
+        };
+        if unsafe { libc::ioctl(vm.as_raw_fd(), KVM_MEMORY_ENCRYPT_OP, &mut cmd) } < 0 {
+            let e = last_error();
+            // KVM wants room for more CPUID entries
+            if e.raw_os_error() == Some(libc::E2BIG) && nent < 4096 {
+                nent *= 4;
+                continue;
+            }
+            return Err(e);
+        }
+
+        let u32_at =
+            |offset: usize| u32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        let count = (u32_at(CAPABILITIES_HEADER) as usize).min(nent);
+        let cpuid = (0..count)
+            .map(|i| {
+                // function, index, flags, eax, ebx, ecx, edx, padding
+                let entry = CAPABILITIES_HEADER + CPUID2_HEADER + i * CPUID_ENTRY;
+                CpuidEntry {
+                    leaf: u32_at(entry),
+                    subleaf: u32_at(entry + 4),
+                    eax: u32_at(entry + 12),
+                    ebx: u32_at(entry + 16),
+                    ecx: u32_at(entry + 20),
+                    edx: u32_at(entry + 24),
+                }
+            })
+            .collect();
+        return Ok(TdxCapabilities {
+            supported_attrs: buffer[0],
+            supported_xfam: buffer[1],
+            cpuid,
+        });
+    }
+}
+
+/// Query the private memory capabilities with KVM_CHECK_EXTENSION, on a new
+/// TD since they depend on the VM type.
+pub fn private_memory() -> std::io::Result<PrivateMemory> {
+    let kvm = File::open("/dev/kvm")?;
+    let td = create_td(&kvm).ok();
+    let fd = td.as_ref().map_or(kvm.as_raw_fd(), |vm| vm.as_raw_fd());
+    let check = |cap: libc::c_ulong| -> std::io::Result<u64> {
+        let res = unsafe { libc::ioctl(fd, KVM_CHECK_EXTENSION, cap) };
+        if res < 0 {
+            return Err(last_error());
+        }
+        Ok(res as u64)
+    };
+    Ok(PrivateMemory {
+        guest_memfd: check(KVM_CAP_GUEST_MEMFD)? != 0,
+        user_memory2: check(KVM_CAP_USER_MEMORY2)? != 0,
+        memory_attributes: check(KVM_CAP_MEMORY_ATTRIBUTES)?,
+        td: td.is_some(),
+    })
+}
@@ -0,0 +1,34 @@
+//! TDX host readiness checks, their remediations, and the message catalogs
+//! shared with the `tdxhost` CLI.
+
+pub mod bios;
+pub mod bios_tool;
+pub mod check;
+pub mod checks;
+pub mod config;
+pub mod cpuid;
+pub mod errata;
+pub mod error;
+pub mod facts;
+pub mod firmware;
+pub mod grub;
+pub mod i18n;
+pub mod info;
+pub mod kvm;
+pub mod modprobe;
+pub mod module_info;
+pub mod module_update;
+pub mod msr;
+pub mod platform;
+pub mod plugin;
+pub mod redfish;
+pub mod registry;
+pub mod remediation;
+pub mod report;
+pub mod run;
+pub mod smoke_test;
+pub mod stack;
+pub mod support;
+pub mod td;
+pub mod topology;
+pub mod wasm;
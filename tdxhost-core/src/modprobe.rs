@@ -0,0 +1,215 @@
+use std::path::PathBuf;
+
+use crate::check::Probe;
+use crate::i18n::tr_args;
+use crate::remediation::{in_path, shell_quote, Action, Remediation};
+
+const MODPROBE_CONF: &str = "/etc/modprobe.d/tdx.conf";
+
+/// Built-in modules have no initstate, and their parameters can only be set
+/// on the kernel command line. A module that is not loaded at all cannot be
+/// built in.
+fn is_loadable(probe: &Probe, module: &str) -> bool {
+    probe.exists(&format!("/sys/module/{}/initstate", module)) || !is_loaded(probe, module)
+}
+
+/// Whether the module is loaded, or built in.
+fn is_loaded(probe: &Probe, module: &str) -> bool {
+    probe.exists(&format!("/sys/module/{}", module))
+}
+
+/// Nothing, e.g. a running VM, holds a reference to the module.
+fn is_unused(probe: &Probe, module: &str) -> bool {
+    probe
+        .read_file(&format!("/sys/module/{}/refcnt", module))
+        .map(|r| r.trim() == "0")
+        .unwrap_or(false)
+}
+
+/// Modules that load from the initramfs read their options from the copy of
+/// modprobe.d inside it.
+fn regenerate_initramfs(probe: &Probe) -> Option<Action> {
+    if in_path(probe, "update-initramfs") {
+        Some(Action::command("update-initramfs", &["-u"]))
+    } else if in_path(probe, "dracut") {
+        Some(Action::command("dracut", &["--force"]))
+    } else {
+        None
+    }
+}
+
+/// Whether `line` is an "options" line of `module`, and its options.
+fn module_options<'a>(line: &'a str, module: &str) -> Option<Vec<&'a str>> {
+    let mut words = line.split_whitespace();
+    (words.next() == Some("options") && words.next() == Some(module)).then(|| words.collect())
+}
+
+/// Set `option`, e.g. "tdx=1", for `module` in the contents of a modprobe.d
+/// file, keeping its other lines and options. None when it is set already.
+pub(crate) fn with_option(contents: &str, module: &str, option: &str) -> Option<String> {
+    let key = format!("{}=", option.split('=').next().unwrap_or(option));
+    let mut lines = vec![];
+    for line in contents.lines() {
+        let Some(options) = module_options(line, module) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        if options.contains(&option) {
+            return None;
+        }
+        let rest: Vec<&str> = options
+            .into_iter()
+            .filter(|o| !o.starts_with(&key))
+            .collect();
+        if !rest.is_empty() {
+            lines.push(format!("options {} {}", module, rest.join(" ")));
+        }
+    }
+    lines.push(format!("options {} {}", module, option));
+
+    Some(lines.join("\n") + "\n")
+}
+
+/// Shell commands doing what `with_option` does to the file at `path` as it
+/// is when they run.
+pub(crate) fn set_option_script(path: &str, module: &str, option: &str) -> String {
+    let key = option.split('=').next().unwrap_or(option);
+    let line = format!("^[[:space:]]*options[[:space:]]+{}", module);
+    let path = shell_quote(path);
+    format!(
+        "mkdir -p \"$(dirname {path})\"\ntouch {path}\nif ! grep -Eq {set} {path}; then\n    sed -i -E {sed} {path}\n    echo {options} >> {path}\nfi",
+        set = shell_quote(&format!(
+            "{}([[:space:]].*)?[[:space:]]{}([[:space:]]|$)",
+            line, option
+        )),
+        sed = shell_quote(&format!(
+            "/{line}([[:space:]]|$)/ s/[[:space:]]+{key}=[^[:space:]]*//g; /{line}[[:space:]]*$/d",
+            line = line,
+            key = key
+        )),
+        options = shell_quote(&format!("options {} {}", module, option)),
+        path = path
+    )
+}
+
+/// Remediation that sets `option` for a loadable `module` of `probe`'s host
+/// through modprobe.d, or None when the module is built into the kernel.
+pub fn set_module_option(probe: &Probe, module: &str, option: &str) -> Option<Remediation> {
+    if !is_loadable(probe, module) {
+        return None;
+    }
+
+    let mut actions = vec![Action::SetModuleOption {
+        path: PathBuf::from(MODPROBE_CONF),
+        module: module.to_string(),
+        option: option.to_string(),
+    }];
+    actions.extend(regenerate_initramfs(probe));
+
+    // loading picks the option up right away; reloading would pull the
+    // module out from under running VMs
+    let loaded = is_loaded(probe, module);
+    let reload = loaded && is_unused(probe, module);
+    if reload {
+        actions.push(Action::command("modprobe", &["-r", module]));
+    }
+    if reload || !loaded {
+        actions.push(Action::command("modprobe", &[module]));
+    }
+
+    Some(Remediation {
+        summary: tr_args(
+            "modprobe-fix",
+            &[
+                ("module", module),
+                ("option", option),
+                ("path", MODPROBE_CONF),
+            ],
+        ),
+        actions,
+        reboot_required: loaded && !reload,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    /// The arguments of the modprobe commands of `remediation`.
+    fn modprobe(remediation: &Remediation) -> Vec<String> {
+        remediation
+            .actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Command { program, args } if program == "modprobe" => Some(args.join(" ")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn module_state_from_probe() {
+        let probe = |files: &[(&str, &str)]| {
+            Probe::new(Fixture {
+                files: files
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+                ..Default::default()
+            })
+        };
+
+        let built_in = probe(&[("/sys/module/kvm_intel/parameters/tdx", "N\n")]);
+        assert!(set_module_option(&built_in, "kvm_intel", "tdx=1").is_none());
+
+        let unloaded = set_module_option(&probe(&[]), "kvm_intel", "tdx=1").unwrap();
+        assert_eq!(modprobe(&unloaded), ["kvm_intel"]);
+        assert!(!unloaded.reboot_required);
+
+        let in_use = probe(&[
+            ("/sys/module/kvm_intel/initstate", "live\n"),
+            ("/sys/module/kvm_intel/refcnt", "2\n"),
+        ]);
+        let in_use = set_module_option(&in_use, "kvm_intel", "tdx=1").unwrap();
+        assert!(modprobe(&in_use).is_empty());
+        assert!(in_use.reboot_required);
+
+        let unused = probe(&[
+            ("/sys/module/kvm_intel/initstate", "live\n"),
+            ("/sys/module/kvm_intel/refcnt", "0\n"),
+        ]);
+        let unused = set_module_option(&unused, "kvm_intel", "tdx=1").unwrap();
+        assert_eq!(modprobe(&unused), ["-r kvm_intel", "kvm_intel"]);
+        assert!(!unused.reboot_required);
+    }
+
+    #[test]
+    fn option_set() {
+        assert_eq!(
+            with_option("", "kvm_intel", "tdx=1").as_deref(),
+            Some("options kvm_intel tdx=1\n")
+        );
+        assert_eq!(
+            with_option("options kvm_intel tdx=1 nested=0\n", "kvm_intel", "tdx=1"),
+            None
+        );
+    }
+
+    #[test]
+    fn option_replaced() {
+        assert_eq!(
+            with_option(
+                "# TDX\noptions kvm_intel nested=0 tdx=0\noptions kvm ignore_msrs=1\n",
+                "kvm_intel",
+                "tdx=1"
+            )
+            .as_deref(),
+            Some("# TDX\noptions kvm_intel nested=0\noptions kvm ignore_msrs=1\noptions kvm_intel tdx=1\n")
+        );
+        assert_eq!(
+            with_option("options kvm_intel tdx=0\n", "kvm_intel", "tdx=1").as_deref(),
+            Some("options kvm_intel tdx=1\n")
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! What the host knows about the loaded TDX module, for `tdxhost module
+//! info`.
+
+use serde::Serialize;
+
+use crate::check::Probe;
+use crate::checks::{
+    check_tdx_module, seamldr_version, tdx_cmrs, tdx_features0, tdx_log_lines, TdxModuleRelease,
+    TDX_MODULE_FEATURES,
+};
+
+/// A physical address range, end exclusive.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// KeyIDs the BIOS split between MKTME and TDX.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyIds {
+    pub mktme: u32,
+    pub tdx: u32,
+}
+
+/// Everything known about the loaded TDX module. Fields the kernel does not
+/// report are None or empty.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleInfo {
+    pub initialized: bool,
+    /// e.g. "1.5.6"
+    pub version: Option<String>,
+    pub build_num: Option<String>,
+    pub build_date: Option<String>,
+    pub attributes: Option<String>,
+    /// TDX_FEATURES0, as hex
+    pub features0: Option<String>,
+    /// Names of the optional features the module was built with
+    pub features: Vec<String>,
+    pub seamldr_version: Option<String>,
+    /// Convertible memory regions
+    pub cmrs: Vec<MemoryRange>,
+    pub keyids: Option<KeyIds>,
+    /// Memory the kernel allocated for the PAMTs of the TDMRs
+    pub pamt_kib: Option<u64>,
+}
+
+impl ModuleInfo {
+    pub fn collect(probe: &Probe) -> Self {
+        let release = TdxModuleRelease::detect(probe);
+        let features0 = tdx_features0(probe);
+        // IA32_MKTME_KEYID_PARTITIONING
+        let keyids = probe.read_msr(0x87).ok().map(|v| KeyIds {
+            mktme: v as u32,
+            tdx: (v >> 32) as u32,
+        });
+        // e.g. "virt/tdx: 262400 KB allocated for PAMT"
+        let pamt_kib = tdx_log_lines(probe).iter().find_map(|l| {
+            let (_, message) = l.split_once("virt/tdx:")?;
+            let (size, _) = message.trim().split_once(" KB allocated for PAMT")?;
+            size.trim().parse().ok()
+        });
+
+        Self {
+            initialized: matches!(check_tdx_module(probe), Ok(Some(_))),
+            version: release.as_ref().map(|r| {
+                let (major, minor, update) = r.release();
+                format!("{}.{}.{}", major, minor, update)
+            }),
+            build_num: release.as_ref().and_then(|r| r.build_num.clone()),
+            build_date: release.as_ref().and_then(|r| r.build_date.clone()),
+            attributes: release.and_then(|r| r.attributes),
+            features0: features0.map(|f| format!("{:#x}", f)),
+            features: TDX_MODULE_FEATURES
+                .iter()
+                .filter(|f| features0.is_some_and(|bits| bits & (1 << f.bit) != 0))
+                .map(|f| f.name.to_string())
+                .collect(),
+            seamldr_version: seamldr_version(probe).map(|(version, _)| version),
+            cmrs: tdx_cmrs(probe)
+                .into_iter()
+                .map(|(start, end)| MemoryRange { start, end })
+                .collect(),
+            keyids,
+            pamt_kib,
+        }
+    }
+}
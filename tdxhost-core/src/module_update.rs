@@ -0,0 +1,67 @@
+//! Runtime TDX module updates through the kernel's firmware upload
+//! interface.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::error::{Result, TdxHostError};
+
+/// Firmware upload interface of kernels that can update the TDX module at
+/// runtime.
+pub const FW_UPLOAD_DIR: &str = "/sys/class/firmware/tdx_module";
+
+/// How long installing the module may take before the upload is cancelled.
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+fn write(name: &str, contents: &[u8]) -> Result<()> {
+    let path = Path::new(FW_UPLOAD_DIR).join(name);
+    std::fs::write(&path, contents).map_err(|e| TdxHostError::io(&path, e))
+}
+
+fn read(name: &str) -> Result<String> {
+    let path = Path::new(FW_UPLOAD_DIR).join(name);
+    std::fs::read_to_string(&path)
+        .map(|contents| contents.trim().to_string())
+        .map_err(|e| TdxHostError::io(&path, e))
+}
+
+/// Hand the module in `image` to the kernel and wait until it is installed.
+///
+/// The kernel only replaces the running module once the new one is in
+/// place, so on failure the previous module keeps running.
+pub fn update(image: &Path) -> Result<()> {
+    let data = std::fs::read(image).map_err(|e| TdxHostError::io(image, e))?;
+    if data.is_empty() {
+        return Err(TdxHostError::ModuleUpdate(format!(
+            "{} is empty",
+            image.display()
+        )));
+    }
+
+    write("loading", b"1")?;
+    if let Err(e) = write("data", &data) {
+        // -1 aborts the upload
+        let _ = write("loading", b"-1");
+        return Err(e);
+    }
+    write("loading", b"0")?;
+
+    // preparing, transferring and programming, then idle again
+    let start = Instant::now();
+    while read("status")? != "idle" {
+        if start.elapsed() > TIMEOUT {
+            let _ = write("cancel", b"1");
+            return Err(TdxHostError::ModuleUpdate(format!(
+                "timed out after {}s",
+                TIMEOUT.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+
+    // e.g. "programming:hw-error", empty when the update succeeded
+    match read("error")? {
+        error if error.is_empty() => Ok(()),
+        error => Err(TdxHostError::ModuleUpdate(error)),
+    }
+}
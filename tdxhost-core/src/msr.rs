@@ -0,0 +1,210 @@
+//! The model-specific registers TDX depends on, and their fields.
+
+use serde::Serialize;
+
+use crate::check::Probe;
+use crate::error::{Result, TdxHostError};
+
+/// A register and its fields, each by its lowest and highest bit.
+pub struct Register {
+    pub index: u32,
+    pub name: &'static str,
+    pub fields: &'static [(u32, u32, &'static str)],
+}
+
+/// Registers of the BIOS settings TDX needs, as defined by the SDM and the
+/// TDX CPU architecture specification.
+pub const REGISTERS: [Register; 8] = [
+    Register {
+        index: 0x3a,
+        name: "IA32_FEATURE_CONTROL",
+        fields: &[
+            (0, 0, "Lock"),
+            (1, 1, "VMX inside SMX"),
+            (2, 2, "VMX outside SMX"),
+            (8, 14, "SENTER local functions"),
+            (15, 15, "SENTER global enable"),
+            (17, 17, "SGX launch control enable"),
+            (18, 18, "SGX global enable"),
+            (20, 20, "LMCE on"),
+        ],
+    },
+    Register {
+        index: 0x87,
+        name: "IA32_MKTME_KEYID_PARTITIONING",
+        fields: &[(0, 31, "MKTME KeyIDs"), (32, 63, "TDX KeyIDs")],
+    },
+    Register {
+        index: 0x8b,
+        name: "IA32_BIOS_SIGN_ID",
+        fields: &[(32, 63, "Microcode revision")],
+    },
+    Register {
+        index: 0xfe,
+        name: "IA32_MTRRCAP",
+        fields: &[
+            (0, 7, "Variable range MTRRs"),
+            (8, 8, "Fixed range MTRRs"),
+            (10, 10, "Write combining"),
+            (11, 11, "SMRR"),
+            (15, 15, "SEAMRR"),
+        ],
+    },
+    Register {
+        index: 0x981,
+        name: "IA32_TME_CAPABILITY",
+        fields: &[
+            (0, 0, "AES-XTS-128"),
+            (1, 1, "AES-XTS-128 with integrity"),
+            (2, 2, "AES-XTS-256"),
+            (31, 31, "TME bypass supported"),
+            (32, 35, "Max KeyID bits"),
+            (36, 50, "Max keys"),
+        ],
+    },
+    Register {
+        index: 0x982,
+        name: "IA32_TME_ACTIVATE",
+        fields: &[
+            (0, 0, "Lock"),
+            (1, 1, "TME enable"),
+            (2, 2, "Key select"),
+            (3, 3, "Save key for standby"),
+            (4, 7, "TME policy"),
+            (31, 31, "TME bypass enable"),
+            (32, 35, "MKTME KeyID bits"),
+            (36, 39, "TDX KeyID bits"),
+            (48, 63, "MKTME algorithms"),
+        ],
+    },
+    Register {
+        index: 0x1400,
+        name: "IA32_SEAMRR_PHYS_BASE",
+        fields: &[(3, 3, "Configured"), (25, 51, "Base (32 MiB units)")],
+    },
+    Register {
+        index: 0x1401,
+        name: "IA32_SEAMRR_PHYS_MASK",
+        fields: &[
+            (10, 10, "Lock"),
+            (11, 11, "Valid"),
+            (25, 51, "Mask (32 MiB units)"),
+        ],
+    },
+];
+
+/// A known register by its index.
+pub fn register(index: u32) -> Option<&'static Register> {
+    REGISTERS.iter().find(|r| r.index == index)
+}
+
+/// The index of an MSR given in hex, e.g. "0x982", in decimal or by name,
+/// e.g. "IA32_TME_ACTIVATE".
+pub fn parse(msr: &str) -> Option<u32> {
+    let msr = msr.trim();
+    if let Some(hex) = msr.strip_prefix("0x").or_else(|| msr.strip_prefix("0X")) {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+    msr.parse().ok().or_else(|| {
+        REGISTERS
+            .iter()
+            .find(|r| r.name.eq_ignore_ascii_case(msr))
+            .map(|r| r.index)
+    })
+}
+
+/// A field of a register value.
+#[derive(Debug, Clone, Serialize)]
+pub struct Field {
+    pub name: &'static str,
+    /// Lowest and highest bit of the field
+    pub bits: (u32, u32),
+    pub value: u64,
+}
+
+/// The fields of `value` of the register `index`, empty if it is unknown.
+pub fn decode(index: u32, value: u64) -> Vec<Field> {
+    let Some(register) = register(index) else {
+        return vec![];
+    };
+    register
+        .fields
+        .iter()
+        .map(|&(low, high, name)| {
+            let width = high - low + 1;
+            let mask = if width == 64 {
+                u64::MAX
+            } else {
+                (1 << width) - 1
+            };
+            Field {
+                name,
+                bits: (low, high),
+                value: (value >> low) & mask,
+            }
+        })
+        .collect()
+}
+
+/// A value of an MSR and the CPUs that hold it.
+#[derive(Debug, Clone, Serialize)]
+pub struct Value {
+    pub cpus: Vec<u16>,
+    pub value: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reading {
+    pub index: u32,
+    pub name: Option<&'static str>,
+    /// Distinct values across the CPUs read
+    pub values: Vec<Value>,
+}
+
+/// Read an MSR on `cpus`, decoding its fields if `decode` is set and the
+/// register is known.
+pub fn read(probe: &Probe, index: u32, cpus: &[u16], decode: bool) -> Result<Reading> {
+    let mut values: Vec<Value> = vec![];
+    for &cpu in cpus {
+        let value = probe.read_msr_on(cpu, index)?;
+        match values.iter_mut().find(|v| v.value == value) {
+            Some(v) => v.cpus.push(cpu),
+            None => values.push(Value {
+                cpus: vec![cpu],
+                value,
+                fields: if decode {
+                    self::decode(index, value)
+                } else {
+                    vec![]
+                },
+            }),
+        }
+    }
+    Ok(Reading {
+        index,
+        name: register(index).map(|r| r.name),
+        values,
+    })
+}
+
+/// Read every known register on `cpus`, leaving out the ones that cannot
+/// be read, e.g. because the CPU does not implement them, unless none can.
+pub fn dump(probe: &Probe, cpus: &[u16], decode: bool) -> Result<Vec<Reading>> {
+    let mut readings = vec![];
+    let mut first_error = None;
+    for register in &REGISTERS {
+        match read(probe, register.index, cpus, decode) {
+            Ok(reading) => readings.push(reading),
+            Err(e @ TdxHostError::PermissionDenied(_)) => return Err(e),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match first_error {
+        Some(e) if readings.is_empty() => Err(e),
+        _ => Ok(readings),
+    }
+}
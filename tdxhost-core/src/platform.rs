@@ -0,0 +1,591 @@
+use serde::Deserialize;
+use std::arch::x86_64::CpuidResult;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Write};
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::error::{Result, TdxHostError};
+use crate::kvm::{self, PrivateMemory, TdxCapabilities};
+
+/// Device node of the msr kernel module for CPU 0
+const MSR_DEVICE: &str = "/dev/cpu/0/msr";
+const KMSG: &str = "/dev/kmsg";
+/// Bit of CAP_SYSLOG in the capability sets of /proc/self/status
+const CAP_SYSLOG: u32 = 34;
+
+pub trait MsrAccess {
+    /// Read an MSR on a logical CPU.
+    fn read_msr(&self, cpu: u16, index: u32) -> Result<u64>;
+}
+
+pub trait CpuidAccess {
+    fn cpuid_count(&self, leaf: u32, subleaf: u32) -> CpuidResult;
+
+    /// Execute CPUID with subleaf 0.
+    fn cpuid(&self, leaf: u32) -> CpuidResult {
+        self.cpuid_count(leaf, 0)
+    }
+}
+
+/// Reads of regular files, sysfs and procfs.
+pub trait FileAccess {
+    fn read_file(&self, path: &str) -> std::io::Result<String>;
+
+    /// Read a file that may not be text, e.g. a firmware image.
+    fn read_bytes(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        self.read_file(path).map(String::into_bytes)
+    }
+
+    fn exists(&self, path: &str) -> bool;
+
+    /// Names of the entries of a directory, sorted.
+    fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>>;
+
+    /// Last modification time of a file.
+    fn modified(&self, path: &str) -> std::io::Result<SystemTime>;
+
+    /// Result of the KVM_GET_API_VERSION ioctl on /dev/kvm, negative when
+    /// the ioctl failed.
+    fn kvm_api_version(&self) -> std::io::Result<i32>;
+
+    /// Bitmap of the VM types KVM creates.
+    fn kvm_vm_types(&self) -> std::io::Result<u64>;
+
+    /// Result of KVM_TDX_CAPABILITIES on a new TD.
+    fn kvm_tdx_capabilities(&self) -> std::io::Result<TdxCapabilities>;
+
+    /// What KVM offers for the private memory of TDs.
+    fn kvm_private_memory(&self) -> std::io::Result<PrivateMemory>;
+}
+
+pub trait KernelLog {
+    /// The messages in the kernel ring buffer, one per line.
+    fn kernel_log(&self) -> Result<String>;
+}
+
+pub trait CommandRunner {
+    /// Run a command to completion and return its standard output, failing
+    /// when it exits unsuccessfully.
+    fn output(&self, program: &str, args: &[&str]) -> std::io::Result<Vec<u8>>;
+
+    /// Like `output`, writing `input` to the command's standard input, e.g.
+    /// secrets kept out of its command line.
+    fn output_with_input(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> std::io::Result<Vec<u8>>;
+
+    /// Whether the plugins in `plugin::PLUGIN_DIR` run here; they see the
+    /// machine tdxhost runs on, not a simulated one.
+    fn runs_plugins(&self) -> bool;
+
+    /// Remove a file a command left behind.
+    fn remove_file(&self, path: &str) -> std::io::Result<()>;
+}
+
+pub trait Privileges {
+    /// Whether MSRs and the kernel log can be read.
+    fn privileged(&self) -> bool;
+}
+
+/// Everything the checks need from the host.
+pub trait Platform:
+    MsrAccess + CpuidAccess + FileAccess + KernelLog + CommandRunner + Privileges + Send + Sync
+{
+}
+
+impl<T> Platform for T where
+    T: MsrAccess + CpuidAccess + FileAccess + KernelLog + CommandRunner + Privileges + Send + Sync
+{
+}
+
+/// The machine tdxhost runs on.
+#[derive(Debug)]
+pub struct Host {
+    load_msr_module: bool,
+    /// Whether `modprobe msr` was attempted and succeeded
+    msr_module_loaded: OnceLock<bool>,
+    /// MSR device nodes by CPU, opened on first use
+    msr_devices: Mutex<HashMap<u16, File>>,
+}
+
+impl Host {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            load_msr_module: config.load_msr_module,
+            msr_module_loaded: OnceLock::new(),
+            msr_devices: Mutex::default(),
+        }
+    }
+
+    /// Load the msr module on first use, if allowed and privileged.
+    fn ensure_msr_module(&self) -> bool {
+        if Path::new(MSR_DEVICE).exists() {
+            return true;
+        }
+
+        *self.msr_module_loaded.get_or_init(|| {
+            self.load_msr_module
+                && self.privileged()
+                && Command::new("modprobe")
+                    .arg("msr")
+                    .output()
+                    .is_ok_and(|o| o.status.success())
+        })
+    }
+}
+
+impl MsrAccess for Host {
+    fn read_msr(&self, cpu: u16, index: u32) -> Result<u64> {
+        if !self.ensure_msr_module() {
+            let reason = if self.load_msr_module && self.privileged() {
+                format!("{} is missing and `modprobe msr` failed", MSR_DEVICE)
+            } else {
+                format!(
+                    "{} is missing, load the msr kernel module with `modprobe msr`",
+                    MSR_DEVICE
+                )
+            };
+            return Err(TdxHostError::MsrUnavailable { index, reason });
+        }
+
+        let mut devices = self.msr_devices.lock().unwrap();
+        let device = match devices.entry(cpu) {
+            Entry::Occupied(e) => e.into_mut(),
+            Entry::Vacant(e) => {
+                let path = format!("/dev/cpu/{}/msr", cpu);
+                e.insert(File::open(&path).map_err(|err| msr_error(index, &path, err))?)
+            }
+        };
+
+        let mut value = [0; 8];
+        device
+            .read_exact_at(&mut value, index.into())
+            .map_err(|e| msr_error(index, &format!("/dev/cpu/{}/msr", cpu), e))?;
+        Ok(u64::from_ne_bytes(value))
+    }
+}
+
+fn msr_error(index: u32, path: &str, e: Error) -> TdxHostError {
+    match e.kind() {
+        ErrorKind::PermissionDenied => TdxHostError::PermissionDenied(format!("{}: {}", path, e)),
+        ErrorKind::NotFound => TdxHostError::MsrUnavailable {
+            index,
+            reason: format!("{} does not exist", path),
+        },
+        // rdmsr faults on registers the CPU does not implement
+        _ if e.raw_os_error() == Some(libc::EIO) => {
+            TdxHostError::UnsupportedPlatform(format!("MSR {:#x} is not implemented", index))
+        }
+        _ => TdxHostError::MsrUnavailable {
+            index,
+            reason: e.to_string(),
+        },
+    }
+}
+
+impl CpuidAccess for Host {
+    fn cpuid_count(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        std::arch::x86_64::__cpuid_count(leaf, subleaf)
+    }
+}
+
+impl FileAccess for Host {
+    fn read_file(&self, path: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn read_bytes(&self, path: &str) -> std::io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        Path::new(path).exists()
+    }
+
+    fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        let mut names = std::fs::read_dir(path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<std::io::Result<Vec<String>>>()?;
+        names.sort();
+        Ok(names)
+    }
+
+    fn modified(&self, path: &str) -> std::io::Result<SystemTime> {
+        std::fs::metadata(path)?.modified()
+    }
+
+    fn kvm_api_version(&self) -> std::io::Result<i32> {
+        use std::os::fd::AsRawFd;
+
+        let fd = std::fs::File::open("/dev/kvm")?;
+        Ok(unsafe { libc::ioctl(fd.as_raw_fd(), 0xAE00, 0) })
+    }
+
+    fn kvm_vm_types(&self) -> std::io::Result<u64> {
+        kvm::vm_types()
+    }
+
+    fn kvm_tdx_capabilities(&self) -> std::io::Result<TdxCapabilities> {
+        kvm::tdx_capabilities()
+    }
+
+    fn kvm_private_memory(&self) -> std::io::Result<PrivateMemory> {
+        kvm::private_memory()
+    }
+}
+
+/// Why /dev/kmsg may not be opened.
+fn kmsg_denied() -> String {
+    let cap_syslog = std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| {
+            let caps = status.lines().find_map(|l| l.strip_prefix("CapEff:"))?;
+            u64::from_str_radix(caps.trim(), 16).ok()
+        })
+        .is_some_and(|caps| caps & (1 << CAP_SYSLOG) != 0);
+    if cap_syslog {
+        return format!("{} may not be opened", KMSG);
+    }
+
+    let restricted =
+        std::fs::read_to_string("/proc/sys/kernel/dmesg_restrict").is_ok_and(|r| r.trim() == "1");
+    if restricted {
+        format!(
+            "reading {} needs CAP_SYSLOG as kernel.dmesg_restrict is set, the process lacks it",
+            KMSG
+        )
+    } else {
+        format!("reading {} needs CAP_SYSLOG, the process lacks it", KMSG)
+    }
+}
+
+impl KernelLog for Host {
+    fn kernel_log(&self) -> Result<String> {
+        use std::io::Read;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut kmsg = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(KMSG)
+            .map_err(|e| match e.kind() {
+                ErrorKind::PermissionDenied => TdxHostError::PermissionDenied(kmsg_denied()),
+                ErrorKind::NotFound => {
+                    TdxHostError::UnsupportedPlatform(format!("{} does not exist", KMSG))
+                }
+                _ => TdxHostError::io(Path::new(KMSG), e),
+            })?;
+
+        // every read returns one "prio,seq,time,flags;message" record,
+        // followed by indented key=value lines
+        let mut log = String::new();
+        let mut record = vec![0; 8192];
+        loop {
+            match kmsg.read(&mut record) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let record = String::from_utf8_lossy(&record[..n]);
+                    if let Some((_, message)) = record.split_once(';') {
+                        log += message.lines().next().unwrap_or_default();
+                        log.push('\n');
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                // the record was overwritten before it could be read
+                Err(e) if e.raw_os_error() == Some(libc::EPIPE) => continue,
+                Err(e) => return Err(TdxHostError::io(Path::new(KMSG), e)),
+            }
+        }
+
+        Ok(log)
+    }
+}
+
+fn command_output(program: &str, output: Output) -> std::io::Result<Vec<u8>> {
+    if !output.status.success() {
+        return Err(Error::other(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+impl CommandRunner for Host {
+    fn output(&self, program: &str, args: &[&str]) -> std::io::Result<Vec<u8>> {
+        command_output(program, Command::new(program).args(args).output()?)
+    }
+
+    fn output_with_input(
+        &self,
+        program: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> std::io::Result<Vec<u8>> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        // written while the output is read, so a command answering before it
+        // read all of its input cannot fill its pipes and block on us
+        let output = std::thread::scope(|scope| {
+            let writer = scope.spawn(move || {
+                // dropping stdin closes it, so the command sees the end of
+                // the input
+                match stdin.write_all(input) {
+                    // the command need not read all of its input
+                    Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+                    res => res,
+                }
+            });
+            let output = child.wait_with_output();
+            writer.join().unwrap().and(output)
+        })?;
+        command_output(program, output)
+    }
+
+    fn runs_plugins(&self) -> bool {
+        true
+    }
+
+    fn remove_file(&self, path: &str) -> std::io::Result<()> {
+        std::fs::remove_file(path)
+    }
+}
+
+impl Privileges for Host {
+    fn privileged(&self) -> bool {
+        unsafe { libc::geteuid() == 0 }
+    }
+}
+
+/// An in-memory host, e.g. loaded from a JSON fixture for `--simulate`:
+///
+/// ```json
+/// {
+///   "msrs": { "0x3a": 262149 },
+///   "cpu_msrs": { "1": { "0x3a": 0 } },
+///   "cpuid": { "0x0": [27, 1970169159, 1818588270, 1231384169] },
+///   "cpuid_subleaves": { "0x12": { "2": [1, 0, 268435457, 0] } },
+///   "files": { "/etc/os-release": "PRETTY_NAME=\"CentOS Stream 9\"\n" },
+///   "modified": { "/etc/os-release": 1700000000 },
+///   "kernel_log": "virt/tdx: module initialized\n",
+///   "kvm_api_version": 12,
+///   "kvm_vm_types": 33,
+///   "kvm_tdx_capabilities": { "supported_attrs": 268435457, "supported_xfam": 393959, "cpuid": [] },
+///   "kvm_private_memory": { "guest_memfd": true, "user_memory2": true, "memory_attributes": 8, "td": true },
+///   "unprivileged": false
+/// }
+/// ```
+///
+/// MSRs hold the same value on every CPU unless `cpu_msrs` overrides them for
+/// a CPU. CPUID leaves hold eax, ebx, ecx and edx of subleaf 0, other
+/// subleaves are listed in `cpuid_subleaves`. `modified` holds the
+/// modification times of files in seconds since the epoch. Directories hold
+/// the files listed under them. Anything not listed is missing on the
+/// simulated host. `unprivileged` simulates running as a normal user.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Fixture {
+    pub msrs: HashMap<String, u64>,
+    pub cpu_msrs: HashMap<u16, HashMap<String, u64>>,
+    pub cpuid: HashMap<String, [u32; 4]>,
+    pub cpuid_subleaves: HashMap<String, HashMap<u32, [u32; 4]>>,
+    pub files: HashMap<String, String>,
+    pub modified: HashMap<String, u64>,
+    pub kernel_log: Option<String>,
+    pub commands: HashMap<String, String>,
+    pub kvm_api_version: Option<i32>,
+    pub kvm_vm_types: Option<u64>,
+    pub kvm_tdx_capabilities: Option<TdxCapabilities>,
+    pub kvm_private_memory: Option<PrivateMemory>,
+    pub unprivileged: bool,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| TdxHostError::io(path, e))?;
+        serde_json::from_str(&contents).map_err(|e| TdxHostError::io(path, e.into()))
+    }
+
+    /// Entries of a register map, whose keys may be written in hex.
+    fn lookup<T>(map: &HashMap<String, T>, index: u32) -> Option<&T> {
+        map.iter()
+            .find(|(k, _)| {
+                let parsed = match k.strip_prefix("0x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => k.parse().ok(),
+                };
+                parsed == Some(index)
+            })
+            .map(|(_, v)| v)
+    }
+}
+
+fn not_found(what: &str) -> Error {
+    Error::new(
+        ErrorKind::NotFound,
+        format!("{} is not in the fixture", what),
+    )
+}
+
+impl MsrAccess for Fixture {
+    fn read_msr(&self, cpu: u16, index: u32) -> Result<u64> {
+        self.cpu_msrs
+            .get(&cpu)
+            .and_then(|msrs| Fixture::lookup(msrs, index))
+            .or_else(|| Fixture::lookup(&self.msrs, index))
+            .copied()
+            .ok_or_else(|| TdxHostError::MsrUnavailable {
+                index,
+                reason: "not in the fixture".to_string(),
+            })
+    }
+}
+
+impl CpuidAccess for Fixture {
+    fn cpuid_count(&self, leaf: u32, subleaf: u32) -> CpuidResult {
+        let [eax, ebx, ecx, edx] = Fixture::lookup(&self.cpuid_subleaves, leaf)
+            .and_then(|subleaves| subleaves.get(&subleaf))
+            .or_else(|| match subleaf {
+                0 => Fixture::lookup(&self.cpuid, leaf),
+                _ => None,
+            })
+            .copied()
+            .unwrap_or_default();
+        CpuidResult { eax, ebx, ecx, edx }
+    }
+}
+
+impl FileAccess for Fixture {
+    fn read_file(&self, path: &str) -> std::io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| not_found(path))
+    }
+
+    fn exists(&self, path: &str) -> bool {
+        let dir = format!("{}/", path.trim_end_matches('/'));
+        self.files.keys().any(|f| f == path || f.starts_with(&dir))
+    }
+
+    fn read_dir(&self, path: &str) -> std::io::Result<Vec<String>> {
+        let dir = format!("{}/", path.trim_end_matches('/'));
+        let mut names: Vec<String> = self
+            .files
+            .keys()
+            .filter_map(|f| f.strip_prefix(&dir)?.split('/').next())
+            .map(str::to_owned)
+            .collect();
+        if names.is_empty() {
+            return Err(not_found(path));
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn modified(&self, path: &str) -> std::io::Result<SystemTime> {
+        self.modified
+            .get(path)
+            .map(|&secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+            .ok_or_else(|| not_found(path))
+    }
+
+    fn kvm_api_version(&self) -> std::io::Result<i32> {
+        self.kvm_api_version.ok_or_else(|| not_found("/dev/kvm"))
+    }
+
+    fn kvm_vm_types(&self) -> std::io::Result<u64> {
+        self.kvm_vm_types.ok_or_else(|| not_found("/dev/kvm"))
+    }
+
+    fn kvm_tdx_capabilities(&self) -> std::io::Result<TdxCapabilities> {
+        self.kvm_tdx_capabilities
+            .clone()
+            .ok_or_else(|| not_found("/dev/kvm"))
+    }
+
+    fn kvm_private_memory(&self) -> std::io::Result<PrivateMemory> {
+        self.kvm_private_memory
+            .clone()
+            .ok_or_else(|| not_found("/dev/kvm"))
+    }
+}
+
+impl KernelLog for Fixture {
+    fn kernel_log(&self) -> Result<String> {
+        Ok(self.kernel_log.clone().ok_or_else(|| not_found(KMSG))?)
+    }
+}
+
+impl Privileges for Fixture {
+    fn privileged(&self) -> bool {
+        !self.unprivileged
+    }
+}
+
+impl CommandRunner for Fixture {
+    fn output(&self, program: &str, args: &[&str]) -> std::io::Result<Vec<u8>> {
+        let command = std::iter::once(program)
+            .chain(args.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.commands
+            .get(&command)
+            .map(|out| out.clone().into_bytes())
+            .ok_or_else(|| not_found(&command))
+    }
+
+    /// The input is not part of the command's key, fixtures hold no
+    /// secrets.
+    fn output_with_input(
+        &self,
+        program: &str,
+        args: &[&str],
+        _input: &[u8],
+    ) -> std::io::Result<Vec<u8>> {
+        self.output(program, args)
+    }
+
+    fn runs_plugins(&self) -> bool {
+        false
+    }
+
+    /// The fixture stays as it was loaded, its files are read again.
+    fn remove_file(&self, _path: &str) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_input() {
+        let host = Host::new(&Config::default());
+        // more than a pipe holds, echoed back while it is written
+        let input = vec![b'x'; 1 << 20];
+        assert_eq!(host.output_with_input("cat", &[], &input).unwrap(), input);
+        // the rest of the input is not read
+        assert_eq!(
+            host.output_with_input("head", &["-c", "1"], &input)
+                .unwrap(),
+            b"x"
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::check::{Check, CheckResult, Probe, Tag, TestState};
+use crate::error::{Result, TdxHostError};
+use crate::i18n::tr_args;
+use crate::wasm;
+
+pub const PLUGIN_DIR: &str = "/etc/tdxhost/checks.d";
+const PROTOCOL_VERSION: u32 = 1;
+/// How long an executable may take to answer, so a hung plugin cannot hang
+/// the run.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct Request {
+    version: u32,
+    command: &'static str,
+}
+
+#[derive(Deserialize)]
+struct Description {
+    id: String,
+    name: String,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    docs_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Outcome {
+    pub state: TestState,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub evidence: Vec<String>,
+    #[serde(default)]
+    pub hints: Vec<String>,
+}
+
+/// A site specific check provided by an executable or a WASM module (`.wasm`)
+/// in `PLUGIN_DIR`. Plugins run as root, so only root may be able to change
+/// them; they run on the host only, never against a simulated one.
+///
+/// An executable gets a single JSON request on stdin and must answer with a
+/// single JSON document on stdout and exit successfully within `TIMEOUT`,
+/// or it is killed. A WASM module exports one function per command instead,
+/// see `wasm::call`.
+///
+/// `{"version": 1, "command": "describe"}` is answered with
+/// `{"id": "site.bios_version", "name": "Check BIOS version", "optional": false,
+/// "docs_url": "https://..."}`, docs_url being optional
+///
+/// `{"version": 1, "command": "run"}` is answered with
+/// `{"state": "ok", "action": "...", "reason": "...", "evidence": [], "hints": []}`
+/// where state is one of ok, fail, warning, tbd or skip. Everything but
+/// state may be omitted.
+pub struct Plugin {
+    path: PathBuf,
+    /// Leaked at discovery, check ids live as long as the process
+    pub id: &'static str,
+    pub name: String,
+    pub optional: bool,
+    pub docs_url: Option<String>,
+}
+
+fn exec(path: &Path, command: &'static str) -> Result<Vec<u8>> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| TdxHostError::plugin(path, e))?;
+
+    let request = serde_json::to_vec(&Request {
+        version: PROTOCOL_VERSION,
+        command,
+    })
+    .unwrap();
+    // dropping stdin closes it, so the plugin sees the end of the request
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(&request)
+        .map_err(|e| TdxHostError::plugin(path, e))?;
+
+    // drained while waiting, so a plugin blocked on a full pipe still exits
+    let drain = |mut pipe: Box<dyn Read + Send>| {
+        let out = Arc::new(Mutex::new(vec![]));
+        let buffer = out.clone();
+        let (done, closed) = mpsc::channel::<()>();
+        thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            while let Ok(n @ 1..) = pipe.read(&mut chunk) {
+                buffer.lock().unwrap().extend_from_slice(&chunk[..n]);
+            }
+            let _ = done.send(());
+        });
+        move || {
+            // a process the plugin left behind may hold the pipe open
+            let _ = closed.recv_timeout(Duration::from_secs(1));
+            std::mem::take(&mut *out.lock().unwrap())
+        }
+    };
+    let stdout = drain(Box::new(child.stdout.take().unwrap()));
+    let stderr = drain(Box::new(child.stderr.take().unwrap()));
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        match child
+            .try_wait()
+            .map_err(|e| TdxHostError::plugin(path, e))?
+        {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(TdxHostError::plugin(
+                    path,
+                    format!("did not answer within {} seconds", TIMEOUT.as_secs()),
+                ));
+            }
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    };
+    let (stdout, stderr) = (stdout(), stderr());
+    if !status.success() {
+        return Err(TdxHostError::plugin(
+            path,
+            format!(
+                "exited with {}: {}",
+                status,
+                String::from_utf8_lossy(&stderr).trim()
+            ),
+        ));
+    }
+
+    Ok(stdout)
+}
+
+fn call<T: DeserializeOwned>(path: &Path, command: &'static str, probe: &Probe) -> Result<T> {
+    let response = if is_wasm(path) {
+        wasm::call(path, command, probe)?
+    } else {
+        exec(path, command)?
+    };
+
+    serde_json::from_slice(&response)
+        .map_err(|e| TdxHostError::plugin(path, format!("invalid response: {}", e)))
+}
+
+fn is_wasm(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|e| e == "wasm")
+}
+
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Whether only root can change `path`, which tdxhost runs as root: it and
+/// the directories leading to it must be owned by root and not writable by
+/// group or others, unless sticky like /tmp.
+fn trusted(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    path.ancestors().all(|p| {
+        p.metadata().is_ok_and(|m| {
+            let sticky = m.is_dir() && m.mode() & 0o1000 != 0;
+            m.uid() == 0 && (m.mode() & 0o022 == 0 || sticky && p != path)
+        })
+    })
+}
+
+fn warn_untrusted(path: &Path) {
+    eprintln!(
+        "{}",
+        tr_args("plugin-untrusted", &[("path", &path.display().to_string())])
+    );
+}
+
+/// Plugins in `PLUGIN_DIR`, described on `probe`'s host. `builtin` holds
+/// the ids plugins must not reuse.
+pub fn discover(builtin: &[&str], probe: &Probe) -> Vec<Plugin> {
+    discover_in(Path::new(PLUGIN_DIR), builtin, probe)
+}
+
+fn discover_in(dir: &Path, builtin: &[&str], probe: &Probe) -> Vec<Plugin> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    if !trusted(dir) {
+        warn_untrusted(dir);
+        return vec![];
+    }
+
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_wasm(p) || is_executable(p))
+        .filter(|p| {
+            let trusted = trusted(p);
+            if !trusted {
+                warn_untrusted(p);
+            }
+            trusted
+        })
+        .collect();
+    paths.sort();
+
+    let mut plugins: Vec<Plugin> = vec![];
+    for path in paths {
+        let description = match call::<Description>(&path, "describe", probe) {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    tr_args("plugin-ignored", &[("error", &e.to_string())])
+                );
+                continue;
+            }
+        };
+
+        let duplicate = builtin.contains(&description.id.as_str())
+            || plugins.iter().any(|p| p.id == description.id);
+        if duplicate {
+            eprintln!(
+                "{}",
+                tr_args(
+                    "plugin-duplicate-id",
+                    &[
+                        ("path", &path.display().to_string()),
+                        ("id", &description.id)
+                    ]
+                )
+            );
+            continue;
+        }
+
+        plugins.push(Plugin {
+            path,
+            id: description.id.leak(),
+            name: description.name,
+            optional: description.optional,
+            docs_url: description.docs_url,
+        });
+    }
+
+    plugins
+}
+
+impl Plugin {
+    pub fn run(&self, probe: &Probe) -> Result<Outcome> {
+        call(&self.path, "run", probe)
+    }
+}
+
+impl Check for Plugin {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn description(&self) -> String {
+        self.name.clone()
+    }
+
+    fn optional(&self) -> bool {
+        self.optional
+    }
+
+    fn docs_url(&self) -> Option<String> {
+        self.docs_url.clone()
+    }
+
+    fn tags(&self) -> &[Tag] {
+        &[Tag::Plugin]
+    }
+
+    fn execute(&self, probe: &Probe) -> CheckResult {
+        match self.run(probe) {
+            Ok(outcome) => CheckResult {
+                action: outcome.action.unwrap_or_else(|| self.name.clone()),
+                reason: outcome.reason,
+                state: outcome.state,
+                evidence: outcome.evidence,
+                hints: outcome.hints.iter().map(|h| format!("\t{}", h)).collect(),
+                ..Default::default()
+            },
+            Err(e) => CheckResult {
+                action: self.name.clone(),
+                reason: tr_args("plugin-failed", &[("error", &e.to_string())]),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+    use std::fs;
+
+    const SCRIPT: &str = r#"#!/bin/sh
+read -r request
+case "$request" in
+*describe*) echo '{"id": "site.example", "name": "Check the example"}' ;;
+*) echo '{"state": "warning", "reason": "example"}' ;;
+esac
+"#;
+
+    /// A plugin directory of `mode` holding the example plugin of
+    /// `plugin_mode`.
+    fn plugin_dir(name: &str, mode: u32, plugin_mode: u32) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tdxhost-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let plugin = dir.join("example");
+        fs::write(&plugin, SCRIPT).unwrap();
+        fs::set_permissions(&plugin, fs::Permissions::from_mode(plugin_mode)).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(mode)).unwrap();
+        dir
+    }
+
+    #[test]
+    fn writable_plugins_ignored() {
+        let probe = Probe::new(Fixture::default());
+        let dir = plugin_dir("group-writable", 0o755, 0o775);
+        assert!(discover_in(&dir, &[], &probe).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let dir = plugin_dir("world-writable", 0o755, 0o757);
+        assert!(discover_in(&dir, &[], &probe).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let dir = plugin_dir("writable-dir", 0o777, 0o755);
+        assert!(discover_in(&dir, &[], &probe).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn root_owned_plugins_run() {
+        // only root can own the plugins it trusts
+        if unsafe { libc::geteuid() } != 0 {
+            return;
+        }
+        let probe = Probe::new(Fixture::default());
+        let dir = plugin_dir("trusted", 0o755, 0o755);
+
+        let plugins = discover_in(&dir, &[], &probe);
+        assert_eq!(plugins.len(), 1);
+        assert_eq!(plugins[0].id, "site.example");
+        assert_eq!(plugins[0].name, "Check the example");
+        let outcome = plugins[0].run(&probe).unwrap();
+        assert_eq!(outcome.state, TestState::Warning);
+        assert_eq!(outcome.reason, "example");
+
+        assert!(discover_in(&dir, &["site.example"], &probe).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,237 @@
+//! BIOS settings read from the BMC over Redfish, for the BIOS checks the
+//! host itself cannot verify.
+
+use serde_json::Value;
+use std::io::ErrorKind;
+
+use crate::bios::{BiosAttribute, BiosSettingsProvider};
+use crate::check::Probe;
+use crate::error::{Result, TdxHostError};
+
+/// A BMC's Redfish service.
+#[derive(Debug, Clone)]
+pub struct Redfish {
+    /// Scheme and host of the service, e.g. "https://bmc.example.com"
+    origin: String,
+    user: String,
+    password: String,
+    /// Accept a self-signed certificate, as most BMCs have
+    insecure: bool,
+}
+
+fn invalid(what: impl ToString) -> TdxHostError {
+    std::io::Error::new(ErrorKind::InvalidData, what.to_string()).into()
+}
+
+/// The "@odata.id" of a resource link.
+fn link(value: &Value) -> Option<&str> {
+    value["@odata.id"].as_str()
+}
+
+impl Redfish {
+    /// The service at `url`, e.g. "https://bmc/" or
+    /// "https://bmc/redfish/v1".
+    pub fn new(url: &str, user: &str, password: &str, insecure: bool) -> Result<Self> {
+        let (scheme, rest) = url
+            .split_once("://")
+            .filter(|(scheme, _)| matches!(*scheme, "http" | "https"))
+            .ok_or_else(|| invalid(format!("{} is not an http(s) URL", url)))?;
+        let host = rest.split('/').next().unwrap_or_default();
+        if host.is_empty() {
+            return Err(invalid(format!("{} has no host", url)));
+        }
+        Ok(Self {
+            origin: format!("{}://{}", scheme, host),
+            user: user.to_string(),
+            password: password.to_string(),
+            insecure,
+        })
+    }
+
+    /// GET the resource at `path`, e.g. "/redfish/v1/Systems".
+    fn get(&self, probe: &Probe, path: &str) -> Result<Value> {
+        let url = format!("{}{}", self.origin, path);
+        // the credentials go through a config on stdin, as the command line
+        // is visible to every user
+        let config = format!(
+            "user = \"{}\"\n",
+            format!("{}:{}", self.user, self.password)
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+        );
+        let mut curl = vec![
+            "-sS",
+            "-f",
+            "--max-time",
+            "10",
+            "-H",
+            "Accept: application/json",
+            "-K",
+            "-",
+        ];
+        if self.insecure {
+            curl.push("-k");
+        }
+        curl.push(&url);
+        let body = probe
+            .output_with_input("curl", &curl, config.as_bytes())
+            .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", url, e)))?;
+        serde_json::from_slice(&body).map_err(|e| invalid(format!("{}: {}", url, e)))
+    }
+
+    /// The attribute registry `id`, from where the BMC publishes it.
+    fn registry(&self, probe: &Probe, id: &str) -> Result<Value> {
+        let file = self.get(probe, &format!("/redfish/v1/Registries/{}", id))?;
+        let locations = file["Location"].as_array().cloned().unwrap_or_default();
+        let location = locations
+            .iter()
+            .find(|l| l["Language"].as_str().is_some_and(|l| l.starts_with("en")))
+            .or(locations.first())
+            .and_then(|l| l["Uri"].as_str())
+            .ok_or_else(|| invalid(format!("registry {} has no location", id)))?;
+        self.get(probe, location)
+    }
+}
+
+impl BiosSettingsProvider for Redfish {
+    /// The current BIOS attributes of the first system, with their display
+    /// names if the BMC publishes its attribute registry.
+    fn bios_attributes(&self, probe: &Probe) -> Result<Vec<BiosAttribute>> {
+        let systems = self.get(probe, "/redfish/v1/Systems")?;
+        let system = link(&systems["Members"][0])
+            .ok_or_else(|| invalid("the BMC lists no systems"))?
+            .to_string();
+        let bios = match link(&self.get(probe, &system)?["Bios"]) {
+            Some(bios) => bios.to_string(),
+            None => format!("{}/Bios", system),
+        };
+        let bios = self.get(probe, &bios)?;
+        let attributes = bios["Attributes"]
+            .as_object()
+            .ok_or_else(|| invalid("the BMC exposes no BIOS attributes"))?;
+
+        // a registry that cannot be read leaves the attributes' names
+        let registry = bios["AttributeRegistry"]
+            .as_str()
+            .and_then(|id| self.registry(probe, id).ok());
+        let entries = registry
+            .as_ref()
+            .and_then(|r| r["RegistryEntries"]["Attributes"].as_array());
+        let entry = |name: &str| {
+            entries.and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|e| e["AttributeName"].as_str() == Some(name))
+            })
+        };
+
+        Ok(attributes
+            .iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                let entry = entry(name);
+                // the menu's wording of an enumeration value, e.g. "Enable"
+                // for "Enabled"
+                let display_value = entry
+                    .and_then(|e| e["Value"].as_array())
+                    .and_then(|values| {
+                        values
+                            .iter()
+                            .find(|v| v["ValueName"].as_str() == Some(&value))
+                    })
+                    .and_then(|v| v["ValueDisplayName"].as_str());
+                BiosAttribute {
+                    name: name.clone(),
+                    display_name: entry
+                        .and_then(|e| e["DisplayName"].as_str())
+                        .map(str::to_owned),
+                    value: display_value.map_or(value, str::to_owned),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    /// A BMC answering with the `resources` by path.
+    fn bmc(resources: &[(&str, &str)]) -> Probe {
+        let curl = "curl -sS -f --max-time 10 -H Accept: application/json -K -";
+        Probe::new(Fixture {
+            commands: resources
+                .iter()
+                .map(|(path, body)| (format!("{} https://bmc{}", curl, path), body.to_string()))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    const SYSTEMS: (&str, &str) = (
+        "/redfish/v1/Systems",
+        r#"{"Members": [{"@odata.id": "/redfish/v1/Systems/1"}]}"#,
+    );
+    const SYSTEM: (&str, &str) = (
+        "/redfish/v1/Systems/1",
+        r#"{"Bios": {"@odata.id": "/redfish/v1/Systems/1/Bios"}}"#,
+    );
+    const BIOS: (&str, &str) = (
+        "/redfish/v1/Systems/1/Bios",
+        r#"{"AttributeRegistry": "BiosAttributeRegistry.1.0",
+            "Attributes": {"TmeMtEnable": "Enabled", "TdxSeamldrSvn": 2}}"#,
+    );
+
+    #[test]
+    fn bios_attributes() {
+        let redfish = Redfish::new("https://bmc/redfish/v1", "root", "secret", false).unwrap();
+        let probe = bmc(&[
+            SYSTEMS,
+            SYSTEM,
+            BIOS,
+            (
+                "/redfish/v1/Registries/BiosAttributeRegistry.1.0",
+                r#"{"Location": [{"Language": "en", "Uri": "/registries/bios.json"}]}"#,
+            ),
+            (
+                "/registries/bios.json",
+                r#"{"RegistryEntries": {"Attributes": [{
+                    "AttributeName": "TmeMtEnable",
+                    "DisplayName": "Total Memory Encryption Multi-Tenant",
+                    "Value": [{"ValueName": "Enabled", "ValueDisplayName": "Enable"}]
+                }]}}"#,
+            ),
+        ]);
+
+        let mut attributes = redfish.bios_attributes(&probe).unwrap();
+        attributes.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].name, "TdxSeamldrSvn");
+        assert_eq!(attributes[0].display_name, None);
+        assert_eq!(attributes[0].value, "2");
+        assert_eq!(attributes[1].name, "TmeMtEnable");
+        assert_eq!(
+            attributes[1].display_name.as_deref(),
+            Some("Total Memory Encryption Multi-Tenant")
+        );
+        assert_eq!(attributes[1].value, "Enable");
+    }
+
+    #[test]
+    fn no_registry() {
+        let redfish = Redfish::new("https://bmc/", "root", "secret", false).unwrap();
+        let attributes = redfish
+            .bios_attributes(&bmc(&[SYSTEMS, SYSTEM, BIOS]))
+            .unwrap();
+        assert!(attributes.iter().all(|a| a.display_name.is_none()));
+        let tme = attributes.iter().find(|a| a.name == "TmeMtEnable").unwrap();
+        assert_eq!(tme.value, "Enabled");
+
+        assert!(redfish.bios_attributes(&bmc(&[SYSTEM, BIOS])).is_err());
+        assert!(Redfish::new("bmc", "root", "secret", false).is_err());
+    }
+}
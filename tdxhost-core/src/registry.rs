@@ -0,0 +1,513 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::check::{Check, CheckResult, Probe, TestOperationState, TestOptionalState, TestState};
+use crate::i18n::tr;
+use crate::remediation::Remediation;
+use crate::{checks, plugin};
+
+/// A check's result and the results of the checks depending on it.
+pub struct CheckNode {
+    pub id: &'static str,
+    pub result: CheckResult,
+    /// Automatable fix, only computed for failed checks
+    pub remediation: Option<Remediation>,
+    pub children: Vec<CheckNode>,
+}
+
+pub struct CheckGroups {
+    pub required: Vec<CheckNode>,
+    pub optional: Vec<CheckNode>,
+    /// Privileged checks were skipped, because tdxhost does not run as root
+    /// or kernel lockdown blocks them
+    pub degraded: bool,
+}
+
+/// The set of checks to run, in execution order.
+#[derive(Default)]
+pub struct Registry {
+    checks: Vec<Box<dyn Check>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in checks, without plugins, e.g. to draw the graph.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for check in checks::builtin() {
+            registry.register(check);
+        }
+        registry
+    }
+
+    /// Built-in checks followed by the plugins in `plugin::PLUGIN_DIR`,
+    /// which are left out when `probe` simulates a host.
+    pub fn with_plugins(probe: &Probe) -> Self {
+        let mut registry = Self::builtin();
+        if !probe.runs_plugins() {
+            return registry;
+        }
+
+        let ids: Vec<&str> = registry.checks().map(|c| c.id()).collect();
+        for p in plugin::discover(&ids, probe) {
+            registry.register(Box::new(p));
+        }
+
+        registry
+    }
+
+    /// Add a check. Checks must be registered after the checks they depend
+    /// on.
+    pub fn register(&mut self, check: Box<dyn Check>) {
+        self.checks.push(check);
+    }
+
+    pub fn checks(&self) -> impl Iterator<Item = &dyn Check> {
+        self.checks.iter().map(|c| c.as_ref())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Check> {
+        self.checks().find(|c| c.id() == id)
+    }
+
+    /// Keep only the checks matching `f`. Checks whose dependencies were
+    /// removed are no longer reachable.
+    pub fn retain(&mut self, mut f: impl FnMut(&dyn Check) -> bool) {
+        self.checks.retain(|c| f(c.as_ref()));
+    }
+
+    fn children(&self, parent: Option<&str>) -> Vec<&dyn Check> {
+        self.checks()
+            .filter(|c| c.dependencies().first().copied() == parent)
+            .collect()
+    }
+
+    pub fn evaluate(&self, probe: &Probe) -> CheckGroups {
+        self.evaluate_selected(probe, &|_| true)
+    }
+
+    /// Evaluate only the checks matching `selected`. Unselected checks are
+    /// left out of the results and do not hold back the checks depending on
+    /// them.
+    ///
+    /// Sibling subtrees that do not depend on each other are evaluated
+    /// concurrently; the results are in registration order regardless.
+    pub fn evaluate_selected(
+        &self,
+        probe: &Probe,
+        selected: &(dyn Fn(&dyn Check) -> bool + Sync),
+    ) -> CheckGroups {
+        let (optional, required) = self
+            .children(None)
+            .into_iter()
+            .partition::<Vec<_>, _>(|c| c.optional());
+
+        probe.refresh();
+        let mut states = HashMap::new();
+        let required = self.evaluate_nodes(probe, required, selected, &mut states);
+        let optional = self.evaluate_nodes(probe, optional, selected, &mut states);
+        let degraded = self
+            .checks()
+            .any(|c| c.privileged() && states.get(c.id()) == Some(&TestState::Skip));
+
+        CheckGroups {
+            required,
+            optional,
+            degraded,
+        }
+    }
+
+    /// Ids of `id` and the checks listed below it.
+    fn subtree(&self, id: &'static str, out: &mut HashSet<&'static str>) {
+        out.insert(id);
+        for c in self.children(Some(id)) {
+            self.subtree(c.id(), out);
+        }
+    }
+
+    /// Whether no check below one of `roots` depends on a check below
+    /// another, so their subtrees can be evaluated independently.
+    fn independent(&self, roots: &[&dyn Check]) -> bool {
+        let subtrees: Vec<HashSet<&str>> = roots
+            .iter()
+            .map(|r| {
+                let mut ids = HashSet::new();
+                self.subtree(r.id(), &mut ids);
+                ids
+            })
+            .collect();
+
+        subtrees.iter().enumerate().all(|(i, ids)| {
+            ids.iter()
+                .flat_map(|id| self.get(id).unwrap().dependencies())
+                .all(|d| {
+                    subtrees
+                        .iter()
+                        .enumerate()
+                        .all(|(j, other)| i == j || !other.contains(d))
+                })
+        })
+    }
+
+    fn evaluate_nodes(
+        &self,
+        probe: &Probe,
+        checks: Vec<&dyn Check>,
+        selected: &(dyn Fn(&dyn Check) -> bool + Sync),
+        states: &mut HashMap<&'static str, TestState>,
+    ) -> Vec<CheckNode> {
+        if checks.len() < 2 || !self.independent(&checks) {
+            return checks
+                .into_iter()
+                .flat_map(|c| self.evaluate_subtree(probe, c, selected, states))
+                .collect();
+        }
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = checks
+                .into_iter()
+                .map(|c| {
+                    let mut states = states.clone();
+                    scope.spawn(move || {
+                        let nodes = self.evaluate_subtree(probe, c, selected, &mut states);
+                        (nodes, states)
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+                .collect()
+        });
+
+        let mut nodes = vec![];
+        for (subtree, subtree_states) in results {
+            nodes.extend(subtree);
+            states.extend(subtree_states);
+        }
+        nodes
+    }
+
+    /// Evaluate `c` and, depending on its result, the checks below it.
+    fn evaluate_subtree(
+        &self,
+        probe: &Probe,
+        c: &dyn Check,
+        selected: &(dyn Fn(&dyn Check) -> bool + Sync),
+        states: &mut HashMap<&'static str, TestState>,
+    ) -> Vec<CheckNode> {
+        let ready = c
+            .dependencies()
+            .iter()
+            .all(|d| matches!(states.get(d), Some(TestState::Ok | TestState::Warning)));
+        if !ready {
+            return vec![self.skipped(c)];
+        }
+
+        if !selected(c) {
+            // dependents take the unselected check's place
+            states.insert(c.id(), TestState::Ok);
+            return self.evaluate_nodes(probe, self.children(Some(c.id())), selected, states);
+        }
+
+        let mut node = evaluate_one(c, probe);
+        states.insert(c.id(), node.result.state);
+        node.children = match node.result.state {
+            // a warning does not hold the checks below it back
+            TestState::Ok | TestState::Warning => {
+                self.evaluate_nodes(probe, self.children(Some(c.id())), selected, states)
+            }
+            TestState::Fail | TestState::Skip => self
+                .children(Some(c.id()))
+                .into_iter()
+                .map(|c| self.skipped(c))
+                .collect(),
+            TestState::Tbd => vec![],
+        };
+        vec![node]
+    }
+
+    fn skipped(&self, check: &dyn Check) -> CheckNode {
+        CheckNode {
+            id: check.id(),
+            result: CheckResult {
+                state: TestState::Skip,
+                action: check.description(),
+                optional_state: optional_state(check),
+                docs_url: check.docs_url(),
+                ..Default::default()
+            },
+            remediation: None,
+            children: self
+                .children(Some(check.id()))
+                .into_iter()
+                .map(|c| self.skipped(c))
+                .collect(),
+        }
+    }
+
+    /// Re-run the check with the given id, e.g. after the operator changed a
+    /// setting. Dependents are not evaluated.
+    pub fn rerun(&self, id: &str, probe: &Probe) -> Option<CheckNode> {
+        probe.refresh();
+        self.get(id).map(|c| evaluate_one(c, probe))
+    }
+
+    /// The dependency graph in Graphviz's DOT language, an edge leading
+    /// from each check to the checks depending on it. Optional checks are
+    /// dashed.
+    pub fn dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut dot = String::from("digraph checks {\n    rankdir=LR;\n    node [shape=box];\n");
+        for check in self.checks() {
+            // the id above the description
+            dot += &format!(
+                "    \"{}\" [label=\"{}\\n{}\"",
+                escape(check.id()),
+                escape(check.id()),
+                escape(&check.description())
+            );
+            if check.optional() {
+                dot += ", style=dashed";
+            }
+            dot += "];\n";
+        }
+        for check in self.checks() {
+            for dependency in check.dependencies() {
+                dot += &format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    escape(dependency),
+                    escape(check.id())
+                );
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+fn optional_state(check: &dyn Check) -> TestOptionalState {
+    if check.optional() {
+        TestOptionalState::Optional
+    } else {
+        TestOptionalState::Required
+    }
+}
+
+/// Run a single check without descending into its dependents.
+fn evaluate_one(check: &dyn Check, probe: &Probe) -> CheckNode {
+    if check.privileged() && !probe.privileged() {
+        return CheckNode {
+            id: check.id(),
+            result: CheckResult {
+                action: check.description(),
+                reason: tr("reason-needs-root"),
+                state: TestState::Skip,
+                optional_state: optional_state(check),
+                docs_url: check.docs_url(),
+                ..Default::default()
+            },
+            remediation: None,
+            children: vec![],
+        };
+    }
+
+    let mut result = check.execute(probe);
+    result.optional_state = optional_state(check);
+    result.docs_url = check.docs_url();
+    if let TestOperationState::Manual = result.operation {
+        if !matches!(result.state, TestState::Ok | TestState::Warning) {
+            result.reason = tr("reason-manual");
+        }
+    }
+
+    let remediation = match result.state {
+        TestState::Fail => check.remediation(probe),
+        _ => None,
+    };
+
+    CheckNode {
+        id: check.id(),
+        result,
+        remediation,
+        children: vec![],
+    }
+}
+
+/// A failure anywhere in the tree means the group did not pass.
+pub fn passed(nodes: &[CheckNode]) -> bool {
+    nodes
+        .iter()
+        .all(|n| n.result.state != TestState::Fail && passed(&n.children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+
+    /// A check with a fixed result.
+    struct Stub {
+        id: &'static str,
+        dependencies: &'static [&'static str],
+        optional: bool,
+        state: TestState,
+    }
+
+    impl Check for Stub {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn description(&self) -> String {
+            self.id.to_string()
+        }
+
+        fn dependencies(&self) -> &[&'static str] {
+            self.dependencies
+        }
+
+        fn optional(&self) -> bool {
+            self.optional
+        }
+
+        fn execute(&self, _probe: &Probe) -> CheckResult {
+            CheckResult {
+                state: self.state,
+                ..Default::default()
+            }
+        }
+    }
+
+    fn stub(id: &'static str, dependencies: &'static [&'static str], state: TestState) -> Stub {
+        Stub {
+            id,
+            dependencies,
+            optional: false,
+            state,
+        }
+    }
+
+    /// Ids and states of `nodes`, depth first.
+    fn flatten(nodes: &[CheckNode], out: &mut Vec<(&'static str, TestState)>) {
+        for n in nodes {
+            out.push((n.id, n.result.state));
+            flatten(&n.children, out);
+        }
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::new();
+        for check in [
+            stub("a", &[], TestState::Ok),
+            stub("a.fail", &["a"], TestState::Fail),
+            stub("a.fail.below", &["a.fail"], TestState::Ok),
+            stub("a.warn", &["a"], TestState::Warning),
+            stub("a.warn.below", &["a.warn"], TestState::Ok),
+            stub("b", &[], TestState::Ok),
+            Stub {
+                optional: true,
+                ..stub("c", &[], TestState::Fail)
+            },
+        ] {
+            registry.register(Box::new(check));
+        }
+        registry
+    }
+
+    #[test]
+    fn builtin_dependencies_come_first() {
+        let mut seen = HashSet::new();
+        for check in checks::builtin() {
+            for dependency in check.dependencies() {
+                assert!(
+                    seen.contains(dependency),
+                    "{} is registered before its dependency {}",
+                    check.id(),
+                    dependency
+                );
+            }
+            seen.insert(check.id());
+        }
+    }
+
+    #[test]
+    fn results_in_registration_order() {
+        let groups = registry().evaluate(&Probe::new(Fixture::default()));
+
+        let mut required = vec![];
+        flatten(&groups.required, &mut required);
+        assert_eq!(
+            required,
+            [
+                ("a", TestState::Ok),
+                ("a.fail", TestState::Fail),
+                ("a.fail.below", TestState::Skip),
+                ("a.warn", TestState::Warning),
+                ("a.warn.below", TestState::Ok),
+                ("b", TestState::Ok),
+            ]
+        );
+        let mut optional = vec![];
+        flatten(&groups.optional, &mut optional);
+        assert_eq!(optional, [("c", TestState::Fail)]);
+        assert!(!passed(&groups.required));
+        assert!(!groups.degraded);
+    }
+
+    #[test]
+    fn unselected_checks_do_not_hold_back() {
+        let groups =
+            registry().evaluate_selected(&Probe::new(Fixture::default()), &|c| c.id() != "a.fail");
+
+        let mut required = vec![];
+        flatten(&groups.required, &mut required);
+        assert_eq!(
+            required,
+            [
+                ("a", TestState::Ok),
+                ("a.fail.below", TestState::Ok),
+                ("a.warn", TestState::Warning),
+                ("a.warn.below", TestState::Ok),
+                ("b", TestState::Ok),
+            ]
+        );
+        assert!(passed(&groups.required));
+    }
+
+    #[test]
+    fn privileged_checks_skipped() {
+        struct Msr;
+
+        impl Check for Msr {
+            fn id(&self) -> &'static str {
+                "msr"
+            }
+
+            fn description(&self) -> String {
+                "msr".to_string()
+            }
+
+            fn privileged(&self) -> bool {
+                true
+            }
+
+            fn execute(&self, _probe: &Probe) -> CheckResult {
+                unreachable!("privileged checks are not run unprivileged")
+            }
+        }
+
+        let mut registry = Registry::new();
+        registry.register(Box::new(Msr));
+        let probe = Probe::new(Fixture {
+            unprivileged: true,
+            ..Default::default()
+        });
+        let groups = registry.evaluate(&probe);
+        assert_eq!(groups.required[0].result.state, TestState::Skip);
+        assert!(groups.degraded);
+    }
+}
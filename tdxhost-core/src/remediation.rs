@@ -0,0 +1,245 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::check::{Probe, TestState};
+use crate::error::{Result, TdxHostError};
+use crate::registry::CheckNode;
+use crate::{grub, modprobe};
+
+/// A single system change made by a remediation.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Replace the contents of a file, creating it if needed
+    WriteFile { path: PathBuf, contents: String },
+    /// Add kernel parameters to a variable of /etc/default/grub, as the file
+    /// is when the action runs
+    AddKernelParams {
+        path: PathBuf,
+        variable: String,
+        params: Vec<String>,
+    },
+    /// Set an option of a kernel module in a modprobe.d file, keeping the
+    /// file's other lines
+    SetModuleOption {
+        path: PathBuf,
+        module: String,
+        option: String,
+    },
+    /// Run a command to completion
+    Command { program: String, args: Vec<String> },
+    /// Enable and start a systemd unit
+    EnableService { unit: String },
+}
+
+/// An automatable fix for a failing check.
+#[derive(Debug, Clone)]
+pub struct Remediation {
+    pub summary: String,
+    pub actions: Vec<Action>,
+    /// The fix only takes effect after a reboot
+    pub reboot_required: bool,
+}
+
+/// Quote a word for POSIX sh.
+pub fn shell_quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+".contains(c))
+    {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+impl Action {
+    pub fn command(program: &str, args: &[&str]) -> Self {
+        Action::Command {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Action::WriteFile { path, .. } => format!("write {}", path.display()),
+            Action::AddKernelParams {
+                path,
+                variable,
+                params,
+            } => format!(
+                "add {} to {} in {}",
+                params.join(" "),
+                variable,
+                path.display()
+            ),
+            Action::SetModuleOption {
+                path,
+                module,
+                option,
+            } => format!("set {} {} in {}", module, option, path.display()),
+            Action::Command { program, args } => format!("run: {} {}", program, args.join(" ")),
+            Action::EnableService { unit } => format!("run: systemctl enable --now {}", unit),
+        }
+    }
+
+    pub fn apply(&self) -> Result<()> {
+        match self {
+            Action::WriteFile { path, contents } => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| TdxHostError::io(parent, e))?;
+                }
+                std::fs::write(path, contents).map_err(|e| TdxHostError::io(path, e))
+            }
+            Action::AddKernelParams {
+                path,
+                variable,
+                params,
+            } => {
+                let contents =
+                    std::fs::read_to_string(path).map_err(|e| TdxHostError::io(path, e))?;
+                let params: Vec<&str> = params.iter().map(String::as_str).collect();
+                match grub::with_params(&contents, variable, &params) {
+                    Some(contents) => {
+                        std::fs::write(path, contents).map_err(|e| TdxHostError::io(path, e))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Action::SetModuleOption {
+                path,
+                module,
+                option,
+            } => {
+                let contents = match std::fs::read_to_string(path) {
+                    Ok(contents) => contents,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                    Err(e) => return Err(TdxHostError::io(path, e)),
+                };
+                match modprobe::with_option(&contents, module, option) {
+                    Some(contents) => {
+                        if let Some(parent) = path.parent() {
+                            std::fs::create_dir_all(parent)
+                                .map_err(|e| TdxHostError::io(parent, e))?;
+                        }
+                        std::fs::write(path, contents).map_err(|e| TdxHostError::io(path, e))
+                    }
+                    None => Ok(()),
+                }
+            }
+            Action::Command { program, args } => run_command(program, args),
+            Action::EnableService { unit } => run_command(
+                "systemctl",
+                &["enable".to_string(), "--now".to_string(), unit.clone()],
+            ),
+        }
+    }
+
+    fn to_shell(&self) -> String {
+        match self {
+            Action::WriteFile { path, contents } => {
+                let path = shell_quote(&path.display().to_string());
+                let mut contents = contents.clone();
+                if !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+                format!(
+                    "mkdir -p \"$(dirname {path})\"\ncat > {path} <<'TDXHOST_EOF'\n{contents}TDXHOST_EOF"
+                )
+            }
+            Action::AddKernelParams {
+                path,
+                variable,
+                params,
+            } => grub::add_params_script(&path.display().to_string(), variable, params),
+            Action::SetModuleOption {
+                path,
+                module,
+                option,
+            } => modprobe::set_option_script(&path.display().to_string(), module, option),
+            Action::Command { program, args } => std::iter::once(program)
+                .chain(args)
+                .map(|w| shell_quote(w))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Action::EnableService { unit } => {
+                format!("systemctl enable --now {}", shell_quote(unit))
+            }
+        }
+    }
+}
+
+/// Whether `program` can be found in the directories of PATH on `probe`'s
+/// host.
+pub fn in_path(probe: &Probe, program: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .any(|dir| probe.exists(&dir.join(program).to_string_lossy()))
+        })
+        .unwrap_or(false)
+}
+
+fn run_command(program: &str, args: &[String]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| TdxHostError::io(program.as_ref(), e))?;
+
+    if !output.status.success() {
+        return Err(TdxHostError::Command {
+            command: format!("{} {}", program, args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Render the remediations of `nodes` as a shell script meant to be reviewed
+/// and applied through a change-management pipeline.
+fn script(nodes: &[&CheckNode]) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+    script += &format!(
+        "# Generated by tdxhost {}. Review before running.\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    script += "set -eu\n";
+
+    let mut reboot_required = false;
+    for node in nodes {
+        let remediation = node.remediation.as_ref().unwrap();
+        reboot_required |= remediation.reboot_required;
+
+        script += &format!("\n# {}: {}\n", node.id, node.result.action);
+        script += &format!("# {}\n", remediation.summary);
+        for action in &remediation.actions {
+            script += &action.to_shell();
+            script += "\n";
+        }
+    }
+
+    if reboot_required {
+        script += "\necho 'A reboot is required for these changes to take effect.'\n";
+    }
+    script
+}
+
+pub fn write_script(path: &std::path::Path, nodes: &[&CheckNode]) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::write(path, script(nodes)).map_err(|e| TdxHostError::io(path, e))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+        .map_err(|e| TdxHostError::io(path, e))
+}
+
+/// Failing checks with a remediation, in check tree order.
+pub fn fixable<'a>(nodes: &'a [CheckNode], out: &mut Vec<&'a CheckNode>) {
+    for node in nodes {
+        if node.result.state == TestState::Fail && node.remediation.is_some() {
+            out.push(node);
+        }
+        fixable(&node.children, out);
+    }
+}
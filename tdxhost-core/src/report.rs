@@ -0,0 +1,92 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::check::{CheckResult, Probe, TestState};
+use crate::error::{Result, TdxHostError};
+use crate::info::{self, Hardware};
+use crate::registry::{passed, CheckGroups, CheckNode};
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ReportEntry {
+    /// Stable check identifier, e.g. "bios.tdx"
+    pub id: String,
+    #[serde(flatten)]
+    pub result: CheckResult,
+}
+
+/// Result of `tdxhost ok --format json`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Report {
+    /// The host's hardware model and BIOS
+    #[serde(default)]
+    pub hardware: Hardware,
+    /// True when every required check passed
+    pub ready: bool,
+    /// Privileged checks were skipped, because tdxhost did not run as root or
+    /// kernel lockdown blocked them; the host is not known to be ready
+    #[serde(default)]
+    pub degraded: bool,
+    /// Required checks, in execution order
+    pub required: Vec<ReportEntry>,
+    /// Optional checks, in execution order
+    pub optional: Vec<ReportEntry>,
+}
+
+fn flatten(nodes: Vec<CheckNode>, out: &mut Vec<ReportEntry>) {
+    for node in nodes {
+        out.push(ReportEntry {
+            id: node.id.to_string(),
+            result: node.result,
+        });
+        flatten(node.children, out);
+    }
+}
+
+impl Report {
+    /// The report of the checks' results on the host `probe` reads.
+    pub fn new(probe: &Probe, groups: CheckGroups) -> Self {
+        let ready = passed(&groups.required) && !groups.degraded;
+        let (mut required, mut optional) = (vec![], vec![]);
+        flatten(groups.required, &mut required);
+        flatten(groups.optional, &mut optional);
+
+        Report {
+            hardware: info::hardware(probe),
+            ready,
+            degraded: groups.degraded,
+            required,
+            optional,
+        }
+    }
+
+    /// `TdxHostError::CheckFailed` with the checks holding the host back,
+    /// unless it is ready. `TdxHostError::PermissionDenied` when nothing
+    /// failed but privileged checks were skipped.
+    pub fn ensure_ready(&self) -> Result<()> {
+        if self.ready {
+            return Ok(());
+        }
+
+        let failed = |entries: &[ReportEntry], states: &[TestState]| -> Vec<String> {
+            entries
+                .iter()
+                .filter(|e| states.contains(&e.result.state))
+                .map(|e| e.id.clone())
+                .collect()
+        };
+        let mut ids = failed(&self.required, &[TestState::Fail]);
+        if ids.is_empty() {
+            // a strict run, warnings and optional checks count too
+            let states = [TestState::Fail, TestState::Warning];
+            ids = failed(&self.required, &states);
+            ids.extend(failed(&self.optional, &states));
+        }
+        if ids.is_empty() && self.degraded {
+            return Err(TdxHostError::PermissionDenied(
+                "privileged checks were skipped".to_string(),
+            ));
+        }
+
+        Err(TdxHostError::CheckFailed { ids })
+    }
+}
@@ -0,0 +1,94 @@
+use crate::check::{Check, Probe, Tag, TestState};
+use crate::registry::Registry;
+use crate::report::Report;
+
+/// Builder for a selective run of the checks.
+///
+/// ```no_run
+/// use tdxhost_core::check::{Probe, Tag};
+/// use tdxhost_core::config::Config;
+/// use tdxhost_core::error::TdxHostError;
+/// use tdxhost_core::run::CheckRun;
+///
+/// # fn main() -> tdxhost_core::error::Result<()> {
+/// let report = CheckRun::new(Probe::host(Config::load()?))
+///     .include_tag(Tag::Bios)
+///     .skip("os.distro")
+///     .strict(true)
+///     .run();
+/// if let Err(TdxHostError::CheckFailed { ids }) = report.ensure_ready() {
+///     eprintln!("not ready: {}", ids.join(", "));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct CheckRun {
+    registry: Option<Registry>,
+    probe: Probe,
+    tags: Vec<Tag>,
+    skip: Vec<String>,
+    strict: bool,
+}
+
+impl CheckRun {
+    /// A run of every check on the host `probe` reads.
+    pub fn new(probe: Probe) -> Self {
+        Self {
+            registry: None,
+            probe,
+            tags: vec![],
+            skip: vec![],
+            strict: false,
+        }
+    }
+
+    /// Run the checks of `registry` instead of `Registry::with_plugins`.
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Only run checks with one of the included tags. Without any, every
+    /// check runs.
+    pub fn include_tag(mut self, tag: Tag) -> Self {
+        self.tags.push(tag);
+        self
+    }
+
+    /// Leave out the check with the given id. The checks depending on it
+    /// still run.
+    pub fn skip(mut self, id: &str) -> Self {
+        self.skip.push(id.to_string());
+        self
+    }
+
+    /// Also require optional checks to pass, and count warnings as failures.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn selected(&self, check: &dyn Check) -> bool {
+        let tagged = self.tags.is_empty() || check.tags().iter().any(|t| self.tags.contains(t));
+        tagged && !self.skip.iter().any(|id| id == check.id())
+    }
+
+    pub fn run(mut self) -> Report {
+        let registry = self
+            .registry
+            .take()
+            .unwrap_or_else(|| Registry::with_plugins(&self.probe));
+        let groups = registry.evaluate_selected(&self.probe, &|c| self.selected(c));
+
+        let mut report = Report::new(&self.probe, groups);
+        if self.strict {
+            report.ready = !report.degraded
+                && report
+                    .required
+                    .iter()
+                    .chain(&report.optional)
+                    .all(|e| !matches!(e.result.state, TestState::Fail | TestState::Warning));
+        }
+        report
+    }
+}
@@ -0,0 +1,382 @@
+//! An end-to-end check of the TDX stack: create a minimal TD through the
+//! KVM ioctls and run its vCPU until it exits in a known way.
+//!
+//! The TD has a single measured page at the top of the 4 GiB boundary,
+//! where its vCPU starts in 32-bit protected mode. The code there reads an
+//! address outside of any memslot, which KVM reports to userspace as a
+//! memory fault; seeing that fault at that address shows the TD ran.
+
+use std::fs::File;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::error::{Result, TdxHostError};
+use crate::kvm::{self, KvmTdxCmd, CPUID2_HEADER, CPUID_ENTRY, KVM_MEMORY_ENCRYPT_OP};
+
+const KVM_GET_VCPU_MMAP_SIZE: libc::c_ulong = 0xae04;
+const KVM_CREATE_VCPU: libc::c_ulong = 0xae41;
+const KVM_SET_USER_MEMORY_REGION2: libc::c_ulong = 0x40a0_ae49;
+const KVM_RUN: libc::c_ulong = 0xae80;
+const KVM_SET_CPUID2: libc::c_ulong = 0x4008_ae90;
+const KVM_ENABLE_CAP: libc::c_ulong = 0x4068_aea3;
+const KVM_SET_MEMORY_ATTRIBUTES: libc::c_ulong = 0x4020_aed2;
+const KVM_CREATE_GUEST_MEMFD: libc::c_ulong = 0xc040_aed4;
+
+/// TDs need the local APICs in KVM and the IOAPIC in userspace
+const KVM_CAP_SPLIT_IRQCHIP: u32 = 121;
+/// Routes reserved for the IOAPIC, as QEMU does
+const IOAPIC_PINS: u64 = 24;
+const KVM_MEM_GUEST_MEMFD: u32 = 1 << 2;
+const KVM_MEMORY_ATTRIBUTE_PRIVATE: u64 = 1 << 3;
+
+/// Commands of KVM_MEMORY_ENCRYPT_OP
+const KVM_TDX_INIT_VM: u32 = 1;
+const KVM_TDX_INIT_VCPU: u32 = 2;
+const KVM_TDX_INIT_MEM_REGION: u32 = 3;
+const KVM_TDX_FINALIZE_VM: u32 = 4;
+/// Extend the TD measurement with the added pages
+const KVM_TDX_MEASURE_MEMORY_REGION: u32 = 1 << 0;
+
+/// Size of struct kvm_tdx_init_vm up to its CPUID entries: attributes,
+/// XFAM, MRCONFIGID, MROWNER, MROWNERCONFIG and reserved space.
+const INIT_VM_HEADER: usize = 32 * 8;
+/// x87 and SSE, the XFAM bits every TD has
+const XFAM: u64 = 0x3;
+
+/// Exit reasons of struct kvm_run
+const KVM_EXIT_SHUTDOWN: u32 = 8;
+const KVM_EXIT_FAIL_ENTRY: u32 = 9;
+const KVM_EXIT_INTERNAL_ERROR: u32 = 17;
+const KVM_EXIT_SYSTEM_EVENT: u32 = 24;
+const KVM_EXIT_MEMORY_FAULT: u32 = 39;
+/// Offsets in struct kvm_run
+const RUN_EXIT_REASON: usize = 8;
+const RUN_MEMORY_FAULT_GPA: usize = 40;
+
+const PAGE_SIZE: usize = 4096;
+/// The page the vCPU starts in, below 4 GiB
+const CODE_GPA: u64 = 0xffff_f000;
+/// Not backed by memory, reading it exits to userspace
+const MARKER_GPA: u64 = 0x1000;
+/// At the reset vector 0xfffffff0: mov eax, [MARKER_GPA]; jmp $
+const GUEST_CODE: [u8; 7] = [0xa1, 0x00, 0x10, 0x00, 0x00, 0xeb, 0xfe];
+const RESET_VECTOR_OFFSET: usize = 0xff0;
+
+/// How long the vCPU may run before the test gives up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A step of the smoke test, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    CreateVm,
+    InitVm,
+    CreateVcpu,
+    InitVcpu,
+    AddMemory,
+    Finalize,
+    Run,
+}
+
+impl Stage {
+    /// Stable identifier, e.g. "init-vm"
+    pub fn id(&self) -> &'static str {
+        match self {
+            Stage::CreateVm => "create-vm",
+            Stage::InitVm => "init-vm",
+            Stage::CreateVcpu => "create-vcpu",
+            Stage::InitVcpu => "init-vcpu",
+            Stage::AddMemory => "add-memory",
+            Stage::Finalize => "finalize",
+            Stage::Run => "run",
+        }
+    }
+
+    /// SEAMCALLs the kernel makes to the TDX module in this stage.
+    pub fn seamcalls(&self) -> &'static [&'static str] {
+        match self {
+            Stage::CreateVm | Stage::CreateVcpu => &[],
+            Stage::InitVm => &["TDH.MNG.CREATE", "TDH.MNG.KEY.CONFIG", "TDH.MNG.INIT"],
+            Stage::InitVcpu => &["TDH.VP.CREATE", "TDH.VP.ADDCX", "TDH.VP.INIT"],
+            Stage::AddMemory => &["TDH.MEM.PAGE.ADD", "TDH.MR.EXTEND"],
+            Stage::Finalize => &["TDH.MR.FINALIZE"],
+            Stage::Run => &["TDH.VP.ENTER"],
+        }
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::CreateVm => "create the VM",
+            Stage::InitVm => "initialize the TD",
+            Stage::CreateVcpu => "create the vCPU",
+            Stage::InitVcpu => "initialize the vCPU",
+            Stage::AddMemory => "add the measured memory",
+            Stage::Finalize => "finalize the measurement",
+            Stage::Run => "run the vCPU",
+        })
+    }
+}
+
+fn failed(stage: Stage, reason: impl ToString) -> TdxHostError {
+    TdxHostError::SmokeTest {
+        stage,
+        reason: reason.to_string(),
+    }
+}
+
+fn ioctl(fd: RawFd, request: libc::c_ulong, arg: u64) -> std::io::Result<i32> {
+    let ret = unsafe { libc::ioctl(fd, request, arg) };
+    if ret < 0 {
+        return Err(kvm::last_error());
+    }
+    Ok(ret)
+}
+
+/// Issue a KVM_MEMORY_ENCRYPT_OP command, with the SEAMCALL error in the
+/// failure reason.
+fn tdx_cmd(fd: RawFd, stage: Stage, id: u32, flags: u32, data: u64) -> Result<()> {
+    let mut cmd = KvmTdxCmd {
+        id,
+        flags,
+        data,
+        hw_error: 0,
+    };
+    ioctl(fd, KVM_MEMORY_ENCRYPT_OP, &mut cmd as *mut _ as u64).map_err(|e| {
+        match cmd.hw_error {
+            0 => failed(stage, e),
+            hw_error => failed(stage, format!("{} (SEAMCALL error {:#018x})", e, hw_error)),
+        }
+    })?;
+    Ok(())
+}
+
+fn into_fd(fd: i32) -> OwnedFd {
+    unsafe { OwnedFd::from_raw_fd(fd) }
+}
+
+/// An mmap()ed region, unmapped on drop.
+struct Mapping {
+    addr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mapping {
+    fn new(len: usize, fd: Option<RawFd>) -> std::io::Result<Self> {
+        let (flags, fd) = match fd {
+            Some(fd) => (libc::MAP_SHARED, fd),
+            None => (libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1),
+        };
+        let addr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                flags,
+                fd,
+                0,
+            )
+        };
+        if addr == libc::MAP_FAILED {
+            return Err(kvm::last_error());
+        }
+        Ok(Self { addr, len })
+    }
+
+    fn bytes(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.addr as *mut u8, self.len) }
+    }
+
+    fn u32_at(&self, offset: usize) -> u32 {
+        unsafe { std::ptr::read_unaligned(self.addr.add(offset) as *const u32) }
+    }
+
+    fn u64_at(&self, offset: usize) -> u64 {
+        unsafe { std::ptr::read_unaligned(self.addr.add(offset) as *const u64) }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.addr, self.len) };
+    }
+}
+
+/// A struct kvm_cpuid2 after `header` bytes, in u64 for its alignment.
+///
+/// KVM leaves out what is not listed: leaf 1 enables the x2APIC TDs run
+/// with, leaf 0x80000008 sets a 48-bit guest physical address width.
+fn cpuid2(header: usize) -> Vec<u64> {
+    // leaf, subleaf, eax, ebx, ecx, edx
+    let entries: [[u32; 6]; 2] = [[0x1, 0, 0, 0, 1 << 21, 0], [0x8000_0008, 0, 48, 0, 0, 0]];
+
+    let size = header + CPUID2_HEADER + entries.len() * CPUID_ENTRY;
+    let mut buffer = vec![0u64; size / 8];
+    let bytes =
+        unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8) };
+    bytes[header..header + 4].copy_from_slice(&(entries.len() as u32).to_ne_bytes());
+    for (i, [leaf, subleaf, eax, ebx, ecx, edx]) in entries.into_iter().enumerate() {
+        // function, index, flags, eax, ebx, ecx, edx, padding
+        let entry = header + CPUID2_HEADER + i * CPUID_ENTRY;
+        for (offset, value) in [
+            (0, leaf),
+            (4, subleaf),
+            (12, eax),
+            (16, ebx),
+            (20, ecx),
+            (24, edx),
+        ] {
+            bytes[entry + offset..entry + offset + 4].copy_from_slice(&value.to_ne_bytes());
+        }
+    }
+    buffer
+}
+
+extern "C" fn interrupt(_: libc::c_int) {}
+
+/// KVM_RUN, interrupted by SIGUSR1 after `TIMEOUT`.
+fn run_vcpu(vcpu: RawFd) -> std::io::Result<i32> {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = interrupt as *const () as usize;
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+    }
+
+    let thread = unsafe { libc::pthread_self() };
+    let (done, finished) = mpsc::channel::<()>();
+    let timer = std::thread::spawn(move || {
+        if let Err(mpsc::RecvTimeoutError::Timeout) = finished.recv_timeout(TIMEOUT) {
+            unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+        }
+    });
+    let ret = ioctl(vcpu, KVM_RUN, 0);
+    let _ = done.send(());
+    let _ = timer.join();
+    ret
+}
+
+/// Create, measure, finalize and run a minimal TD, calling `passed` after
+/// every stage that succeeded.
+pub fn run(mut passed: impl FnMut(Stage)) -> Result<()> {
+    let kvm_dev = File::open("/dev/kvm").map_err(|e| failed(Stage::CreateVm, e))?;
+    let vm = kvm::create_td(&kvm_dev).map_err(|e| failed(Stage::CreateVm, e))?;
+    let vm_fd = vm.as_raw_fd();
+    // struct kvm_enable_cap: cap, flags, args[4], pad[64]
+    let mut enable_cap = [0u64; 13];
+    enable_cap[0] = KVM_CAP_SPLIT_IRQCHIP as u64;
+    enable_cap[1] = IOAPIC_PINS;
+    ioctl(vm_fd, KVM_ENABLE_CAP, enable_cap.as_ptr() as u64)
+        .map_err(|e| failed(Stage::CreateVm, e))?;
+    passed(Stage::CreateVm);
+
+    // attributes 0: no debug, no migration
+    let mut init_vm = cpuid2(INIT_VM_HEADER);
+    init_vm[1] = XFAM;
+    tdx_cmd(
+        vm_fd,
+        Stage::InitVm,
+        KVM_TDX_INIT_VM,
+        0,
+        init_vm.as_mut_ptr() as u64,
+    )?;
+    passed(Stage::InitVm);
+
+    let vcpu = ioctl(vm_fd, KVM_CREATE_VCPU, 0)
+        .map(into_fd)
+        .map_err(|e| failed(Stage::CreateVcpu, e))?;
+    let vcpu_fd = vcpu.as_raw_fd();
+    let cpuid = cpuid2(0);
+    ioctl(vcpu_fd, KVM_SET_CPUID2, cpuid.as_ptr() as u64)
+        .map_err(|e| failed(Stage::CreateVcpu, e))?;
+    let run_size = ioctl(kvm_dev.as_raw_fd(), KVM_GET_VCPU_MMAP_SIZE, 0)
+        .map_err(|e| failed(Stage::CreateVcpu, e))?;
+    let kvm_run =
+        Mapping::new(run_size as usize, Some(vcpu_fd)).map_err(|e| failed(Stage::CreateVcpu, e))?;
+    passed(Stage::CreateVcpu);
+
+    // the initial RCX, the address of a TD HOB the test TD does not have
+    tdx_cmd(vcpu_fd, Stage::InitVcpu, KVM_TDX_INIT_VCPU, 0, 0)?;
+    passed(Stage::InitVcpu);
+
+    // struct kvm_create_guest_memfd: size, flags, reserved[6]
+    let mut create_memfd = [0u64; 8];
+    create_memfd[0] = PAGE_SIZE as u64;
+    let memfd = ioctl(
+        vm_fd,
+        KVM_CREATE_GUEST_MEMFD,
+        create_memfd.as_mut_ptr() as u64,
+    )
+    .map(into_fd)
+    .map_err(|e| failed(Stage::AddMemory, e))?;
+    let mut source = Mapping::new(PAGE_SIZE, None).map_err(|e| failed(Stage::AddMemory, e))?;
+    source.bytes()[RESET_VECTOR_OFFSET..RESET_VECTOR_OFFSET + GUEST_CODE.len()]
+        .copy_from_slice(&GUEST_CODE);
+
+    // struct kvm_userspace_memory_region2: slot, flags, guest_phys_addr,
+    // memory_size, userspace_addr, guest_memfd_offset, guest_memfd, pad
+    let mut region = [0u64; 20];
+    region[0] = (KVM_MEM_GUEST_MEMFD as u64) << 32;
+    region[1] = CODE_GPA;
+    region[2] = PAGE_SIZE as u64;
+    region[3] = source.addr as u64;
+    region[5] = memfd.as_raw_fd() as u64;
+    ioctl(vm_fd, KVM_SET_USER_MEMORY_REGION2, region.as_ptr() as u64)
+        .map_err(|e| failed(Stage::AddMemory, e))?;
+    // struct kvm_memory_attributes: address, size, attributes, flags
+    let attributes = [CODE_GPA, PAGE_SIZE as u64, KVM_MEMORY_ATTRIBUTE_PRIVATE, 0];
+    ioctl(vm_fd, KVM_SET_MEMORY_ATTRIBUTES, attributes.as_ptr() as u64)
+        .map_err(|e| failed(Stage::AddMemory, e))?;
+    // struct kvm_tdx_init_mem_region: source_addr, gpa, nr_pages
+    let mut init_mem = [source.addr as u64, CODE_GPA, 1];
+    tdx_cmd(
+        vcpu_fd,
+        Stage::AddMemory,
+        KVM_TDX_INIT_MEM_REGION,
+        KVM_TDX_MEASURE_MEMORY_REGION,
+        init_mem.as_mut_ptr() as u64,
+    )?;
+    passed(Stage::AddMemory);
+
+    tdx_cmd(vm_fd, Stage::Finalize, KVM_TDX_FINALIZE_VM, 0, 0)?;
+    passed(Stage::Finalize);
+
+    let ret = run_vcpu(vcpu_fd);
+    let exit_reason = kvm_run.u32_at(RUN_EXIT_REASON);
+    match ret {
+        // the guest's read of MARKER_GPA
+        Err(e)
+            if e.raw_os_error() == Some(libc::EFAULT) && exit_reason == KVM_EXIT_MEMORY_FAULT =>
+        {
+            let gpa = kvm_run.u64_at(RUN_MEMORY_FAULT_GPA);
+            if gpa != MARKER_GPA {
+                return Err(failed(
+                    Stage::Run,
+                    format!("memory fault at {:#x} instead of {:#x}", gpa, MARKER_GPA),
+                ));
+            }
+        }
+        Err(e) if e.raw_os_error() == Some(libc::EINTR) => {
+            return Err(failed(
+                Stage::Run,
+                format!("no exit within {}s", TIMEOUT.as_secs()),
+            ))
+        }
+        Err(e) => return Err(failed(Stage::Run, e)),
+        Ok(_) => {
+            let exit = match exit_reason {
+                KVM_EXIT_SHUTDOWN => "shutdown (triple fault)",
+                KVM_EXIT_FAIL_ENTRY => "failed entry",
+                KVM_EXIT_INTERNAL_ERROR => "internal error",
+                KVM_EXIT_SYSTEM_EVENT => "system event",
+                _ => "",
+            };
+            return Err(failed(
+                Stage::Run,
+                format!("unexpected exit {} {}", exit_reason, exit).trim_end(),
+            ));
+        }
+    }
+    passed(Stage::Run);
+    Ok(())
+}
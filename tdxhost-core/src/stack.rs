@@ -0,0 +1,285 @@
+//! Versions of the TDX host stack, checked against a compatibility matrix
+//! of the versions known to work together.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::check::Probe;
+use crate::checks::{self, TdxModuleRelease};
+use crate::error::{Result, TdxHostError};
+
+/// Site copy of the matrix, replacing the bundled one.
+pub const MATRIX_PATH: &str = "/etc/tdxhost/compat.toml";
+const BUNDLED_MATRIX: &str = include_str!("../compat/matrix.toml");
+
+/// A part of the stack whose version matters to running TDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    Kernel,
+    TdxModule,
+    Qemu,
+    Libvirt,
+    /// The Intel SGX DCAP quoting library
+    Dcap,
+}
+
+impl Component {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Component::Kernel => "kernel",
+            Component::TdxModule => "TDX module",
+            Component::Qemu => "QEMU",
+            Component::Libvirt => "libvirt",
+            Component::Dcap => "DCAP",
+        }
+    }
+}
+
+/// A component and its version, if it is installed.
+#[derive(Debug, Clone, Serialize)]
+pub struct Installed {
+    pub component: Component,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// A comparison against a version, e.g. ">=6.16".
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Requirement {
+    comparison: Comparison,
+    version: Vec<u32>,
+    text: String,
+}
+
+/// The leading numbers of a version, e.g. [1, 21, 100, 3] for
+/// "1.21.100.3-jammy1" and [6, 8, 0] for "6.8.0-1014-intel".
+fn version_parts(version: &str) -> Option<Vec<u32>> {
+    // skip the epoch
+    let version = version.rsplit(':').next()?;
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    version[..end]
+        .split('.')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse().ok())
+        .collect::<Option<Vec<u32>>>()
+        .filter(|parts| !parts.is_empty())
+}
+
+/// Compare versions, missing trailing numbers counting as 0.
+fn compare(a: &[u32], b: &[u32]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let part = |v: &[u32]| v.get(i).copied().unwrap_or(0);
+            part(a).cmp(&part(b))
+        })
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+impl TryFrom<String> for Requirement {
+    type Error = String;
+
+    fn try_from(text: String) -> std::result::Result<Self, String> {
+        let (comparison, version) = [
+            (">=", Comparison::GreaterOrEqual),
+            ("<=", Comparison::LessOrEqual),
+            (">", Comparison::Greater),
+            ("<", Comparison::Less),
+            ("=", Comparison::Equal),
+        ]
+        .into_iter()
+        .find_map(|(op, comparison)| Some((comparison, text.trim().strip_prefix(op)?)))
+        .ok_or_else(|| format!("{:?} does not start with <, <=, =, >= or >", text))?;
+        let version =
+            version_parts(version.trim()).ok_or_else(|| format!("{:?} has no version", text))?;
+        Ok(Self {
+            comparison,
+            version,
+            text,
+        })
+    }
+}
+
+impl From<Requirement> for String {
+    fn from(requirement: Requirement) -> Self {
+        requirement.text
+    }
+}
+
+impl std::fmt::Display for Requirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Requirement {
+    /// Whether `version` satisfies the requirement, if it can be parsed.
+    pub fn matches(&self, version: &str) -> Option<bool> {
+        let ordering = compare(&version_parts(version)?, &self.version);
+        Some(match self.comparison {
+            Comparison::Less => ordering.is_lt(),
+            Comparison::LessOrEqual => ordering.is_le(),
+            Comparison::Equal => ordering.is_eq(),
+            Comparison::GreaterOrEqual => ordering.is_ge(),
+            Comparison::Greater => ordering.is_gt(),
+        })
+    }
+}
+
+/// Versions that must go together: when the installed components satisfy
+/// `when`, the installed ones of `require` must satisfy theirs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    #[serde(default)]
+    pub when: BTreeMap<Component, Requirement>,
+    pub require: BTreeMap<Component, Requirement>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Matrix {
+    #[serde(rename = "rule", default)]
+    pub rules: Vec<Rule>,
+}
+
+/// An installed component not satisfying a rule.
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub component: Component,
+    pub version: String,
+    pub requirement: Requirement,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StackReport {
+    pub components: Vec<Installed>,
+    pub violations: Vec<Violation>,
+}
+
+impl Matrix {
+    /// The matrix shipped with tdxhost.
+    pub fn bundled() -> Self {
+        toml::from_str(BUNDLED_MATRIX).expect("the bundled compatibility matrix is valid")
+    }
+
+    /// The matrix in `MATRIX_PATH`, or the bundled one if there is none.
+    pub fn load() -> Result<Self> {
+        let path = Path::new(MATRIX_PATH);
+        if !path.exists() {
+            return Ok(Self::bundled());
+        }
+        Self::load_from(path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| TdxHostError::io(path, e))?;
+        toml::from_str(&contents)
+            .map_err(|e| TdxHostError::io(path, std::io::Error::new(ErrorKind::InvalidData, e)))
+    }
+
+    /// The rules the installed versions break. Components that are not
+    /// installed, or whose version cannot be parsed, satisfy every rule.
+    pub fn evaluate(&self, installed: &[Installed]) -> Vec<Violation> {
+        let version = |component: &Component| {
+            installed
+                .iter()
+                .find(|i| i.component == *component)
+                .and_then(|i| i.version.as_deref())
+        };
+
+        let mut violations = vec![];
+        for rule in &self.rules {
+            let applies = rule.when.iter().all(|(component, requirement)| {
+                version(component).and_then(|v| requirement.matches(v)) == Some(true)
+            });
+            if !applies {
+                continue;
+            }
+            for (component, requirement) in &rule.require {
+                let Some(version) = version(component) else {
+                    continue;
+                };
+                if requirement.matches(version) == Some(false) {
+                    violations.push(Violation {
+                        component: *component,
+                        version: version.to_string(),
+                        requirement: requirement.clone(),
+                        reason: rule.reason.clone(),
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Version of libvirt, from virsh or libvirtd.
+fn libvirt_version(probe: &Probe) -> Option<String> {
+    // "10.0.0", or "libvirtd (libvirt) 10.0.0"
+    let output = probe
+        .output("virsh", &["--version"])
+        .or_else(|_| probe.output("libvirtd", &["--version"]))
+        .ok()?;
+    let output = String::from_utf8_lossy(&output);
+    output.split_whitespace().last().map(str::to_owned)
+}
+
+/// The installed versions of the stack's components.
+pub fn collect(probe: &Probe) -> Vec<Installed> {
+    let kernel = probe
+        .read_file("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|r| r.trim().to_string());
+    let tdx_module = TdxModuleRelease::detect(probe).map(|r| {
+        let (major, minor, update) = r.release();
+        format!("{}.{}.{}", major, minor, update)
+    });
+    // e.g. "QEMU emulator version 10.1.0 (qemu-kvm-10.1.0-1.el10)"
+    let qemu = checks::qemu_binary(probe).and_then(|(_, line)| {
+        let mut words = line.split_whitespace();
+        words.find(|w| *w == "version")?;
+        words.next().map(str::to_owned)
+    });
+
+    [
+        (Component::Kernel, kernel),
+        (Component::TdxModule, tdx_module),
+        (Component::Qemu, qemu),
+        (Component::Libvirt, libvirt_version(probe)),
+        (
+            Component::Dcap,
+            checks::package_version(probe, "libsgx-dcap-ql"),
+        ),
+    ]
+    .into_iter()
+    .map(|(component, version)| Installed { component, version })
+    .collect()
+}
+
+/// Collect the installed versions and evaluate them against `matrix`.
+pub fn verify(probe: &Probe, matrix: &Matrix) -> StackReport {
+    let components = collect(probe);
+    let violations = matrix.evaluate(&components);
+    StackReport {
+        components,
+        violations,
+    }
+}
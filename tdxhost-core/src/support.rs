@@ -0,0 +1,139 @@
+//! Support bundles: what a TDX support case needs to know about the host,
+//! gathered into files.
+
+use serde::Serialize;
+
+use crate::check::Probe;
+use crate::checks::{self, DCAP_PACKAGES, QCNL_CONF};
+use crate::registry::Registry;
+use crate::report::Report;
+use crate::{cpuid, info, msr, topology};
+
+/// Files copied into the bundle as they are.
+const FILES: [&str; 6] = [
+    "/etc/os-release",
+    "/proc/cmdline",
+    "/proc/cpuinfo",
+    QCNL_CONF,
+    "/etc/qgs.conf",
+    "/etc/mpa_registration.conf",
+];
+/// Modules whose parameters are collected.
+const MODULES: [&str; 2] = ["kvm", "kvm_intel"];
+/// Packages of the TDX stack besides DCAP, by their Debian or RPM name.
+const PACKAGES: [&str; 10] = [
+    "qemu-system-x86",
+    "qemu-kvm",
+    "libvirt-daemon",
+    "ovmf",
+    "edk2-ovmf",
+    "sgx-aesm-service",
+    "libsgx-ae-qe3",
+    "libsgx-ae-tdqe",
+    "sgx-ra-service",
+    "mpa_registration_tool",
+];
+/// Kernel messages of the journal worth keeping, lowercase.
+const JOURNAL_KEYWORDS: [&str; 6] = ["tdx", "tme", "sgx", "kvm", "seam", "keyid"];
+
+/// Files of a support bundle, by their path inside it.
+pub struct Bundle {
+    pub files: Vec<(String, Vec<u8>)>,
+    /// What could not be collected and why, also in "missing.txt"
+    pub missing: Vec<String>,
+}
+
+impl Bundle {
+    fn add(&mut self, name: &str, contents: impl Into<Vec<u8>>) {
+        self.files.push((name.to_string(), contents.into()));
+    }
+
+    fn add_json(&mut self, name: &str, value: &impl Serialize) {
+        match serde_json::to_vec_pretty(value) {
+            Ok(json) => self.add(name, json),
+            Err(e) => self.missing.push(format!("{}: {}", name, e)),
+        }
+    }
+}
+
+/// Gather the bundle. What cannot be collected is listed in "missing.txt"
+/// rather than failing.
+pub fn collect(probe: &Probe) -> Bundle {
+    let mut bundle = Bundle {
+        files: vec![],
+        missing: vec![],
+    };
+
+    let report = Report::new(probe, Registry::with_plugins(probe).evaluate(probe));
+    bundle.add_json("report.json", &report);
+    bundle.add_json("info.json", &info::collect(probe));
+    bundle.add_json("topology.json", &topology::collect(probe));
+    bundle.add_json("cpuid.json", &cpuid::dump(probe, true));
+    let cpus = probe.online_cpus().unwrap_or_else(|_| vec![0]);
+    match msr::dump(probe, &cpus, true) {
+        Ok(readings) => bundle.add_json("msr.json", &readings),
+        Err(e) => bundle.missing.push(format!("msr.json: {}", e)),
+    }
+
+    match probe.kernel_log() {
+        Ok(log) => bundle.add("dmesg.txt", log.as_bytes()),
+        Err(e) => bundle.missing.push(format!("dmesg.txt: {}", e)),
+    }
+    match probe.kernel_journal() {
+        Ok(journal) => {
+            let excerpt: String = journal
+                .lines()
+                .filter(|l| {
+                    let l = l.to_lowercase();
+                    JOURNAL_KEYWORDS.iter().any(|k| l.contains(k))
+                })
+                .map(|l| format!("{}\n", l))
+                .collect();
+            bundle.add("journal-kernel.txt", excerpt);
+        }
+        Err(e) => bundle.missing.push(format!("journal-kernel.txt: {}", e)),
+    }
+
+    for path in FILES {
+        match probe.read_bytes(path) {
+            Ok(contents) => bundle.add(path.trim_start_matches('/'), contents),
+            Err(e) => bundle.missing.push(format!("{}: {}", path, e)),
+        }
+    }
+    let release = probe
+        .read_file("/proc/sys/kernel/osrelease")
+        .map(|r| r.trim().to_string())
+        .unwrap_or_default();
+    match checks::kernel_config(probe, &release) {
+        Some((_, config)) => bundle.add("kernel-config.txt", config),
+        None => bundle
+            .missing
+            .push(format!("kernel-config.txt: no config of {}", release)),
+    }
+
+    let mut parameters = String::new();
+    for module in MODULES {
+        let dir = format!("/sys/module/{}/parameters", module);
+        for name in probe.read_dir(&dir).unwrap_or_default() {
+            if let Ok(value) = probe.read_file(&format!("{}/{}", dir, name)) {
+                parameters += &format!("{}.{}={}\n", module, name, value.trim());
+            }
+        }
+    }
+    bundle.add("module-parameters.txt", parameters);
+
+    let packages: String = DCAP_PACKAGES
+        .iter()
+        .map(|(package, _)| *package)
+        .chain(PACKAGES)
+        .filter_map(|package| {
+            let version = checks::package_version(probe, package)?;
+            Some(format!("{} {}\n", package, version))
+        })
+        .collect();
+    bundle.add("packages.txt", packages);
+
+    let missing: String = bundle.missing.iter().map(|m| format!("{}\n", m)).collect();
+    bundle.add("missing.txt", missing);
+    bundle
+}
@@ -0,0 +1,277 @@
+//! The TDs running on the host, found through their VMM processes.
+
+use serde::Serialize;
+
+use crate::check::Probe;
+
+/// Per-VM directories of KVM in debugfs, named "<pid>-<fd>".
+const KVM_DEBUGFS: &str = "/sys/kernel/debug/kvm";
+/// Root of the misc cgroup controller, which accounts TDX KeyIDs.
+const MISC_CGROUP: &str = "/sys/fs/cgroup";
+/// Arguments marking a VMM process as running a TD: the QEMU object and
+/// the cloud-hypervisor platform option.
+const TD_MARKERS: [&str; 2] = ["tdx-guest", "tdx=on"];
+
+/// A TD and the process running it.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningTd {
+    pub pid: u32,
+    /// Name given to the VMM, e.g. by libvirt
+    pub name: Option<String>,
+    pub uuid: Option<String>,
+    /// Executable of the VMM, e.g. "qemu-system-x86_64"
+    pub vmm: String,
+    /// Guest memory as configured
+    pub memory_mib: Option<u64>,
+    /// Private KeyIDs of the TD, each TD has one
+    pub keyids: u32,
+}
+
+/// How the host's memory encryption KeyIDs are split and used.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyIdUsage {
+    /// KeyIDs for MKTME, besides KeyID 0 of TME
+    pub mktme: Option<u64>,
+    /// KeyIDs reserved for TDX
+    pub tdx: Option<u64>,
+    /// TDX KeyIDs the kernel keeps for the TDX module's own metadata
+    pub global: u64,
+    /// TDX KeyIDs left for TDs, each TD using one
+    pub td_capacity: Option<u64>,
+    /// TDX KeyIDs running TDs use
+    pub in_use: u64,
+    /// How many more TDs the KeyIDs allow
+    pub free: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TdList {
+    pub tds: Vec<RunningTd>,
+    /// TDX KeyIDs in use, by the misc cgroup or else counted from `tds`
+    pub keyids_used: u64,
+    /// TDX KeyIDs available to TDs
+    pub keyids_total: Option<u64>,
+}
+
+/// Value of a key of a flat-keyed cgroup file, e.g. "tdx 63".
+fn cgroup_value(probe: &Probe, file: &str, key: &str) -> Option<u64> {
+    let contents = probe.read_file(&format!("{}/{}", MISC_CGROUP, file)).ok()?;
+    contents.lines().find_map(|l| {
+        let (k, v) = l.split_once(' ')?;
+        (k == key).then(|| v.trim().parse().ok())?
+    })
+}
+
+/// Value of an option in QEMU's or cloud-hypervisor's syntax, e.g. "4G"
+/// for `-m 4G`, `-m size=4G,slots=2` or `--memory size=4G`.
+fn option_value<'a>(args: &'a [&str], names: &[&str], key: &str) -> Option<&'a str> {
+    let value = args
+        .windows(2)
+        .find(|pair| names.contains(&pair[0]))
+        .map(|pair| pair[1])?;
+    // the first part may be the value without its key
+    let first = value.split(',').next()?;
+    if !first.contains('=') {
+        return Some(first);
+    }
+    value
+        .split(',')
+        .find_map(|part| part.strip_prefix(key)?.strip_prefix('='))
+}
+
+/// A memory size in MiB, given in MiB unless suffixed, e.g. "4G" or "4096".
+fn memory_mib(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let (number, kib) = match size.char_indices().last()? {
+        (i, 'K' | 'k') => (&size[..i], 1),
+        (i, 'M' | 'm') => (&size[..i], 1 << 10),
+        (i, 'G' | 'g') => (&size[..i], 1 << 20),
+        (i, 'T' | 't') => (&size[..i], 1 << 30),
+        _ => (size, 1 << 10),
+    };
+    let number: u64 = number.parse().ok()?;
+    Some((number * kib) >> 10)
+}
+
+/// The TD run by a process, if its command line creates one.
+fn running_td(probe: &Probe, pid: u32) -> Option<RunningTd> {
+    let cmdline = probe.read_file(&format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<&str> = cmdline.split('\0').filter(|a| !a.is_empty()).collect();
+    if !args
+        .iter()
+        .any(|a| TD_MARKERS.iter().any(|m| a.contains(m)))
+    {
+        return None;
+    }
+
+    let vmm = args.first()?.rsplit('/').next()?.to_string();
+    Some(RunningTd {
+        pid,
+        name: option_value(&args, &["-name"], "guest").map(str::to_owned),
+        uuid: option_value(&args, &["-uuid"], "uuid").map(str::to_owned),
+        vmm,
+        memory_mib: option_value(&args, &["-m", "--memory"], "size").and_then(memory_mib),
+        keyids: 1,
+    })
+}
+
+/// The TDs running on the host.
+///
+/// Processes are taken for TDs by their command lines. When KVM's debugfs
+/// can be read, only the processes that hold a VM are listed.
+fn running_tds(probe: &Probe) -> Vec<RunningTd> {
+    let vm_pids: Option<Vec<u32>> = probe.read_dir(KVM_DEBUGFS).ok().map(|vms| {
+        vms.iter()
+            .filter_map(|vm| vm.split_once('-')?.0.parse().ok())
+            .collect()
+    });
+    probe
+        .read_dir("/proc")
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|pid| pid.parse().ok())
+        .filter(|pid| vm_pids.as_ref().is_none_or(|vms| vms.contains(pid)))
+        .filter_map(|pid| running_td(probe, pid))
+        .collect()
+}
+
+/// The TDs running on the host and the KeyIDs they use.
+pub fn list(probe: &Probe) -> TdList {
+    let tds = running_tds(probe);
+
+    let keyids = keyid_usage(probe, &tds);
+    TdList {
+        keyids_used: keyids.in_use,
+        keyids_total: keyids.td_capacity,
+        tds,
+    }
+}
+
+/// The KeyID split of the host and the KeyIDs of `tds`, or of the misc
+/// cgroup when it accounts them.
+fn keyid_usage(probe: &Probe, tds: &[RunningTd]) -> KeyIdUsage {
+    // IA32_MKTME_KEYID_PARTITIONING: MKTME KeyIDs in the lower half, TDX
+    // KeyIDs in the upper one
+    let partitioning = probe.read_msr(0x87).ok();
+    let tdx = partitioning.map(|v| v >> 32);
+    // the kernel allots the TDX module one global KeyID
+    let global = 1;
+    let td_capacity = cgroup_value(probe, "misc.capacity", "tdx")
+        .or_else(|| tdx.map(|tdx| tdx.saturating_sub(global)));
+    let in_use = cgroup_value(probe, "misc.current", "tdx")
+        .unwrap_or_else(|| tds.iter().map(|td| u64::from(td.keyids)).sum());
+    KeyIdUsage {
+        mktme: partitioning.map(|v| v & 0xffff_ffff),
+        tdx,
+        global,
+        td_capacity,
+        in_use,
+        free: td_capacity.map(|capacity| capacity.saturating_sub(in_use)),
+    }
+}
+
+/// The KeyID split of the host and how many KeyIDs running TDs use.
+pub fn keyids(probe: &Probe) -> KeyIdUsage {
+    keyid_usage(probe, &running_tds(probe))
+}
+
+/// What is known about one TD.
+#[derive(Debug, Clone, Serialize)]
+pub struct TdDetails {
+    #[serde(flatten)]
+    pub td: RunningTd,
+    /// vCPUs KVM created, or else the configured ones
+    pub vcpus: Option<u32>,
+    /// Guest memory KVM mapped for the TD, by its page size stats
+    pub mapped_kib: Option<u64>,
+    /// Resident shared memory of the VMM, e.g. a memfd memory backend
+    pub shmem_kib: Option<u64>,
+    /// Resident anonymous memory of the VMM
+    pub anon_kib: Option<u64>,
+    /// The libvirt domain, when libvirt started the TD
+    pub libvirt_domain: Option<String>,
+}
+
+/// KVM's debugfs directory of the VM a process holds.
+fn vm_debugfs(probe: &Probe, pid: u32) -> Option<String> {
+    let prefix = format!("{}-", pid);
+    let vm = probe
+        .read_dir(KVM_DEBUGFS)
+        .ok()?
+        .into_iter()
+        .find(|vm| vm.starts_with(&prefix))?;
+    Some(format!("{}/{}", KVM_DEBUGFS, vm))
+}
+
+/// A "Key:   123 kB" value of /proc/<pid>/status.
+fn status_kib(status: &str, key: &str) -> Option<u64> {
+    status.lines().find_map(|l| {
+        let value = l.strip_prefix(key)?.strip_prefix(':')?;
+        value.trim().trim_end_matches("kB").trim().parse().ok()
+    })
+}
+
+/// Look up a running TD by the PID of its VMM or its UUID and inspect it.
+pub fn inspect(probe: &Probe, target: &str) -> Option<TdDetails> {
+    let td = running_tds(probe).into_iter().find(|td| {
+        target.parse() == Ok(td.pid)
+            || td
+                .uuid
+                .as_deref()
+                .is_some_and(|uuid| uuid.eq_ignore_ascii_case(target))
+    })?;
+    let cmdline = probe
+        .read_file(&format!("/proc/{}/cmdline", td.pid))
+        .unwrap_or_default();
+    let args: Vec<&str> = cmdline.split('\0').filter(|a| !a.is_empty()).collect();
+
+    let vm = vm_debugfs(probe, td.pid);
+    let vcpus = vm
+        .as_ref()
+        .and_then(|vm| probe.read_dir(vm).ok())
+        .map(|entries| entries.iter().filter(|e| e.starts_with("vcpu")).count() as u32)
+        .filter(|&vcpus| vcpus > 0)
+        .or_else(|| {
+            option_value(&args, &["-smp", "--cpus"], "cpus")?
+                .parse()
+                .ok()
+        });
+    // the pages KVM mapped into the TD's EPT, by size
+    let mapped_kib = vm.as_ref().and_then(|vm| {
+        [
+            ("pages_4k", 4),
+            ("pages_2m", 2 << 10),
+            ("pages_1g", 1 << 20),
+        ]
+        .into_iter()
+        .map(|(stat, kib)| {
+            let pages: u64 = probe
+                .read_file(&format!("{}/{}", vm, stat))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            Some(pages * kib)
+        })
+        .sum()
+    });
+    let status = probe
+        .read_file(&format!("/proc/{}/status", td.pid))
+        .unwrap_or_default();
+    // libvirt keeps the domain's state in a per-domain directory, e.g.
+    // /var/lib/libvirt/qemu/domain-3-td1/
+    let libvirt_domain = args
+        .iter()
+        .any(|a| a.contains("/libvirt/qemu/domain-"))
+        .then(|| td.name.clone())
+        .flatten();
+
+    Some(TdDetails {
+        vcpus,
+        mapped_kib,
+        shmem_kib: status_kib(&status, "RssShmem"),
+        anon_kib: status_kib(&status, "RssAnon"),
+        libvirt_domain,
+        td,
+    })
+}
@@ -0,0 +1,155 @@
+//! Sockets, cores and NUMA nodes of the host, and the memory encryption
+//! state of each socket.
+
+use serde::Serialize;
+
+use crate::check::{self, Probe};
+
+const NODES: &str = "/sys/devices/system/node";
+
+/// A physical package (socket).
+#[derive(Debug, Clone, Serialize)]
+pub struct Package {
+    pub id: u16,
+    /// Online logical CPUs
+    pub cpus: Vec<u16>,
+    /// Physical cores, each running one or more of `cpus`
+    pub cores: usize,
+    pub nodes: Vec<u16>,
+    /// Whether BIOS activated TME, by IA32_TME_ACTIVATE
+    pub tme_enabled: Option<bool>,
+    /// Whether BIOS enabled TDX, by the SEAM range being valid in
+    /// IA32_SEAMRR_PHYS_MASK
+    pub tdx_enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Node {
+    pub id: u16,
+    pub cpus: Vec<u16>,
+    pub memory_mib: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Topology {
+    pub packages: Vec<Package>,
+    pub nodes: Vec<Node>,
+}
+
+/// A CPU list in the kernel's format, e.g. "0-3,8".
+pub fn format_cpu_list(cpus: &[u16]) -> String {
+    let mut ranges: Vec<(u16, u16)> = vec![];
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == cpu => *last = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| {
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{}-{}", first, last)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// "Node 0 MemTotal:  131072 kB" of a node's meminfo, in MiB.
+fn node_memory_mib(probe: &Probe, node: u16) -> Option<u64> {
+    let meminfo = probe
+        .read_file(&format!("{}/node{}/meminfo", NODES, node))
+        .ok()?;
+    meminfo.lines().find_map(|l| {
+        let (_, value) = l.split_once("MemTotal:")?;
+        let kib: u64 = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+        Some(kib >> 10)
+    })
+}
+
+fn nodes(probe: &Probe) -> Vec<Node> {
+    let online = probe
+        .read_file(&format!("{}/online", NODES))
+        .ok()
+        .and_then(|online| check::parse_cpu_list(&online))
+        .unwrap_or_default();
+    online
+        .into_iter()
+        .map(|id| Node {
+            id,
+            cpus: probe
+                .read_file(&format!("{}/node{}/cpulist", NODES, id))
+                .ok()
+                .and_then(|cpus| check::parse_cpu_list(&cpus))
+                .unwrap_or_default(),
+            memory_mib: node_memory_mib(probe, id),
+        })
+        .collect()
+}
+
+/// Whether `bit` of `msr` is set on `cpu`, if the MSR can be read.
+fn msr_bit(probe: &Probe, cpu: u16, msr: u32, bit: u32) -> Option<bool> {
+    probe
+        .read_msr_on(cpu, msr)
+        .ok()
+        .map(|value| value & (1 << bit) != 0)
+}
+
+/// The topology of the online CPUs. CPUs of an unknown package are taken
+/// for package 0, as by the MSR checks.
+pub fn collect(probe: &Probe) -> Topology {
+    let nodes = nodes(probe);
+    let cpus = probe.online_cpus().unwrap_or_default();
+
+    let mut packages: Vec<Package> = vec![];
+    for cpu in cpus {
+        let id = probe.package(cpu).unwrap_or(0);
+        let package = match packages.iter_mut().position(|p| p.id == id) {
+            Some(i) => &mut packages[i],
+            None => {
+                packages.push(Package {
+                    id,
+                    cpus: vec![],
+                    cores: 0,
+                    nodes: vec![],
+                    // the first CPU of the package speaks for it
+                    tme_enabled: msr_bit(probe, cpu, 0x982, 1),
+                    tdx_enabled: msr_bit(probe, cpu, 0x1401, 11),
+                });
+                packages.last_mut().unwrap()
+            }
+        };
+        package.cpus.push(cpu);
+        if let Some(node) = nodes.iter().find(|n| n.cpus.contains(&cpu)) {
+            if !package.nodes.contains(&node.id) {
+                package.nodes.push(node.id);
+            }
+        }
+    }
+    packages.sort_by_key(|p| p.id);
+
+    for package in &mut packages {
+        let mut cores: Vec<String> = package
+            .cpus
+            .iter()
+            .map(|cpu| {
+                probe
+                    .read_file(&format!(
+                        "/sys/devices/system/cpu/cpu{}/topology/core_id",
+                        cpu
+                    ))
+                    .map(|id| id.trim().to_string())
+                    // without the core, count every CPU as one
+                    .unwrap_or_else(|_| format!("cpu{}", cpu))
+            })
+            .collect();
+        cores.sort_unstable();
+        cores.dedup();
+        package.cores = cores.len();
+    }
+
+    Topology { packages, nodes }
+}
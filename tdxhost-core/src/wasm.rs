@@ -0,0 +1,257 @@
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::check::Probe;
+use crate::error::{Result, TdxHostError};
+
+/// Instructions a module may execute per call, so a buggy plugin cannot
+/// hang the run.
+const FUEL: u64 = 1_000_000_000;
+const MEMORY_LIMIT: usize = 64 << 20;
+
+/// Files a module may read through `tdxhost.read_file`, after symlinks are
+/// resolved: these exact files and the files below the directories ending
+/// in '/'.
+const READABLE: [&str; 9] = [
+    "/proc/cpuinfo",
+    "/proc/cmdline",
+    "/proc/sys/kernel/osrelease",
+    "/usr/lib/os-release",
+    "/etc/os-release",
+    "/sys/firmware/tdx/",
+    "/sys/module/kvm/parameters/",
+    "/sys/module/kvm_intel/parameters/",
+    "/sys/devices/system/cpu/",
+];
+/// Bytes of a file `tdxhost.read_file` reads at most.
+const READ_LIMIT: u64 = 1 << 20;
+
+struct Host<'a> {
+    limits: StoreLimits,
+    probe: &'a Probe,
+}
+
+fn memory(caller: &Caller<'_, Host>) -> Option<Memory> {
+    caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+/// The range of `len` bytes at `ptr` in the module's memory, unless it is
+/// not inside it.
+fn range(memory: &Memory, caller: &Caller<'_, Host>, ptr: i32, len: i32) -> Option<(usize, usize)> {
+    let (ptr, len) = (usize::try_from(ptr).ok()?, usize::try_from(len).ok()?);
+    (ptr.checked_add(len)? <= memory.data(caller).len()).then_some((ptr, len))
+}
+
+/// The path `path` resolves to, if it is in `READABLE`. The resolved path is
+/// what gets opened, so a symlink swapped in afterwards is not followed.
+fn readable(path: &str) -> Option<PathBuf> {
+    let path = std::fs::canonicalize(path).ok()?;
+    let resolved = path.to_str()?;
+    READABLE
+        .iter()
+        .any(|allowed| match allowed.strip_suffix('/') {
+            Some(dir) => resolved
+                .strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/')),
+            None => resolved == *allowed,
+        })
+        .then_some(path)
+}
+
+/// `read_file(path_ptr, path_len, buf_ptr, buf_len) -> len`
+///
+/// Copies up to buf_len bytes of the file into the buffer and returns the
+/// length of the file, up to `READ_LIMIT`, or -1 if it is not readable.
+fn read_file(
+    mut caller: Caller<'_, Host>,
+    path_ptr: i32,
+    path_len: i32,
+    buf_ptr: i32,
+    buf_len: i32,
+) -> i32 {
+    let Some(memory) = memory(&caller) else {
+        return -1;
+    };
+
+    let (Some((path_ptr, path_len)), Some((buf_ptr, buf_len))) = (
+        range(&memory, &caller, path_ptr, path_len),
+        range(&memory, &caller, buf_ptr, buf_len),
+    ) else {
+        return -1;
+    };
+
+    let mut path = vec![0; path_len];
+    if memory.read(&caller, path_ptr, &mut path).is_err() {
+        return -1;
+    }
+    let Ok(path) = String::from_utf8(path) else {
+        return -1;
+    };
+    let Some(path) = readable(&path) else {
+        return -1;
+    };
+
+    let mut contents = vec![];
+    let read = OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)
+        .and_then(|file| file.take(READ_LIMIT).read_to_end(&mut contents));
+    if read.is_err() {
+        return -1;
+    }
+    let n = contents.len().min(buf_len);
+    if memory.write(&mut caller, buf_ptr, &contents[..n]).is_err() {
+        return -1;
+    }
+
+    contents.len() as i32
+}
+
+/// `read_msr(index, out_ptr) -> status`
+///
+/// Stores the little endian value of the MSR on CPU 0, as the checks read
+/// it, at out_ptr and returns 0, or -1 if it cannot be read.
+fn read_msr(mut caller: Caller<'_, Host>, index: i32, out_ptr: i32) -> i32 {
+    let Some(memory) = memory(&caller) else {
+        return -1;
+    };
+    let Ok(value) = caller.data().probe.read_msr(index as u32) else {
+        return -1;
+    };
+
+    match memory.write(&mut caller, out_ptr as usize, &value.to_le_bytes()) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    }
+}
+
+/// Call the `command` export of the module at `path` in a fresh instance.
+///
+/// The export takes no arguments and returns `(ptr << 32) | len` of a JSON
+/// response in the module's memory. Modules can only import the functions
+/// above, from the `tdxhost` namespace, reading MSRs through `probe`.
+pub fn call(path: &Path, command: &str, probe: &Probe) -> Result<Vec<u8>> {
+    let wasm = std::fs::read(path).map_err(|e| TdxHostError::plugin(path, e))?;
+
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+    let module = Module::new(&engine, &wasm)
+        .map_err(|e| TdxHostError::plugin(path, format!("invalid module: {}", e)))?;
+
+    let mut store = Store::new(
+        &engine,
+        Host {
+            limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+            probe,
+        },
+    );
+    store.limiter(|host| &mut host.limits);
+    store.set_fuel(FUEL).unwrap();
+
+    let mut linker = <Linker<Host>>::new(&engine);
+    linker.func_wrap("tdxhost", "read_file", read_file).unwrap();
+    linker.func_wrap("tdxhost", "read_msr", read_msr).unwrap();
+
+    let fail = |e: wasmi::Error| TdxHostError::plugin(path, e);
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .and_then(|pre| pre.start(&mut store))
+        .map_err(fail)?;
+    let packed = instance
+        .get_typed_func::<(), i64>(&store, command)
+        .and_then(|f| f.call(&mut store, ()))
+        .map_err(fail)?;
+
+    let (ptr, len) = (
+        (packed >> 32) as u32 as usize,
+        (packed & 0xffff_ffff) as usize,
+    );
+    let memory = instance
+        .get_memory(&store, "memory")
+        .ok_or_else(|| TdxHostError::plugin(path, "does not export its memory"))?;
+    if ptr.saturating_add(len) > memory.data(&store).len() {
+        return Err(TdxHostError::plugin(
+            path,
+            "invalid response: outside of its memory",
+        ));
+    }
+    let mut response = vec![0; len];
+    memory
+        .read(&store, ptr, &mut response)
+        .map_err(|e| TdxHostError::plugin(path, format!("invalid response: {}", e)))?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Fixture;
+    use std::fs;
+
+    /// Answers `run` with the status of reading MSR 0x982 and
+    /// /etc/shadow, followed by the MSR's value.
+    const MODULE: &str = r#"(module
+  (import "tdxhost" "read_file" (func $read_file (param i32 i32 i32 i32) (result i32)))
+  (import "tdxhost" "read_msr" (func $read_msr (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 64) "/etc/shadow")
+  (func (export "run") (result i64)
+    (i32.store (i32.const 0) (call $read_msr (i32.const 0x982) (i32.const 8)))
+    (i32.store (i32.const 4)
+      (call $read_file (i32.const 64) (i32.const 11) (i32.const 128) (i32.const 64)))
+    (i64.const 16)))"#;
+
+    #[test]
+    fn readable_paths() {
+        assert_eq!(readable("/proc/cmdline"), Some("/proc/cmdline".into()));
+        assert_eq!(
+            readable("/proc/sys/kernel/../kernel/osrelease"),
+            Some("/proc/sys/kernel/osrelease".into())
+        );
+        assert_eq!(readable("/etc/shadow"), None);
+        assert_eq!(readable("/sys/devices/system/cpu"), None);
+        assert_eq!(readable("/proc/cpuinfo/../self/environ"), None);
+
+        // the target of a symlink decides, and is what gets opened
+        let dir = std::env::temp_dir().join(format!("tdxhost-wasm-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        std::os::unix::fs::symlink("/etc/shadow", dir.join("shadow")).unwrap();
+        std::os::unix::fs::symlink("/proc/cmdline", dir.join("cmdline")).unwrap();
+        assert_eq!(readable(dir.join("shadow").to_str().unwrap()), None);
+        assert_eq!(
+            readable(dir.join("cmdline").to_str().unwrap()),
+            Some("/proc/cmdline".into())
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn host_functions() {
+        let dir = std::env::temp_dir().join(format!("tdxhost-module-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir(&dir).unwrap();
+        let path = dir.join("example.wasm");
+        fs::write(&path, wat::parse_str(MODULE).unwrap()).unwrap();
+        let probe = Probe::new(Fixture {
+            msrs: [("0x982".to_string(), 0x1234)].into(),
+            ..Default::default()
+        });
+
+        let response = call(&path, "run", &probe).unwrap();
+        assert_eq!(response[..4], 0i32.to_le_bytes());
+        assert_eq!(response[4..8], (-1i32).to_le_bytes());
+        assert_eq!(response[8..], 0x1234u64.to_le_bytes());
+
+        assert!(call(&path, "describe", &probe).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,27 @@
+/* SPDX-License-Identifier: Apache-2.0 */
+
+#ifndef TDXHOST_H
+#define TDXHOST_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Run every check and return the report as a NUL-terminated JSON string in
+ * the format of `tdxhost ok --format json`, or NULL if the checks could not
+ * be run, e.g. /etc/tdxhost/config.toml is invalid. The checks use the
+ * settings of that file. Messages follow LC_ALL, LC_MESSAGES or LANG.
+ *
+ * The string must be released with tdxhost_string_free().
+ */
+char *tdxhost_run_checks(void);
+
+/* Release a string returned by this library. NULL is ignored. */
+void tdxhost_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TDXHOST_H */
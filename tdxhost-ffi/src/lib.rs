@@ -0,0 +1,46 @@
+//! C ABI for embedding the tdxhost checks, see include/tdxhost.h.
+
+use std::ffi::{c_char, CString};
+use std::panic::catch_unwind;
+use std::ptr::null_mut;
+
+use tdxhost_core::check::Probe;
+use tdxhost_core::config::Config;
+use tdxhost_core::registry::Registry;
+use tdxhost_core::report::Report;
+
+/// Run every check and return the report as a JSON string in the format of
+/// `tdxhost ok --format json`, or NULL if the checks could not be run, e.g.
+/// /etc/tdxhost/config.toml is invalid.
+///
+/// The string must be released with `tdxhost_string_free`.
+#[no_mangle]
+pub extern "C" fn tdxhost_run_checks() -> *mut c_char {
+    // unwinding into C is undefined behavior
+    let json = catch_unwind(|| {
+        // the site's settings, as `tdxhost ok` reads them
+        let probe = Probe::host(Config::load().ok()?);
+        let report = Report::new(&probe, Registry::with_plugins(&probe).evaluate(&probe));
+        serde_json::to_string(&report).ok()
+    });
+
+    match json {
+        Ok(Some(json)) => CString::new(json)
+            .map(CString::into_raw)
+            .unwrap_or(null_mut()),
+        _ => null_mut(),
+    }
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by this library that was not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tdxhost_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}